@@ -39,6 +39,7 @@ await new Promise(async (resolve, reject) => {
     .addVestedBalance({ storageDepositLimit, gasLimit }, 
       Address,
       OriginalBalance,
+      null,
     ).signAndSend(alice, ({ status, events, dispatchError }) => {    
       console.log("Status:", status?.type);
       if(events?.length > 0) {
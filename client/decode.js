@@ -18,6 +18,87 @@ export function decode(data) {
         "Error::VestedBalanceScheduleNotFound",
         "Error::VestedBalanceScheduleNotLiquid",
         "Error::VestedBalanceScheduleNotRequested",
+        "Error::VestedBalanceScheduleNotApproved",
+        "Error::PayoutNotFound",
+        "Error::VestedBalanceScheduleNotDisputable",
+        "Error::VestedBalanceScheduleNotDisputed",
+        "Error::VestedBalanceScheduleNotApprovedOrTransferred",
+        "Error::InvalidRevertTarget",
+        "Error::GuardianNotSet",
+        "Error::InvalidSignature",
+        "Error::InvalidNonce",
+        "Error::ReentrantCall",
+        "Error::VestedBalancesExist",
+        "Error::InvalidScheduleCount",
+        "Error::InvalidRequestTtl",
+        "Error::InvalidFeeBps",
+        "Error::VestedBalanceScheduleNotForfeitable",
+        "Error::DonationsDisabled",
+        "Error::InvalidDonationTarget",
+        "Error::ScheduleLimitReached",
+        "Error::InsufficientNativeEscrow",
+        "Error::NativeTransferFailed",
+        "Error::FundingMismatch",
+        "Error::VersionMismatch",
+        "Error::RequestNotFound",
+        "Error::RecipientPinned",
+        "Error::MemoTooLong",
+        "Error::RequestNotVerified",
+        "Error::KycNotVerified",
+        "Error::CategoryLockedUp",
+        "Error::TransferNotAllowed",
+        "Error::ComplianceHookFailed",
+        "Error::RecipientCallbackFailed",
+        "Error::TemplateNotFound",
+        "Error::InvalidTemplate",
+        "Error::VestedBalanceScheduleNotFrozen",
+        "Error::DuplicateTxHash",
+        "Error::ArchivedBalanceNotFound",
+        "Error::VestedBalanceHasActivity",
+        "Error::NoPendingOwner",
+        "Error::OwnerRotationNotReady",
+        "Error::BackupNotSet",
+        "Error::BeneficiaryStillActive",
+        "Error::InvalidRecoveryConfig",
+        "Error::RecoveryNotReady",
+        "Error::VestingSuspended",
+        "Error::VestingNotSuspended",
+        "Error::AmendmentNotFound",
+        "Error::AssetSupplyExceeded",
+        "Error::InvalidAssetDecimals",
+        "Error::BelowMinimumBalance",
+        "Error::VestedBalanceNotSettled",
+        "Error::MetadataTooLong",
+        "Error::SchedulePledged",
+        "Error::ScheduleNotPledged",
+        "Error::CollateralNotAccepted",
+        "Error::EscrowApprovalRequired",
+        "Error::InvalidDisputeSplit",
+        "Error::ScheduleNumberTooHigh",
+        "Error::RequestNotExecutable",
+        "Error::InvalidVestingCurve",
+        "Error::PriceGateClosed",
+        "Error::InvalidPriceGate",
+        "Error::GovernorRequired",
+        "Error::InvalidEnforcementBackend",
+        "Error::PalletVestingFailed",
+        "Error::XcmTransferFailed",
+        "Error::XcmTransferRequired",
+        "Error::NotesTooLong",
+        "Error::RecipientCommitmentNotFound",
+        "Error::RecipientCommitmentMismatch",
+        "Error::DisputeOutcomeNotAllowed",
+        "Error::EscrowConsentRequired",
+        "Error::ApprovalsMissing",
+        "Error::GuardianChangeNotReady",
+        "Error::PriceAlreadyReported",
+        "Error::DuplicateScheduleNumber",
+        "Error::AssetSupplyUnavailable",
+        "Error::AssetMetadataUnavailable",
+        "Error::InvalidCallbackGasLimit",
+        "Error::NativeVestingDisabled",
+        "Error::PayoutNotRevertible",
+        "Error::GuardianIsOwner",
     ]; 
 
     const successMap = [
@@ -27,6 +108,94 @@ export function decode(data) {
         "Success::VestedBalanceScheduleThawed",
         "Success::VestedBalanceScheduleRequested",
         "Success::VestedBalanceScheduleApproved",
+        "Success::PayoutInstructed",
+        "Success::PayoutAcknowledged",
+        "Success::VestedBalanceScheduleUnlockTimeSet",
+        "Success::DueUnlocksProcessed",
+        "Success::KeeperBountySet",
+        "Success::KeeperPoolFunded",
+        "Success::KeeperBountyPaid",
+        "Success::ArbiterSet",
+        "Success::VestedBalanceScheduleDisputed",
+        "Success::DisputeResolved",
+        "Success::GuardianSet",
+        "Success::ApprovalRevertSigned",
+        "Success::ApprovalReverted",
+        "Success::OperationAlreadyApplied",
+        "Success::VestedBalanceRepaired",
+        "Success::AssetIdSet",
+        "Success::DefaultScheduleCountSet",
+        "Success::RequestTtlSet",
+        "Success::FeeBpsSet",
+        "Success::ExpiredRequestsProcessed",
+        "Success::VestedBalanceScheduleForfeited",
+        "Success::DonationsEnabledSet",
+        "Success::VestedBalanceScheduleDonated",
+        "Success::AssetKindSet",
+        "Success::NativeFunded",
+        "Success::NativePayoutTransferred",
+        "Success::TransferRequestRejected",
+        "Success::TransferRequestCancelled",
+        "Success::RecipientPinningSet",
+        "Success::RecipientUnlocked",
+        "Success::ComplianceOperatorSet",
+        "Success::TreasurySet",
+        "Success::TransferRequestVerified",
+        "Success::ApprovalTiersSet",
+        "Success::ApproversSet",
+        "Success::ApprovalRecorded",
+        "Success::KycRequiredSet",
+        "Success::KycVerifiedSet",
+        "Success::ComplianceProfileSet",
+        "Success::CategoryLockupSet",
+        "Success::ComplianceHookSet",
+        "Success::TransferCallbackSet",
+        "Success::CallbackGasLimitSet",
+        "Success::TemplateCreated",
+        "Success::VestedBalanceRestored",
+        "Success::ArchivedBalancePurged",
+        "Success::DepositPerByteSet",
+        "Success::OwnerRotationDelaySet",
+        "Success::OwnerProposed",
+        "Success::OwnerRotationCancelled",
+        "Success::OwnerChanged",
+        "Success::BackupSet",
+        "Success::ActivityRecorded",
+        "Success::PositionClaimedByBackup",
+        "Success::RecoveryGuardiansSet",
+        "Success::RecoveryVoteRecorded",
+        "Success::RecoveryCancelled",
+        "Success::PositionRecovered",
+        "Success::VestingSuspended",
+        "Success::VestingResumed",
+        "Success::GrantRescheduled",
+        "Success::AmendmentProposed",
+        "Success::AmendmentApplied",
+        "Success::AssetSupplySet",
+        "Success::AssetMetadataSet",
+        "Success::ProgramMetadataSet",
+        "Success::CollateralLocked",
+        "Success::CollateralReleased",
+        "Success::GrantEscrowSet",
+        "Success::EscrowApproved",
+        "Success::PayoutRetried",
+        "Success::AlreadyTransferred",
+        "Success::StrictModeSet",
+        "Success::MaxScheduleNumberSet",
+        "Success::DeadManPeriodSet",
+        "Success::AbandonedRequestExecuted",
+        "Success::PriceOracleSet",
+        "Success::PriceReported",
+        "Success::PriceGateSet",
+        "Success::GovernorSet",
+        "Success::SnapshotTaken",
+        "Success::DelegateSet",
+        "Success::EnforcementBackendSet",
+        "Success::NotesSet",
+        "Success::ViewersSet",
+        "Success::PrivateSet",
+        "Success::RecipientCommitted",
+        "Success::GuardianChangeProposed",
     ];     
 
     if (payload[2] === 0) {
@@ -36,4 +205,18 @@ export function decode(data) {
     } else {
         throw new Error("Invalid event payload");
     }    
-}
\ No newline at end of file
+}
+// Stable numeric error code of an event, zero on success. The code is the
+// last field of the event so it can be read without decoding the status.
+export function errorCode(data) {
+    if (!data || data.length < 2) {
+        throw new Error("Invalid event data format");
+    }
+
+    const bytes = data[1].toU8a();
+    if (bytes.length < 2) {
+        throw new Error("Invalid event payload");
+    }
+
+    return bytes[bytes.length - 2] | (bytes[bytes.length - 1] << 8);
+}
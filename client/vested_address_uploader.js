@@ -5,6 +5,7 @@ import fs from "fs";
 import 'dotenv/config';
 import { decode } from "./decode.js";
 import csv from "csv-parser";
+import { blake2AsHex } from "@polkadot/util-crypto";
 
 /// Blockchain
 const WS_ENDPOINT = process.env.WS_ENDPOINT;
@@ -56,6 +57,7 @@ stream.on("data", async (data) => {
         .addVestedBalance({ storageDepositLimit, gasLimit }, 
           String(data.Address ?? "").trim(),
           String(data.Balance).replace(/"/g, "").trim(),
+          blake2AsHex(`${data.Address}:${data.Balance}`, 256), // op id, re-running the file is a no-op
         ).signAndSend(owner, ({ status, events, dispatchError }) => {   
           console.log("Status:", status?.type);
           if(events?.length > 0) {
@@ -1,9 +1,184 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
-#[ink::contract]
+use ink::env::{DefaultEnvironment, Environment};
+use ink::prelude::vec::Vec;
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+/// Chain extension to the runtime, the runtime must route the function ids to pallet-vesting, pallet-xcm and
+/// pallet-assets
+#[ink::chain_extension]
+pub trait VestingExtension {
+    type ErrorCode = ExtensionError;
+
+    /// Transfer `locked` from the contract to `target` under a new pallet-vesting schedule unlocking
+    /// `per_block` from `starting_block`, like `Vesting::vested_transfer`
+    #[ink(extension = 0x5601)]
+    fn vested_transfer(target: AccountId, locked: Balance, per_block: Balance, starting_block: BlockNumber);
+
+    /// Reserve transfer `amount` of the asset, the native balance when none, from the contract to an
+    /// account on a sibling parachain, like `PolkadotXcm::limited_reserve_transfer_assets`. Returns the
+    /// hash of the sent XCM message
+    #[ink(extension = 0x5602)]
+    fn reserve_transfer(asset_id: Option<u128>, amount: Balance, destination: XcmDestination) -> [u8; 32];
+
+    /// Supply of the asset, the balance of `holder` when given and the total supply otherwise, like
+    /// `Assets::balance` and `Assets::total_supply`
+    #[ink(extension = 0x5603)]
+    fn asset_supply(asset_id: u128, holder: Option<AccountId>) -> Balance;
+
+    /// Name, symbol, decimals and minimum balance of the asset, like `Assets::metadata` and the asset details
+    #[ink(extension = 0x5604)]
+    fn asset_metadata(asset_id: u128) -> AssetDetails;
+}
+
+/// Name, symbol, decimals and minimum balance of an asset, as answered by the chain extension
+pub type AssetDetails = (Vec<u8>, Vec<u8>, u8, Balance);
+
+/// Account on a sibling parachain, the runtime turns it into the multi-location
+/// `(1, X2(Parachain(para_id), AccountId32 { network: None, id: account }))`
+#[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct XcmDestination {
+    /// Sibling parachain id
+    pub para_id: u32,
+    /// 32-byte account on the parachain
+    pub account: [u8; 32],
+}
+
+/// Errors of the chain extension, the status code is the discriminant
+#[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ExtensionError {
+    /// Amount below the minimum vested transfer of the runtime
+    AmountLow = 1,
+    /// Recipient already has the maximum number of vesting schedules
+    AtMaxVestingSchedules = 2,
+    /// Contract balance does not cover the transfer
+    InsufficientBalance = 3,
+    /// XCM message could not be sent to the destination
+    XcmSendFailed = 4,
+    /// Call rejected by the runtime or unknown status code
+    Other = 5,
+}
+
+impl ink::env::chain_extension::FromStatusCode for ExtensionError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            1 => Err(Self::AmountLow),
+            2 => Err(Self::AtMaxVestingSchedules),
+            3 => Err(Self::InsufficientBalance),
+            4 => Err(Self::XcmSendFailed),
+            _ => Err(Self::Other),
+        }
+    }
+}
+
+/// Default environment with the vesting chain extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum VestingEnvironment {}
+
+impl Environment for VestingEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = AccountId;
+    type Balance = Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = VestingExtension;
+}
+
+pub use self::vesting::{ScheduleStatus, Vesting, VestingRef};
+
+#[ink::contract(env = crate::VestingEnvironment)]
 mod vesting {
 
+    use crate::{ExtensionError, XcmDestination};
+
     use ink::prelude::vec::Vec;
+    use ink::env::hash::Blake2x256;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::storage::{Lazy, Mapping};
+    use scale::Encode;
+
+    /// Maximum length of a transfer request memo
+    pub const MAX_MEMO_LENGTH: usize = 128;
+
+    /// Default gas limit of a transfer callback
+    pub const DEFAULT_CALLBACK_GAS_LIMIT: u64 = 5_000_000_000;
+
+    /// Maximum length of the program name, URI and label
+    pub const MAX_METADATA_LENGTH: usize = 256;
+
+    /// Maximum length of the owner notes of a vested balance
+    pub const MAX_NOTES_LENGTH: usize = 256;
+
+    /// Number of oracle price observations kept for price-gated unlocks
+    pub const MAX_PRICE_OBSERVATIONS: usize = 64;
+
+    /// Default highest schedule number of a vested balance
+    pub const DEFAULT_MAX_SCHEDULE_NUMBER: u16 = 100;
+
+    /// Number of vested balances per state chunk
+    pub const STATE_CHUNK_SIZE: usize = 16;
+
+    /// Version of the storage layout, bumped on incompatible layout changes.
+    /// Version 2 widened schedule numbers and counts from u8 to u16.
+    /// Version 3 moved the holders index into its own storage cell.
+    /// Version 4 added owner notes to the vested balances.
+    /// Version 5 moved the holders index back inline and the schedule particulars into their own mapping.
+    /// Version 6 timed the schedule history entries and recorded unlocks and requests in it.
+    /// Version 7 kept every archived record of an address instead of the last one.
+    pub const STORAGE_VERSION: u16 = 7;
+
+    /// Serde helpers for 32-byte ids (AccountId, Hash) as `0x` hex strings, ink! types have no serde support
+    #[cfg(feature = "std")]
+    mod serde_hex {
+        use ink::prelude::{format, string::String};
+        use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<T: AsRef<[u8]>, S: Serializer>(id: &T, serializer: S) -> Result<S::Ok, S::Error> {
+            let hex: String = id.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+            serializer.serialize_str(&format!("0x{}", hex))
+        }
+
+        pub fn deserialize<'de, T: From<[u8; 32]>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+            let hex = String::deserialize(deserializer)?;
+            let hex = hex.strip_prefix("0x").unwrap_or(&hex);
+            let mut bytes = [0u8; 32];
+            if hex.len() != 64 || !hex.is_ascii() {
+                return Err(D::Error::custom("expected 32 bytes of hex"));
+            }
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(D::Error::custom)?;
+            }
+            Ok(T::from(bytes))
+        }
+
+        pub mod option {
+            use serde::{Deserialize, Deserializer, Serializer};
+
+            pub fn serialize<T: AsRef<[u8]>, S: Serializer>(id: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+                match id {
+                    Some(id) => super::serialize(id, serializer),
+                    None => serializer.serialize_none(),
+                }
+            }
+
+            pub fn deserialize<'de, T: From<[u8; 32]>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+                #[derive(Deserialize)]
+                struct Id<T: From<[u8; 32]>>(#[serde(with = "super")] T);
+
+                Ok(Option::<Id<T>>::deserialize(deserializer)?.map(|Id(id)| id))
+            }
+        }
+    }
 
     /// Error Messages
     #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Eq)]
@@ -12,22 +187,290 @@ mod vesting {
         /// Bad origin error, e.g., wrong caller
         BadOrigin,
         /// There is already an existing vested balance for that address
-        VestedBalanceAlreadyExist,
+        VestedBalanceAlreadyExist { address: AccountId },
         /// Vested balance not found
-        VestedBalanceNotFound,
+        VestedBalanceNotFound { address: AccountId },
         /// Vested balance schedule not found
-        VestedBalanceScheduleNotFound,
-        /// Vested balance schedule not liquid
-        VestedBalanceScheduleNotLiquid,
-        /// Vested balance schedule not requested
-        VestedBalanceScheduleNotRequested,
+        VestedBalanceScheduleNotFound { address: AccountId, schedule_number: u16 },
+        /// Vested balance schedule not liquid, with its current status
+        VestedBalanceScheduleNotLiquid { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Vested balance schedule not requested, with its current status
+        VestedBalanceScheduleNotRequested { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Vested balance schedule not approved for payout, with its current status
+        VestedBalanceScheduleNotApproved { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Payout instruction not found
+        PayoutNotFound { payout_id: u64 },
+        /// Vested balance schedule cannot be disputed in its current status
+        VestedBalanceScheduleNotDisputable { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Vested balance schedule not disputed, with its current status
+        VestedBalanceScheduleNotDisputed { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Vested balance schedule not transferred or approved, with its current status
+        VestedBalanceScheduleNotApprovedOrTransferred { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// The status cannot be the target of a reverted approval
+        InvalidRevertTarget { status: ScheduleStatus },
+        /// No guardian is set to co-sign
+        GuardianNotSet,
+        /// The signature does not match the signer
+        InvalidSignature,
+        /// The nonce of a signed request is not the expected one
+        InvalidNonce { expected: u64 },
+        /// The contract was re-entered while a guarded message was executing
+        ReentrantCall,
+        /// Vested balances already exist
+        VestedBalancesExist,
+        /// The number of schedules must be greater than zero
+        InvalidScheduleCount { total_vested_schedule: u16 },
+        /// The request time-to-live must be greater than zero
+        InvalidRequestTtl,
+        /// The fee cannot exceed 10000 basis points
+        InvalidFeeBps { fee_bps: u16 },
+        /// Vested balance schedule cannot be forfeited in its current status
+        VestedBalanceScheduleNotForfeitable { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Donations between beneficiaries are not allowed by the owner
+        DonationsDisabled,
+        /// A schedule cannot be donated to its own beneficiary
+        InvalidDonationTarget { address: AccountId },
+        /// The vested balance cannot hold more schedules
+        ScheduleLimitReached { address: AccountId },
+        /// The native escrow cannot cover the transfer
+        InsufficientNativeEscrow { available: Balance, required: Balance },
+        /// The native transfer to the recipient failed
+        NativeTransferFailed { recipient: AccountId },
+        /// The transferred value does not match the funding expected by the grant
+        FundingMismatch { expected: Balance, transferred: Balance },
+        /// The vested balance changed since the expected version
+        VersionMismatch { address: AccountId, expected: u32, current: u32 },
+        /// Transfer request not found or no longer pending
+        RequestNotFound { request_id: u64 },
+        /// Transfers of the vested balance are pinned to another recipient
+        RecipientPinned { address: AccountId, recipient: AccountId },
+        /// The request memo is longer than the allowed length
+        MemoTooLong { length: u32 },
+        /// The transfer request was not verified by the compliance operator
+        RequestNotVerified { request_id: u64 },
+        /// The beneficiary is not KYC verified
+        KycNotVerified { address: AccountId },
+        /// The investor category of the beneficiary is locked up until a time
+        CategoryLockedUp { category: u8, until: Timestamp },
+        /// The compliance hook does not allow the transfer
+        TransferNotAllowed { beneficiary: AccountId, recipient: AccountId },
+        /// The call to the compliance hook failed
+        ComplianceHookFailed { hook: AccountId },
+        /// The transfer callback of the recipient failed, the transfer itself went through
+        RecipientCallbackFailed { recipient: AccountId },
+        /// Vesting template not found
+        TemplateNotFound { template_id: u32 },
+        /// The template weights are empty, all zero or too many
+        InvalidTemplate,
+        /// Vested balance schedule is not frozen
+        VestedBalanceScheduleNotFrozen { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// The tx-hash was already recorded for another schedule
+        DuplicateTxHash { address: AccountId, schedule_number: u16 },
+        /// Archived vested balance not found
+        ArchivedBalanceNotFound { address: AccountId },
+        /// The vested balance has outstanding requests or transfers, removal must be forced
+        VestedBalanceHasActivity { address: AccountId, requested_balance: u128, transferred_balance: u128 },
+        /// No owner rotation is pending
+        NoPendingOwner,
+        /// The proposed owner cannot take over before the rotation delay has passed
+        OwnerRotationNotReady { activates_at: Timestamp },
+        /// The beneficiary has no backup address or the caller is not the backup
+        BackupNotSet { address: AccountId },
+        /// The beneficiary has been active within the inactivity period
+        BeneficiaryStillActive { inactive_at: Timestamp },
+        /// The recovery threshold must be between one and the number of recovery guardians
+        InvalidRecoveryConfig,
+        /// No recovery was agreed on yet or its challenge period has not passed
+        RecoveryNotReady { executable_at: Option<Timestamp> },
+        /// Vesting of the address is suspended
+        VestingSuspended { address: AccountId },
+        /// Vesting of the address is not suspended
+        VestingNotSuspended { address: AccountId },
+        /// Amendment not found or already applied
+        AmendmentNotFound { amendment_id: u64 },
+        /// Grants would oblige more than the asset supply
+        AssetSupplyExceeded { supply: Balance, obligations: Balance },
+        /// Asset decimals out of range
+        InvalidAssetDecimals { decimals: u8 },
+        /// Schedule amount below the asset's minimum balance
+        BelowMinimumBalance { schedule_number: u16, amount: Balance, min_balance: Balance },
+        /// Archived balance still has requested or disputed schedules
+        VestedBalanceNotSettled { address: AccountId, requested_balance: u128, disputed_balance: u128 },
+        /// Program metadata too long
+        MetadataTooLong { length: u32 },
+        /// Schedule is pledged as collateral
+        SchedulePledged { address: AccountId, schedule_number: u16, lender: AccountId },
+        /// Schedule is not pledged to the caller
+        ScheduleNotPledged { address: AccountId, schedule_number: u16 },
+        /// Lender contract did not accept the pledge
+        CollateralNotAccepted { lender: AccountId },
+        /// Transfer request needs the approval of the grant's escrow
+        EscrowApprovalRequired { request_id: u64, escrow: AccountId },
+        /// Split of a disputed schedule must return a part of its amount
+        InvalidDisputeSplit { address: AccountId, schedule_number: u16, amount: u128 },
+        /// Schedule number above the configured maximum
+        ScheduleNumberTooHigh { schedule_number: u16, max_schedule_number: u16 },
+        /// Request cannot be executed without the owner yet, none if the dead-man switch is off
+        RequestNotExecutable { executable_at: Option<BlockNumber> },
+        /// Vesting curve without steps, out of time order or with invalid parameters
+        InvalidVestingCurve,
+        /// Schedule waits for the oracle price to stay above its threshold
+        PriceGateClosed { address: AccountId, schedule_number: u16 },
+        /// Price condition needs between one and the kept number of observations and a non-empty TWAP window
+        InvalidPriceGate,
+        /// Thaws are controlled by the governance contract
+        GovernorRequired { governor: AccountId },
+        /// The pallet-vesting backend needs native vesting and at least one unlock block
+        InvalidEnforcementBackend,
+        /// Pallet-vesting did not create the schedule of the recipient
+        PalletVestingFailed { recipient: AccountId, error: ExtensionError },
+        /// XCM reserve transfer to the sibling parachain failed
+        XcmTransferFailed { para_id: u32, error: ExtensionError },
+        /// Schedule requested to an XCM destination is settled by an approval, not by a payout
+        XcmTransferRequired { address: AccountId, schedule_number: u16 },
+        /// Owner notes of a vested balance above the maximum length
+        NotesTooLong { length: u32 },
+        /// No recipient committed for the schedule
+        RecipientCommitmentNotFound { address: AccountId, schedule_number: u16 },
+        /// Revealed recipient and salt do not hash to the committed recipient
+        RecipientCommitmentMismatch { address: AccountId, schedule_number: u16 },
+        /// Dispute outcome would return a settled schedule to the beneficiary
+        DisputeOutcomeNotAllowed { address: AccountId, schedule_number: u16, status: ScheduleStatus },
+        /// Removing, replacing or raising the threshold of a grant escrow needs the escrow itself
+        EscrowConsentRequired { escrow: AccountId },
+        /// Request needs more approvals by its amount tier than the dead-man switch stands in for
+        ApprovalsMissing { address: AccountId, schedule_number: u16 },
+        /// Guardian change proposed by the owner alone cannot be applied yet
+        GuardianChangeNotReady { activates_at: Timestamp },
+        /// The oracle already reported a price at this time
+        PriceAlreadyReported { reported_at: Timestamp },
+        /// Schedule number given more than once
+        DuplicateScheduleNumber { schedule_number: u16 },
+        /// The asset supply could not be read from pallet-assets
+        AssetSupplyUnavailable { error: ExtensionError },
+        /// The asset metadata could not be read from pallet-assets
+        AssetMetadataUnavailable { error: ExtensionError },
+        /// Callback gas limit of zero, it would let callbacks use all the remaining gas
+        InvalidCallbackGasLimit,
+        /// The program does not vest the native balance
+        NativeVestingDisabled,
+        /// Transferred schedule was paid out by the contract, natively or over XCM, and cannot be reverted
+        PayoutNotRevertible { address: AccountId, schedule_number: u16 },
+        /// The guardian and the owner would be the same account
+        GuardianIsOwner,
+    }
+
+    impl Error {
+        /// Stable numeric code of the error, new variants get the next free code and
+        /// existing codes never change
+        pub fn error_code(&self) -> u16 {
+            match self {
+                Error::BadOrigin => 1,
+                Error::VestedBalanceAlreadyExist { .. } => 2,
+                Error::VestedBalanceNotFound { .. } => 3,
+                Error::VestedBalanceScheduleNotFound { .. } => 4,
+                Error::VestedBalanceScheduleNotLiquid { .. } => 5,
+                Error::VestedBalanceScheduleNotRequested { .. } => 6,
+                Error::VestedBalanceScheduleNotApproved { .. } => 7,
+                Error::PayoutNotFound { .. } => 8,
+                Error::VestedBalanceScheduleNotDisputable { .. } => 9,
+                Error::VestedBalanceScheduleNotDisputed { .. } => 10,
+                Error::VestedBalanceScheduleNotApprovedOrTransferred { .. } => 11,
+                Error::InvalidRevertTarget { .. } => 12,
+                Error::GuardianNotSet => 13,
+                Error::InvalidSignature => 14,
+                Error::InvalidNonce { .. } => 15,
+                Error::ReentrantCall => 16,
+                Error::VestedBalancesExist => 17,
+                Error::InvalidScheduleCount { .. } => 18,
+                Error::InvalidRequestTtl => 19,
+                Error::InvalidFeeBps { .. } => 20,
+                Error::VestedBalanceScheduleNotForfeitable { .. } => 21,
+                Error::DonationsDisabled => 22,
+                Error::InvalidDonationTarget { .. } => 23,
+                Error::ScheduleLimitReached { .. } => 24,
+                Error::InsufficientNativeEscrow { .. } => 25,
+                Error::NativeTransferFailed { .. } => 26,
+                Error::FundingMismatch { .. } => 27,
+                Error::VersionMismatch { .. } => 28,
+                Error::RequestNotFound { .. } => 29,
+                Error::RecipientPinned { .. } => 30,
+                Error::MemoTooLong { .. } => 31,
+                Error::RequestNotVerified { .. } => 32,
+                Error::KycNotVerified { .. } => 33,
+                Error::CategoryLockedUp { .. } => 34,
+                Error::TransferNotAllowed { .. } => 35,
+                Error::ComplianceHookFailed { .. } => 36,
+                Error::RecipientCallbackFailed { .. } => 37,
+                Error::TemplateNotFound { .. } => 38,
+                Error::InvalidTemplate => 39,
+                Error::VestedBalanceScheduleNotFrozen { .. } => 40,
+                Error::DuplicateTxHash { .. } => 41,
+                Error::ArchivedBalanceNotFound { .. } => 42,
+                Error::VestedBalanceHasActivity { .. } => 43,
+                Error::NoPendingOwner => 44,
+                Error::OwnerRotationNotReady { .. } => 45,
+                Error::BackupNotSet { .. } => 46,
+                Error::BeneficiaryStillActive { .. } => 47,
+                Error::InvalidRecoveryConfig => 48,
+                Error::RecoveryNotReady { .. } => 49,
+                Error::VestingSuspended { .. } => 50,
+                Error::VestingNotSuspended { .. } => 51,
+                Error::AmendmentNotFound { .. } => 52,
+                Error::AssetSupplyExceeded { .. } => 53,
+                Error::InvalidAssetDecimals { .. } => 54,
+                Error::BelowMinimumBalance { .. } => 55,
+                Error::VestedBalanceNotSettled { .. } => 56,
+                Error::MetadataTooLong { .. } => 57,
+                Error::SchedulePledged { .. } => 58,
+                Error::ScheduleNotPledged { .. } => 59,
+                Error::CollateralNotAccepted { .. } => 60,
+                Error::EscrowApprovalRequired { .. } => 61,
+                Error::InvalidDisputeSplit { .. } => 62,
+                Error::ScheduleNumberTooHigh { .. } => 63,
+                Error::RequestNotExecutable { .. } => 64,
+                Error::InvalidVestingCurve => 65,
+                Error::PriceGateClosed { .. } => 66,
+                Error::InvalidPriceGate => 67,
+                Error::GovernorRequired { .. } => 68,
+                Error::InvalidEnforcementBackend => 69,
+                Error::PalletVestingFailed { .. } => 70,
+                Error::XcmTransferFailed { .. } => 71,
+                Error::XcmTransferRequired { .. } => 72,
+                Error::NotesTooLong { .. } => 73,
+                Error::RecipientCommitmentNotFound { .. } => 74,
+                Error::RecipientCommitmentMismatch { .. } => 75,
+                Error::DisputeOutcomeNotAllowed { .. } => 76,
+                Error::EscrowConsentRequired { .. } => 77,
+                Error::ApprovalsMissing { .. } => 78,
+                Error::GuardianChangeNotReady { .. } => 79,
+                Error::PriceAlreadyReported { .. } => 80,
+                Error::DuplicateScheduleNumber { .. } => 81,
+                Error::AssetSupplyUnavailable { .. } => 82,
+                Error::AssetMetadataUnavailable { .. } => 83,
+                Error::InvalidCallbackGasLimit => 84,
+                Error::NativeVestingDisabled => 85,
+                Error::PayoutNotRevertible { .. } => 86,
+                Error::GuardianIsOwner => 87,
+            }
+        }
+
+        /// Outbound payout failures, queued for a retry when they happen
+        pub fn is_payout_failure(&self) -> bool {
+            matches!(self,
+                Error::InsufficientNativeEscrow { .. }
+                | Error::NativeTransferFailed { .. }
+                | Error::PalletVestingFailed { .. }
+                | Error::XcmTransferFailed { .. })
+        }
     }
 
     /// Success Messages
     #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Eq)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[allow(clippy::enum_variant_names)]
     pub enum Success {
-        /// Vesting setup successful
+        /// Vesting setup successful, no longer emitted, kept so the codes of the other successes hold
         VestingSetupSuccess,
         /// Success adding vested balance
         VestedBalanceAdded,
@@ -39,6 +482,182 @@ mod vesting {
         VestedBalanceScheduleRequested,
         /// Request for transfer successful
         VestedBalanceScheduleApproved,
+        /// Payout instruction issued
+        PayoutInstructed,
+        /// Payout settlement acknowledged
+        PayoutAcknowledged,
+        /// Vested balance schedule unlock time set
+        VestedBalanceScheduleUnlockTimeSet,
+        /// Due unlocks processed
+        DueUnlocksProcessed,
+        /// Keeper bounty set
+        KeeperBountySet,
+        /// Keeper pool funded
+        KeeperPoolFunded,
+        /// Keeper bounty paid
+        KeeperBountyPaid,
+        /// Arbiter set
+        ArbiterSet,
+        /// Vested balance schedule disputed
+        VestedBalanceScheduleDisputed,
+        /// Dispute resolved by the arbiter
+        DisputeResolved,
+        /// Guardian set
+        GuardianSet,
+        /// Approval revert signed, waiting for the co-signer
+        ApprovalRevertSigned,
+        /// Approval reverted
+        ApprovalReverted,
+        /// Operation id already applied, nothing was done
+        OperationAlreadyApplied,
+        /// Vested balance totals recalculated
+        VestedBalanceRepaired,
+        /// Asset id set
+        AssetIdSet,
+        /// Default schedule count set
+        DefaultScheduleCountSet,
+        /// Request time-to-live set
+        RequestTtlSet,
+        /// Fee set
+        FeeBpsSet,
+        /// Expired requests processed
+        ExpiredRequestsProcessed,
+        /// Vested balance schedule forfeited
+        VestedBalanceScheduleForfeited,
+        /// Donation policy set
+        DonationsEnabledSet,
+        /// Vested balance schedule donated
+        VestedBalanceScheduleDonated,
+        /// Asset kind set
+        AssetKindSet,
+        /// Native escrow funded
+        NativeFunded,
+        /// Vested balance schedule paid out in native tokens
+        NativePayoutTransferred,
+        /// Transfer request rejected by the owner
+        TransferRequestRejected,
+        /// Transfer request cancelled by the beneficiary
+        TransferRequestCancelled,
+        /// Recipient pinning policy set
+        RecipientPinningSet,
+        /// Pinned recipient unlocked
+        RecipientUnlocked,
+        /// Compliance operator set
+        ComplianceOperatorSet,
+        /// Treasury set
+        TreasurySet,
+        /// Transfer request verified by the compliance operator
+        TransferRequestVerified,
+        /// Approval tiers set
+        ApprovalTiersSet,
+        /// Approvers set
+        ApproversSet,
+        /// Approval recorded, more approvals are required by the amount tier
+        ApprovalRecorded,
+        /// KYC requirement set
+        KycRequiredSet,
+        /// KYC verification of a beneficiary set
+        KycVerifiedSet,
+        /// Compliance profile of a beneficiary set
+        ComplianceProfileSet,
+        /// Lockup of an investor category set
+        CategoryLockupSet,
+        /// Compliance hook set
+        ComplianceHookSet,
+        /// Transfer callback of a recipient set
+        TransferCallbackSet,
+        /// Gas limit of transfer callbacks set
+        CallbackGasLimitSet,
+        /// Vesting template created
+        TemplateCreated,
+        /// Archived vested balance restored
+        VestedBalanceRestored,
+        /// Archived vested balance purged
+        ArchivedBalancePurged,
+        /// Storage deposit per byte set
+        DepositPerByteSet,
+        /// Owner rotation delay set
+        OwnerRotationDelaySet,
+        /// New owner proposed
+        OwnerProposed,
+        /// Owner rotation cancelled
+        OwnerRotationCancelled,
+        /// Proposed owner took over
+        OwnerChanged,
+        /// Backup address set
+        BackupSet,
+        /// Beneficiary activity recorded
+        ActivityRecorded,
+        /// Position claimed by the backup address of an inactive beneficiary
+        PositionClaimedByBackup,
+        /// Recovery guardians set
+        RecoveryGuardiansSet,
+        /// Recovery vote recorded
+        RecoveryVoteRecorded,
+        /// Recovery cancelled by the beneficiary
+        RecoveryCancelled,
+        /// Position moved to the address agreed on by the recovery guardians
+        PositionRecovered,
+        /// Vesting of an address suspended
+        VestingSuspended,
+        /// Vesting of an address resumed
+        VestingResumed,
+        /// Unlock times of a grant changed
+        GrantRescheduled,
+        /// Grant amendment proposed, waiting for the beneficiary
+        AmendmentProposed,
+        /// Grant amendment applied
+        AmendmentApplied,
+        /// Asset supply check set
+        AssetSupplySet,
+        /// Asset metadata set or refreshed
+        AssetMetadataSet,
+        /// Program name, URI and label set
+        ProgramMetadataSet,
+        /// Schedules pledged as collateral
+        CollateralLocked,
+        /// Collateral released by the lender
+        CollateralReleased,
+        /// Grant escrow set
+        GrantEscrowSet,
+        /// Transfer request approved by the grant's escrow
+        EscrowApproved,
+        /// Failed payout retried and paid out
+        PayoutRetried,
+        /// Approval of a schedule that was already transferred, nothing changed
+        AlreadyTransferred,
+        /// Strict mode set
+        StrictModeSet,
+        /// Highest schedule number set
+        MaxScheduleNumberSet,
+        /// Dead-man switch period set
+        DeadManPeriodSet,
+        /// Pending request executed without the owner
+        AbandonedRequestExecuted,
+        /// Price oracle set
+        PriceOracleSet,
+        /// Price reported by the oracle
+        PriceReported,
+        /// Price condition of a schedule set
+        PriceGateSet,
+        /// Governor set
+        GovernorSet,
+        /// Voting weight snapshot taken
+        SnapshotTaken,
+        /// Voting weight delegated or undelegated
+        DelegateSet,
+        /// Enforcement backend set
+        EnforcementBackendSet,
+        /// Owner notes of a vested balance set
+        NotesSet,
+        /// Viewers set
+        ViewersSet,
+        /// Privacy of the bulk views turned on or off
+        PrivateSet,
+        /// Recipient of a liquid schedule committed
+        RecipientCommitted,
+        /// Guardian change proposed, it applies after the owner rotation delay
+        GuardianChangeProposed,
     }
 
     /// Vesting Status
@@ -55,33 +674,415 @@ mod vesting {
         #[ink(topic)]
         operator: AccountId,
         status: VestingStatus,
+        /// Stable error code of an error status, zero on success
+        error_code: u16,
     } 
 
-    /// Vested balance schedules
+    /// Payout Instruction Event, consumed by off-chain treasury payout bots
+    #[ink(event)]
+    pub struct PayoutInstruction {
+        #[ink(topic)]
+        payout_id: u64,
+        asset_id: u128,
+        amount: u128,
+        #[ink(topic)]
+        recipient: Recipient,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        schedule_id: Hash,
+    }
+
+    /// Transfer Requested Event, surfaces the request and its memo to the approver
+    #[ink(event)]
+    pub struct TransferRequested {
+        #[ink(topic)]
+        request_id: u64,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        schedule_number: u16,
+        schedule_id: Hash,
+        recipient: Recipient,
+        amount: u128,
+        memo: Vec<u8>,
+    }
+
+    /// Payout Failed Event, the schedule stays requested and the payout is queued for `retry_payout`
+    #[ink(event)]
+    pub struct PayoutFailed {
+        #[ink(topic)]
+        payout_id: u64,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        schedule_number: u16,
+        schedule_id: Hash,
+        #[ink(topic)]
+        recipient: AccountId,
+        amount: u128,
+        reason: Error,
+    }
+
+    /// Balance Removed Event, records what the removal took off the books
+    #[ink(event)]
+    pub struct BalanceRemoved {
+        #[ink(topic)]
+        address: AccountId,
+        /// Non-transferred balance forfeited by the removal
+        forfeited: u128,
+        /// Balance already transferred to the beneficiary
+        transferred: u128,
+        /// The removal overrode outstanding requests or transfers
+        forced: bool,
+    }
+
+    /// Program Metadata Changed Event, lets explorers refresh the program label
+    #[ink(event)]
+    pub struct ProgramMetadataChanged {
+        /// Program name
+        program_name: Vec<u8>,
+        /// Program URI
+        program_uri: Vec<u8>,
+        /// Program label, e.g. team, investors or advisors
+        label: Vec<u8>,
+    }
+
+    /// Storage Released Event, records the storage freed by purging a settled account
+    #[ink(event)]
+    pub struct StorageReleased {
+        #[ink(topic)]
+        address: AccountId,
+        /// Encoded bytes of the removed storage items
+        bytes: u32,
+        /// Storage deposit freed at the recorded deposit per byte
+        deposit: Balance,
+    }
+
+    /// Dispute Resolved Event, the arbiter decision on a disputed schedule
+    #[ink(event)]
+    pub struct DisputeResolved {
+        #[ink(topic)]
+        address: AccountId,
+        schedule_number: u16,
+        #[ink(topic)]
+        arbiter: AccountId,
+        outcome: DisputeOutcome,
+        /// Status of the schedule after the decision
+        status: ScheduleStatus,
+        /// Liquid schedule returned to the beneficiary by a split
+        returned_schedule_number: Option<u16>,
+    }
+
+    /// Owner Proposed Event, lets monitors catch an unexpected rotation while it can still be cancelled
+    #[ink(event)]
+    pub struct OwnerProposed {
+        #[ink(topic)]
+        current_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+        activates_at: Timestamp,
+    }
+
+    /// Position Migrated Event, a vested balance moved to another address
+    #[ink(event)]
+    pub struct PositionMigrated {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    /// Grant Rescheduled Event, each change is (schedule number, old unlock time, new unlock time)
+    #[ink(event)]
+    pub struct GrantRescheduled {
+        #[ink(topic)]
+        address: AccountId,
+        changes: Vec<(u16, Option<Timestamp>, Option<Timestamp>)>,
+    }
+
+    /// XCM Transfer Sent Event, the message hash is the settlement reference of the schedule
+    #[ink(event)]
+    pub struct XcmTransferSent {
+        #[ink(topic)]
+        beneficiary: AccountId,
+        schedule_number: u16,
+        destination: XcmDestination,
+        amount: u128,
+        message_hash: [u8; 32],
+    }
+
+    /// Delegate Changed Event, the delegate receiving the voting weight of a beneficiary, none for itself
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        from_delegate: Option<AccountId>,
+        #[ink(topic)]
+        to_delegate: Option<AccountId>,
+    }
+
+    /// Vested balance schedule status, the discriminant is the stored status code
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ScheduleStatus {
+        #[default]
+        Frozen = 0,
+        Liquid = 1,
+        Requested = 2,
+        Transferred = 3,
+        Approved = 4,
+        Disputed = 5,
+        Forfeited = 6,
+    }
+
+    impl ScheduleStatus {
+        /// All statuses, ordered by status code
+        pub const ALL: [ScheduleStatus; 7] = [
+            ScheduleStatus::Frozen,
+            ScheduleStatus::Liquid,
+            ScheduleStatus::Requested,
+            ScheduleStatus::Transferred,
+            ScheduleStatus::Approved,
+            ScheduleStatus::Disputed,
+            ScheduleStatus::Forfeited,
+        ];
+    }
+
+    /// Kind of the vested asset
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum AssetKind {
+        /// An asset identified by `asset_id`, paid out off-chain
+        #[default]
+        Asset,
+        /// The native balance of the chain, paid out from the contract escrow
+        Native,
+    }
+
+    /// Supply of the asset the grants are checked against
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum SupplyScope {
+        /// Total supply of the asset
+        Total,
+        /// Balance of the asset held by the owner
+        OwnerHeld,
+    }
+
+    /// Backend enforcing the lock of transferred native vesting
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum EnforcementBackend {
+        /// Transfers are paid out of the escrow unlocked
+        #[default]
+        Contract,
+        /// Transfers create a pallet-vesting schedule of the recipient through the chain extension,
+        /// unlocking over `unlock_blocks` blocks starting `start_delay` blocks after the approval
+        PalletVesting { unlock_blocks: BlockNumber, start_delay: BlockNumber },
+    }
+
+    /// Outcome of a dispute resolution
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum DisputeOutcome {
+        /// Restore the status the schedule had before the dispute
+        Uphold,
+        /// Return the schedule to liquid, clearing the recipient and the tx-hash. Not for transferred schedules
+        Reverse,
+        /// Return part of the schedule to the beneficiary as a new liquid schedule, the rest is upheld.
+        /// Not for transferred schedules
+        Split { returned: u128 },
+    }
+
+    /// Compact vested balance without the schedules, for listings
     #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VestedSummary {
+        /// The address that holds the vested balance
+        #[cfg_attr(feature = "std", serde(with = "serde_hex"))]
+        pub address: AccountId,
+        /// The original balance
+        pub original: u128,
+        /// The total frozen balance
+        pub frozen: u128,
+        /// The total liquid balance
+        pub liquid: u128,
+        /// The total requested balance
+        pub requested: u128,
+        /// The total transferred balance
+        pub transferred: u128,
+        /// Number of schedules
+        pub schedule_count: u16,
+        /// Version of the vested balance
+        pub version: u32,
+    }
+
+    /// Asset metadata cached from pallet-assets
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, Default, PartialEq, Eq)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
-    pub struct VestedBalanceSchedule {
-        /// Schedule number 1-100
-        pub schedule_number: u8,
-        /// Schedule balance
-        pub schedule_balance: u128,
-        /// Status (0-Frozen, 1-Liquid, 2-Requested, 3-Transferred)
-        pub status: u8,
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct AssetMetadata {
+        /// Asset name
+        pub name: Vec<u8>,
+        /// Asset symbol
+        pub symbol: Vec<u8>,
+        /// Decimals of the asset
+        pub decimals: u8,
+        /// Minimum balance of the asset, smaller non-zero schedules could not be paid out
+        pub min_balance: Balance,
+        /// Time of the last refresh
+        pub refreshed_at: Timestamp,
+    }
+
+    /// Raw amount with its decimal-adjusted parts, `whole.fraction` in asset units
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DecimalAmount {
+        /// Amount in the smallest unit of the asset
+        pub raw: u128,
+        /// Whole asset units
+        pub whole: u128,
+        /// Remainder in the smallest unit, `decimals` digits wide
+        pub fraction: u128,
+        /// Decimals of the asset
+        pub decimals: u8,
+    }
+
+    /// Schedule line of a position report
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct ScheduleReport {
+        /// Schedule number
+        pub schedule_number: u16,
+        /// Status of the schedule
+        pub status: ScheduleStatus,
+        /// Schedule amount
+        pub amount: DecimalAmount,
+        /// Time the schedule becomes liquid, none if thawed by the owner only
+        pub unlock_time: Option<Timestamp>,
         /// Transfer recipient
-        pub recipient_address: Option<AccountId>,
-        /// Particulars
-        pub particulars: Vec<u8>,
-    }    
+        #[cfg_attr(feature = "std", serde(with = "serde_hex::option"))]
+        pub recipient: Option<AccountId>,
+        /// Tx-hash of the settlement, once approved or transferred
+        pub settlement_hash: Option<Vec<u8>>,
+    }
 
-    /// Vested balances
+    /// Kind of a statement entry
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum StatementEntryKind {
+        /// Schedule became liquid
+        Unlock,
+        /// Transfer of the schedule requested
+        Request,
+        /// Transfer of the schedule settled
+        Settlement,
+    }
+
+    /// Line of an account statement
     #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
-    pub struct VestedBalance {
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct StatementEntry {
+        /// Kind of the entry
+        pub kind: StatementEntryKind,
+        /// Schedule number
+        pub schedule_number: u16,
+        /// Schedule amount
+        pub amount: u128,
+        /// Time of the entry
+        pub at: Timestamp,
+        /// Recipient of requests and settlements
+        pub recipient: Option<Recipient>,
+        /// Tx-hash of settlements
+        pub tx_hash: Vec<u8>,
+    }
+
+    /// Unlocks, requests and settlements of an account within a period, for tax reporting
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Statement {
+        /// The address of the statement
+        pub address: AccountId,
+        /// Start of the period, inclusive
+        pub from: Timestamp,
+        /// End of the period, inclusive
+        pub to: Timestamp,
+        /// Entries ordered by time
+        pub entries: Vec<StatementEntry>,
+        /// Total unlocked in the period
+        pub unlocked: u128,
+        /// Total requested in the period
+        pub requested: u128,
+        /// Total settled in the period
+        pub settled: u128,
+    }
+
+    /// Position of a beneficiary, ready to be rendered by explorers and wallets
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PositionReport {
         /// The address that holds the vested balance
+        #[cfg_attr(feature = "std", serde(with = "serde_hex"))]
         pub address: AccountId,
-        /// Vested schedules
-        pub vested_balance_schedules: Vec<VestedBalanceSchedule>,
         /// The original balance
+        pub original: DecimalAmount,
+        /// Balance not yet transferred or forfeited
+        pub remaining: DecimalAmount,
+        /// The total transferred balance
+        pub transferred: DecimalAmount,
+        /// Unlocks are suspended
+        pub suspended: bool,
+        /// Version of the vested balance
+        pub version: u32,
+        /// Schedules in schedule number order
+        pub schedules: Vec<ScheduleReport>,
+    }
+
+    /// Outcome of a thaw if it ran now
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct SimulationResult {
+        /// Number of schedules that would become liquid
+        pub schedule_count: u32,
+        /// Total amount that would become liquid
+        pub total_amount: u128,
+        /// Amount that would become liquid per beneficiary
+        pub beneficiaries: Vec<(AccountId, u128)>,
+        /// Beneficiaries skipped because their vesting is suspended
+        pub suspended: Vec<AccountId>,
+    }
+
+    /// Contract health for monitoring
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HealthReport {
+        /// Beneficiaries whose unlocks are suspended
+        pub suspended: u32,
+        /// Balance owed to beneficiaries, excluding transferred and forfeited schedules
+        pub obligations: Balance,
+        /// Native pool in native mode, none for assets, whose supply is checked when grants are added
+        pub reserves: Option<Balance>,
+        /// Obligations are covered by the reserves, true when no reserves are known
+        pub solvent: bool,
+        /// Frozen schedules past their unlock time, excluding suspended beneficiaries
+        pub overdue_unlocks: u32,
+        /// Requests older than the request TTL
+        pub stale_requests: u32,
+        /// Version of the contract state
+        pub state_version: u64,
+    }
+
+    /// Contract-wide balance totals
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VestingTotals {
+        /// The total original balance
         pub original_balance: u128,
         /// The total frozen balance
         pub frozen_balance: u128,
@@ -90,407 +1091,10565 @@ mod vesting {
         /// The total requested balance
         pub requested_balance: u128,
         /// The total transferred balance
-        pub transferred_balance: u128,   
+        pub transferred_balance: u128,
+        /// The total disputed balance
+        pub disputed_balance: u128,
+        /// The total forfeited balance
+        pub forfeited_balance: u128,
+        /// Version of the contract state
+        pub state_version: u64,
     }
 
-    /// Contract Storage
-    #[ink(storage)]
-    pub struct Vesting {
-        /// The asset that is vested.
+    /// Price condition of a schedule, it only thaws once the oracle reported prices above the threshold
+    /// for the given number of consecutive observations. With a TWAP window, the time-weighted average
+    /// price over the window must be above the threshold instead, from at least that many observations
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct PriceGate {
+        /// Price the observations must be above
+        pub threshold: u128,
+        /// Number of consecutive observations above the threshold
+        pub observations: u16,
+        /// Length of the time-weighted average price window, none for spot prices
+        pub twap_window: Option<Timestamp>,
+    }
+
+    /// Failed outbound payout waiting in the retry queue
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct FailedPayout {
+        /// Payout id, kept across retries
+        pub payout_id: u64,
+        /// Beneficiary of the schedule
+        pub beneficiary: AccountId,
+        /// Requested schedule
+        pub schedule_number: u16,
+        /// Recipient of the payout
+        pub recipient: AccountId,
+        /// Amount of the payout
+        pub amount: u128,
+        /// Number of failed attempts
+        pub attempts: u32,
+        /// Error code of the last failure
+        pub last_error_code: u16,
+        /// Block of the last failure
+        pub last_attempt_at: BlockNumber,
+    }
+
+    /// Key of a vested balance schedule (beneficiary, schedule number)
+    pub type ScheduleKey = (AccountId, u16);
+
+    /// Ethereum-style 20-byte (H160) address
+    pub type EthAddress = [u8; 20];
+
+    /// Recipient of a transfer
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum Recipient {
+        /// Account of the chain
+        Account(AccountId),
+        /// EVM address, on chains with unified accounts or for payouts executed on an EVM sidechain.
+        /// Native payouts go to its mapped account, the blake2x256 hash of "evm:" and the address
+        EthAddress(EthAddress),
+        /// Account on a sibling parachain, paid by an XCM reserve transfer. It has no local account,
+        /// so it is never pinned, asked about by the compliance hook or called back
+        Xcm(XcmDestination),
+    }
+
+    /// Settlement receipt of a transferred schedule, kept when the schedule changes later
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Receipt {
+        /// Receipt id
+        pub receipt_id: u64,
+        /// Beneficiary of the transferred schedule
+        pub beneficiary: AccountId,
+        /// Transferred schedule number
+        pub schedule_number: u16,
+        /// Recipient of the transfer
+        pub recipient: Recipient,
+        /// Transferred amount
+        pub amount: u128,
+        /// Asset id of the program
         pub asset_id: u128,
-        /// Total number of scheduled vested balances
-        pub total_vested_schedule: u8,
-        /// Vested balances
-        pub vested_balances: Vec<VestedBalance>,
-        /// Vesting owner
-        pub vesting_owner: AccountId,
+        /// Tx-hash settling the transfer
+        pub tx_hash: Vec<u8>,
+        /// Block of the settlement
+        pub block: BlockNumber,
+        /// Time of the settlement
+        pub settled_at: Timestamp,
     }
 
-    impl Vesting {
-        /// Constructor 
-        #[ink(constructor)]
-        pub fn new(asset_id: u128, total_vested_schedule: u8) -> Self {
+    /// Vested balance schedule history entry
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct ScheduleHistoryEntry {
+        /// Status before the change
+        pub previous_status: ScheduleStatus,
+        /// Status after the change
+        pub status: ScheduleStatus,
+        /// Particulars (tx-hash) before the change
+        pub particulars: Vec<u8>,
+        /// Reason of the change
+        pub reason: Vec<u8>,
+        /// Account that made the change
+        pub operator: AccountId,
+        /// Block of the change
+        pub block_number: BlockNumber,
+        /// Time of the change
+        pub timestamp: Timestamp,
+    }
 
-            let caller = Self::env().caller();
+    /// Preview of an approval, computed without changing the state
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ApprovalPreview {
+        /// Amount of the schedule
+        pub amount: u128,
+        /// Recipient of the transfer
+        pub recipient: Recipient,
+        /// Fee charged on the amount
+        pub fee: u128,
+        /// Conditions that would make the approval fail, empty if it can go through
+        pub blocking: Vec<Error>,
+    }
 
-            Self { 
-                asset_id: asset_id, 
-                total_vested_schedule: total_vested_schedule,
-                vested_balances: Vec::new(),
-                vesting_owner: caller,
-            }
+    /// Approvals required for transfers of at least an amount
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct ApprovalTier {
+        /// Minimum amount the tier applies to
+        pub min_amount: u128,
+        /// Number of approvals by the owner or the approvers
+        pub approvals: u8,
+        /// The guardian must approve as well
+        pub guardian: bool,
+    }
 
-        }
+    /// Compliance profile of a beneficiary
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct ComplianceProfile {
+        /// Jurisdiction code, e.g. an ISO 3166-1 numeric country code
+        pub jurisdiction: u16,
+        /// Investor category
+        pub category: u8,
+    }
 
-        /// Default
-        #[ink(constructor)]
-        pub fn default() -> Self {
+    /// Backup address of a beneficiary, it can claim the position after the inactivity period
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct BackupConfig {
+        /// Backup address
+        pub backup: AccountId,
+        /// Time without beneficiary activity after which the backup can claim
+        pub inactivity_period: Timestamp,
+    }
 
-            Self::new(0u128, 0u8)
+    /// Recovery guardians of a beneficiary, who can jointly move the position to a new address
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct RecoveryConfig {
+        /// Recovery guardians
+        pub guardians: Vec<AccountId>,
+        /// Number of guardians that must vote for the same new address
+        pub threshold: u8,
+        /// Time the beneficiary has to cancel an agreed recovery
+        pub challenge_period: Timestamp,
+    }
 
-        }
+    /// Recovery votes of a beneficiary's guardians
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Recovery {
+        /// Vote of each guardian (guardian, new address)
+        pub votes: Vec<(AccountId, AccountId)>,
+        /// New address agreed on by the threshold and the time it can be executed
+        pub agreed: Option<(AccountId, Timestamp)>,
+    }
 
-        /// Setup vesting
-        #[ink(message)]
-        pub fn setup_vesting(&mut self,
-            asset_id: u128,
-            total_vested_schedule: u8,) -> Result<(), Error> {
-            
-            let caller = self.env().caller();
-            if self.env().caller() != self.vesting_owner {
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::BadOrigin),
-                });
-                return Ok(());
-            } 
+    /// Change to the frozen schedules of a grant
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum AmendmentChange {
+        /// New unlock time per schedule number, none if thawed by the owner only
+        UnlockTimes(Vec<(u16, Option<Timestamp>)>),
+        /// New amount per schedule number
+        ScheduleAmounts(Vec<(u16, u128)>),
+    }
 
-            // The setup will erase the existing vested balances
-            self.asset_id = asset_id;
-            self.total_vested_schedule = total_vested_schedule;
-            self.vested_balances =  Vec::new();
-            
-            self.env().emit_event(VestingEvent {
-                operator: caller,
-                status: VestingStatus::EmitSuccess(Success::VestingSetupSuccess),
+    /// Owner operation of a multicall
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum VestingCall {
+        /// Add a vested balance split into equal schedules, asset vesting only
+        AddVestedBalance { address: AccountId, original_balance: u128 },
+        /// Thaw a frozen schedule
+        ThawSchedule { address: AccountId, schedule_number: u16 },
+        /// Revoke a frozen or liquid schedule, it is kept as forfeited
+        ForfeitSchedule { address: AccountId, schedule_number: u16 },
+        /// Amend the frozen schedules of a grant, e.g. top up a schedule
+        AmendGrant { address: AccountId, change: AmendmentChange },
+        /// Remove a vested balance, the record is archived
+        RemoveVestedBalance { address: AccountId, force: bool },
+    }
+
+    /// Adverse grant amendment waiting for the beneficiary's consent
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct PendingAmendment {
+        /// The amended grant
+        pub address: AccountId,
+        /// Proposed change
+        pub change: AmendmentChange,
+        /// Account that proposed the change
+        pub proposed_by: AccountId,
+        /// Time of the proposal
+        pub proposed_at: Timestamp,
+    }
+
+    /// Applied grant amendment
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct AmendmentRecord {
+        /// Amendment id
+        pub amendment_id: u64,
+        /// Applied change
+        pub change: AmendmentChange,
+        /// Values before the change
+        pub previous: AmendmentChange,
+        /// Account that proposed the change
+        pub proposed_by: AccountId,
+        /// Beneficiary that accepted an adverse change
+        pub accepted_by: Option<AccountId>,
+        /// Time the change was applied
+        pub applied_at: Timestamp,
+    }
+
+    /// Reusable shape of a grant
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct VestingTemplate {
+        /// Name of the template
+        pub name: Vec<u8>,
+        /// Weight of each schedule in the grant
+        pub schedule_weights: Vec<u32>,
+        /// Time from the grant to the first unlock
+        pub cliff: Timestamp,
+        /// Time between two unlocks
+        pub interval: Timestamp,
+    }
+
+    /// Release shape of a grant, turned into timed schedules when the grant is added. The request and
+    /// approve machinery only sees the schedules, new shapes only need a variant and its unlocks
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum VestingCurve {
+        /// Weighted steps, the first after the cliff and one every interval after it
+        Stepwise { start: Timestamp, cliff: Timestamp, interval: Timestamp, weights: Vec<u32> },
+        /// Equal steps spread over the duration
+        Linear { start: Timestamp, duration: Timestamp, steps: u16 },
+        /// Nothing before the cliff, then the time elapsed so far unlocks at once and the rest in equal steps
+        CliffLinear { start: Timestamp, cliff: Timestamp, duration: Timestamp, steps: u16 },
+        /// Each step, one every interval, unlocks a share in basis points of what is left, the last step the rest
+        Exponential { start: Timestamp, interval: Timestamp, steps: u16, rate_bps: u16 },
+        /// Weights unlocking at the given times, in time order
+        Custom { points: Vec<(Timestamp, u128)> },
+        /// Cumulative unlocked share in basis points at each point, strictly later points and never decreasing,
+        /// ending at 10_000. The share at the first point unlocks at once, each segment is interpolated in steps
+        Piecewise { points: Vec<(Timestamp, u16)>, steps_per_segment: u16 },
+    }
+
+    impl VestingCurve {
+        /// Unlock time and weight of each step, in time order
+        pub fn unlocks(&self) -> Result<Vec<(Timestamp, u128)>, Error> {
+            let unlocks: Vec<(Timestamp, u128)> = match self {
+                VestingCurve::Stepwise { start, cliff, interval, weights } => weights.iter()
+                    .enumerate()
+                    .map(|(i, weight)| (
+                        start.saturating_add(*cliff).saturating_add(interval.saturating_mul(i as Timestamp)),
+                        *weight as u128,
+                    ))
+                    .collect(),
+                VestingCurve::Linear { start, duration, steps } => (1..=*steps as u64)
+                    .map(|i| (start.saturating_add(duration.saturating_mul(i) / *steps as u64), 1))
+                    .collect(),
+                VestingCurve::CliffLinear { start, cliff, duration, steps } => {
+                    if cliff > duration || *steps == 0 {
+                        return Err(Error::InvalidVestingCurve);
+                    }
+                    let rest = duration - cliff;
+                    core::iter::once((start.saturating_add(*cliff), *cliff as u128))
+                        .chain((1..=*steps as u64).map(|i| (
+                            start.saturating_add(*cliff).saturating_add(rest.saturating_mul(i) / *steps as u64),
+                            (rest / *steps as u64) as u128,
+                        )))
+                        .filter(|(_, weight)| *weight > 0)
+                        .collect()
+                }
+                VestingCurve::Exponential { start, interval, steps, rate_bps } => {
+                    if *rate_bps == 0 || *rate_bps > 10_000 {
+                        return Err(Error::InvalidVestingCurve);
+                    }
+                    let mut remaining: u128 = 1_000_000_000_000;
+                    (1..=*steps as u64).map(|i| {
+                        let weight = if i == *steps as u64 { remaining } else { remaining * *rate_bps as u128 / 10_000 };
+                        remaining -= weight;
+                        (start.saturating_add(interval.saturating_mul(i)), weight)
+                    }).collect()
+                }
+                VestingCurve::Custom { points } => points.clone(),
+                VestingCurve::Piecewise { points, steps_per_segment } => {
+                    if *steps_per_segment == 0
+                        || points.last().map(|(_, share)| *share) != Some(10_000)
+                        || points.windows(2).any(|w| w[0].0 >= w[1].0 || w[0].1 > w[1].1) {
+                        return Err(Error::InvalidVestingCurve);
+                    }
+                    let steps = *steps_per_segment as u64;
+                    let (first_time, first_share) = points[0];
+                    core::iter::once((first_time, first_share as u128))
+                        .chain(points.windows(2).flat_map(|w| {
+                            let ((from_time, from_share), (to_time, to_share)) = (w[0], w[1]);
+                            let share = move |k: u64| (to_share - from_share) as u64 * k / steps;
+                            (1..=steps).map(move |k| (
+                                from_time + (to_time - from_time).saturating_mul(k) / steps,
+                                (share(k) - share(k - 1)) as u128,
+                            ))
+                        }))
+                        .filter(|(_, weight)| *weight > 0)
+                        .collect()
+                }
+            };
+
+            // Every curve needs steps in time order and something to unlock
+            if unlocks.is_empty()
+                || unlocks.windows(2).any(|w| w[0].0 > w[1].0)
+                || unlocks.iter().all(|(_, weight)| *weight == 0) {
+                return Err(Error::InvalidVestingCurve);
+            }
+
+            Ok(unlocks)
+        }
+    }
+
+    /// Snapshot of the locked-balance voting weights
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct SnapshotInfo {
+        /// Block of the snapshot
+        pub block_number: BlockNumber,
+        /// Time of the snapshot
+        pub taken_at: Timestamp,
+        /// Sum of the voting weights
+        pub total_weight: u128,
+    }
+
+    /// Action executed by the governance contract after a passed proposal
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum GovernanceAction {
+        /// Thaw every frozen schedule of a schedule number
+        ThawVestedBalances { schedule_number: u16 },
+        /// Thaw one frozen schedule
+        ThawSchedule { address: AccountId, schedule_number: u16 },
+        /// Set the unlock time of a schedule number
+        SetUnlockTime { schedule_number: u16, unlock_time: Timestamp },
+        /// Hand the unlocks to another governor, or back to the owner
+        SetGovernor { governor: Option<AccountId> },
+    }
+
+    /// Result of one item of a batch message
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct BatchItemResult {
+        /// Index of the item in the batch
+        pub index: u32,
+        /// Outcome of the item
+        pub result: Result<Success, Error>,
+    }
+
+    /// Results of the items of a batch message
+    pub type BatchResult = Vec<BatchItemResult>;
+
+    /// Pending transfer request
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct TransferRequest {
+        /// Beneficiary of the requested schedule
+        pub beneficiary: AccountId,
+        /// Requested schedule number
+        pub schedule_number: u16,
+        /// Recipient of the transfer
+        pub recipient_address: AccountId,
+        /// Requested amount
+        pub amount: u128,
+        /// Time of the request
+        pub requested_at: Timestamp,
+        /// Block of the request
+        pub requested_block: BlockNumber,
+        /// Memo of the request for the approver
+        pub memo: Vec<u8>,
+        /// Verified by the compliance operator
+        pub verified: bool,
+    }
+
+    /// External escrow whose approval a grant's large transfers need besides the owner's
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct EscrowConfig {
+        /// Escrow or arbiter account
+        pub escrow: AccountId,
+        /// Transfers above this amount need the escrow approval
+        pub threshold: u128,
+    }
+
+    /// Pending revert of an approval, waiting for the owner and guardian signatures
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct ApprovalRevert {
+        /// Status to revert to, liquid or requested
+        pub to_status: ScheduleStatus,
+        /// Reason of the revert
+        pub reason: Vec<u8>,
+        /// Owner account that signed
+        pub owner_signer: Option<AccountId>,
+        /// Guardian account that signed
+        pub guardian_signer: Option<AccountId>,
+    }
+
+    /// Vested balance schedules
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VestedBalanceSchedule {
+        /// Schedule number, 1 up to the configured maximum (100 by default)
+        pub schedule_number: u16,
+        /// Stable identifier, hash of the beneficiary, the original schedule number, the creation block and a
+        /// contract-wide counter. It does not change when the schedule is moved or renumbered
+        #[cfg_attr(feature = "std", serde(with = "serde_hex"))]
+        pub schedule_id: Hash,
+        /// Schedule balance
+        pub schedule_balance: u128,
+        /// Status
+        pub status: ScheduleStatus,
+        /// Transfer recipient
+        #[cfg_attr(feature = "std", serde(with = "serde_hex::option"))]
+        pub recipient_address: Option<AccountId>,
+        /// Particulars
+        pub particulars: Vec<u8>,
+        /// Transfer request id
+        pub request_id: Option<u64>,
+        /// Time the schedule becomes liquid, none if thawed by the owner only
+        pub unlock_time: Option<Timestamp>,
+        /// Time of the transfer request
+        pub requested_at: Option<Timestamp>,
+        /// Memo of the transfer request for the approver
+        pub memo: Vec<u8>,
+    }    
+
+    /// Vested balances
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VestedBalance {
+        /// The address that holds the vested balance
+        #[cfg_attr(feature = "std", serde(with = "serde_hex"))]
+        pub address: AccountId,
+        /// Vested schedules
+        pub vested_balance_schedules: Vec<VestedBalanceSchedule>,
+        /// The original balance
+        pub original_balance: u128,
+        /// The total frozen balance
+        pub frozen_balance: u128,
+        /// The total liquid balance
+        pub liquid_balance: u128,
+        /// The total requested balance
+        pub requested_balance: u128,
+        /// The total transferred balance
+        pub transferred_balance: u128,   
+        /// The total disputed balance
+        pub disputed_balance: u128,
+        /// The total forfeited balance
+        pub forfeited_balance: u128,
+        /// Bumped on every change of the vested balance
+        pub version: u32,
+        /// Pin transfers to the recipient of the first approved transfer
+        pub pin_recipient: bool,
+        /// Recipient transfers are pinned to
+        #[cfg_attr(feature = "std", serde(with = "serde_hex::option"))]
+        pub pinned_recipient: Option<AccountId>,
+        /// KYC verified by the compliance operator
+        pub kyc_verified: bool,
+        /// Owner notes, e.g. HR references or internal grant ids, visible to the owner and the viewers only
+        pub notes: Vec<u8>,
+    }
+
+    /// Vested balance before the owner notes were added, the shape of `VersionedVestedBalance::V1`
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VestedBalanceV1 {
+        /// The address that holds the vested balance
+        #[cfg_attr(feature = "std", serde(with = "serde_hex"))]
+        pub address: AccountId,
+        /// Vested schedules
+        pub vested_balance_schedules: Vec<VestedBalanceSchedule>,
+        /// The original balance
+        pub original_balance: u128,
+        /// The total frozen balance
+        pub frozen_balance: u128,
+        /// The total liquid balance
+        pub liquid_balance: u128,
+        /// The total requested balance
+        pub requested_balance: u128,
+        /// The total transferred balance
+        pub transferred_balance: u128,
+        /// The total disputed balance
+        pub disputed_balance: u128,
+        /// The total forfeited balance
+        pub forfeited_balance: u128,
+        /// Bumped on every change of the vested balance
+        pub version: u32,
+        /// Pin transfers to the recipient of the first approved transfer
+        pub pin_recipient: bool,
+        /// Recipient transfers are pinned to
+        #[cfg_attr(feature = "std", serde(with = "serde_hex::option"))]
+        pub pinned_recipient: Option<AccountId>,
+        /// KYC verified by the compliance operator
+        pub kyc_verified: bool,
+    }
+
+    /// Vested balance tagged with its encoding version. A changed shape is added as the next variant,
+    /// older variants keep decoding in existing clients
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub enum VersionedVestedBalance {
+        /// Shape before the owner notes
+        V1(VestedBalanceV1),
+        /// Current shape, with the owner notes
+        V2(VestedBalance),
+    }
+
+    impl From<VestedBalance> for VersionedVestedBalance {
+        fn from(vested_balance: VestedBalance) -> Self {
+            VersionedVestedBalance::V2(vested_balance)
+        }
+    }
+
+    /// Position report tagged with its encoding version, see `VersionedVestedBalance`
+    #[derive(scale::Encode, scale::Decode, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+    pub enum VersionedPositionReport {
+        /// Current shape
+        V1(PositionReport),
+    }
+
+    impl From<PositionReport> for VersionedPositionReport {
+        fn from(report: PositionReport) -> Self {
+            VersionedPositionReport::V1(report)
+        }
+    }
+
+    /// Contract Storage
+    #[ink(storage)]
+    pub struct Vesting {
+        /// The asset that is vested.
+        pub asset_id: u128,
+        /// Total number of scheduled vested balances
+        pub total_vested_schedule: u16,
+        /// Highest schedule number of a vested balance
+        pub max_schedule_number: u16,
+        /// Vested balances
+        pub vested_balances: Vec<VestedBalance>,
+        /// Vesting owner
+        pub vesting_owner: AccountId,
+        /// Next transfer request id
+        pub next_request_id: u64,
+        /// Pending transfer requests by request id
+        pub requests: Mapping<u64, TransferRequest>,
+        /// Next payout id
+        pub next_payout_id: u64,
+        /// Pending payouts (payout id -> beneficiary, schedule number)
+        pub payouts: Mapping<u64, (AccountId, u16)>,
+        /// Pending payout id per approved schedule
+        pub payout_ids: Mapping<ScheduleKey, u64>,
+        /// Next settlement receipt id
+        pub next_receipt_id: u64,
+        /// Settlement receipts
+        pub receipts: Mapping<u64, Receipt>,
+        /// Settlement receipt ids per beneficiary, oldest first
+        pub beneficiary_receipts: Mapping<AccountId, Vec<u64>>,
+        /// Failed outbound payouts waiting for a retry
+        pub failed_payouts: Lazy<Vec<FailedPayout>>,
+        /// Number of schedules and total amount per status code
+        pub status_totals: Vec<(u32, u128)>,
+        /// Sum of the original balances of all vested balances
+        pub total_original_balance: u128,
+        /// Bumped on every successful change of the contract state
+        pub state_version: u64,
+        /// Holders ordered by remaining (non-transferred) balance, largest first
+        pub holders_index: Vec<(AccountId, u128)>,
+        /// Unlock time per schedule number, applied to new vested balances
+        pub unlock_times: Mapping<u16, Timestamp>,
+        /// Bounty paid to keepers per processed item
+        pub keeper_bounty: Balance,
+        /// Native balance reserved to pay keeper bounties
+        pub keeper_pool: Balance,
+        /// Account resolving disputed schedules
+        pub arbiter: Option<AccountId>,
+        /// Status of disputed schedules before the dispute
+        pub disputes: Mapping<ScheduleKey, ScheduleStatus>,
+        /// Account co-signing sensitive owner operations
+        pub guardian: Option<AccountId>,
+        /// Time between proposing a new owner and the new owner being able to take over
+        pub owner_rotation_delay: Timestamp,
+        /// Proposed owner and the time it can take over
+        pub pending_owner: Option<(AccountId, Timestamp)>,
+        /// Guardian change proposed by the owner alone and the time it can be applied
+        pub pending_guardian: Option<(Option<AccountId>, Timestamp)>,
+        /// Account verifying transfer requests before they are executed
+        pub compliance_operator: Option<AccountId>,
+        /// Account executing verified transfer requests besides the owner
+        pub treasury: Option<AccountId>,
+        /// Approval tiers ordered by minimum amount
+        pub approval_tiers: Vec<ApprovalTier>,
+        /// Accounts approving transfers besides the owner
+        pub approvers: Vec<AccountId>,
+        /// Accounts allowed to read the access-gated views besides the owner, without any other right
+        pub viewers: Vec<AccountId>,
+        /// Restrict the bulk views to the owner and the viewers, beneficiaries still read their own position
+        pub private: bool,
+        /// Approvals collected per requested schedule
+        pub approvals: Mapping<ScheduleKey, Vec<AccountId>>,
+        /// Only KYC verified beneficiaries may request transfers
+        pub kyc_required: bool,
+        /// Compliance profile per beneficiary
+        pub compliance_profiles: Mapping<AccountId, ComplianceProfile>,
+        /// Time before which an investor category may not request transfers
+        pub category_lockups: Mapping<u8, Timestamp>,
+        /// Contract asked `is_allowed(beneficiary, recipient, amount) -> bool` before requests and approvals
+        pub compliance_hook: Option<AccountId>,
+        /// Selector of `on_vested_transfer(beneficiary, amount, asset_id)` registered by recipient contracts
+        pub transfer_callbacks: Mapping<AccountId, [u8; 4]>,
+        /// Gas limit of a transfer callback
+        pub callback_gas_limit: u64,
+        /// Vesting templates
+        pub templates: Mapping<u32, VestingTemplate>,
+        /// Next template id
+        pub next_template_id: u32,
+        /// Recorded tx-hashes and the schedule they settled
+        pub tx_hashes: Mapping<Vec<u8>, ScheduleKey>,
+        /// Operation ids already applied, with the block they were applied at
+        pub applied_op_ids: Mapping<[u8; 32], BlockNumber>,
+        /// Pending approval reverts
+        pub approval_reverts: Mapping<ScheduleKey, ApprovalRevert>,
+        /// Schedule history
+        pub schedule_history: Mapping<ScheduleKey, Vec<ScheduleHistoryEntry>>,
+        /// Particulars (tx-hash) per schedule id, kept out of the vested balances so routine messages do not
+        /// load them. The views fill them into the schedules
+        pub schedule_particulars: Mapping<Hash, Vec<u8>>,
+        /// Number of schedule ids derived so far, keeps the ids of re-added grants apart
+        pub schedule_id_counter: u64,
+        /// Next nonce of signed requests per account
+        pub nonces: Mapping<AccountId, u64>,
+        /// Removed vested balances kept for audits, oldest first
+        pub archived_balances: Mapping<AccountId, Vec<VestedBalance>>,
+        /// Backup addresses of beneficiaries
+        pub backups: Mapping<AccountId, BackupConfig>,
+        /// Last time a beneficiary acted on its position
+        pub last_activity: Mapping<AccountId, Timestamp>,
+        /// Recovery guardians of beneficiaries
+        pub recovery_configs: Mapping<AccountId, RecoveryConfig>,
+        /// Ongoing recoveries of beneficiaries
+        pub recoveries: Mapping<AccountId, Recovery>,
+        /// Beneficiaries whose unlocks are halted, with the time of the suspension
+        pub suspensions: Mapping<AccountId, Timestamp>,
+        /// Number of suspended beneficiaries
+        pub suspended_count: u32,
+        /// Adverse amendments waiting for the beneficiary's consent
+        pub amendments: Mapping<u64, PendingAmendment>,
+        /// Schedules pledged as collateral, with the lender holding the pledge
+        pub pledges: Mapping<ScheduleKey, AccountId>,
+        /// Governance contract controlling the thaws, the owner thaws when none
+        pub governor: Option<AccountId>,
+        /// Next voting weight snapshot id
+        pub next_snapshot_id: u32,
+        /// Voting weight snapshots
+        pub snapshots: Mapping<u32, SnapshotInfo>,
+        /// Non-zero voting weights per snapshot
+        pub snapshot_weights: Mapping<(u32, AccountId), u128>,
+        /// Delegates receiving the voting weight of beneficiaries
+        pub delegates: Mapping<AccountId, AccountId>,
+        /// Oracle reporting the asset price for price-gated unlocks
+        pub price_oracle: Option<AccountId>,
+        /// Latest oracle price observations (time, price), oldest first
+        pub price_observations: Lazy<Vec<(Timestamp, u128)>>,
+        /// Price conditions of schedules
+        pub price_gates: Mapping<ScheduleKey, PriceGate>,
+        /// Curves vested balances were added with
+        pub curves: Mapping<AccountId, VestingCurve>,
+        /// Escrow approvers of grants
+        pub escrows: Mapping<AccountId, EscrowConfig>,
+        /// Requests approved by the escrow of their grant
+        pub escrow_approvals: Mapping<u64, AccountId>,
+        /// Next amendment id
+        pub next_amendment_id: u64,
+        /// Applied amendments per grant, oldest first
+        pub amendment_history: Mapping<AccountId, Vec<AmendmentRecord>>,
+        /// Set while a message that may call external contracts is executing. Kept in its own storage cell
+        /// and written right away, so a re-entered call reads it before the root storage is written back
+        pub reentrancy_lock: Lazy<bool>,
+        /// Time after which a transfer request expires
+        pub request_ttl: Option<Timestamp>,
+        /// Transfer fee in basis points
+        pub fee_bps: u16,
+        /// Allow beneficiaries to donate liquid schedules to each other
+        pub donations_enabled: bool,
+        /// Revert invalid operations with the error instead of emitting it and returning Ok
+        pub strict_mode: bool,
+        /// Blocks without approvals after which pending requests can be executed without the owner
+        pub dead_man_period: Option<BlockNumber>,
+        /// Block of the last approval by an approver
+        pub last_approval_block: BlockNumber,
+        /// Kind of the vested asset
+        pub asset_kind: AssetKind,
+        /// Native balance escrowed to pay out native vesting
+        pub native_pool: Balance,
+        /// Backend enforcing the lock of transferred native vesting
+        pub enforcement_backend: EnforcementBackend,
+        /// Sibling parachain accounts of schedules requested for an XCM transfer
+        pub xcm_destinations: Mapping<ScheduleKey, XcmDestination>,
+        /// EVM addresses of schedules requested for a transfer to an Ethereum-style recipient
+        pub eth_recipients: Mapping<ScheduleKey, EthAddress>,
+        /// Committed recipients of liquid schedules, the Blake2x256 hash of the encoded (recipient, salt)
+        pub recipient_commitments: Mapping<ScheduleKey, Hash>,
+        /// Storage deposit charged by the chain per stored byte, used for estimates only
+        pub deposit_per_byte: Balance,
+        /// Supply of the asset, read from pallet-assets, that grants cannot oblige more than. None to not check
+        pub supply_check: Option<SupplyScope>,
+        /// Cached metadata of the asset
+        pub asset_metadata: AssetMetadata,
+        /// Program name
+        pub program_name: Vec<u8>,
+        /// Program URI, e.g. terms or a website
+        pub program_uri: Vec<u8>,
+        /// Program label, distinguishes vesting contracts of the same asset (team, investors, advisors)
+        pub label: Vec<u8>,
+        /// Version of the storage layout the contract was deployed or migrated with
+        pub storage_version: u16,
+    }
+
+    impl Vesting {
+        /// Constructor 
+        #[ink(constructor)]
+        pub fn new(asset_id: u128, total_vested_schedule: u16) -> Result<Self, Error> {
+
+            let caller = Self::env().caller();
+
+            // Balances are split equally over the schedules
+            if total_vested_schedule == 0 {
+                return Err(Error::InvalidScheduleCount { total_vested_schedule });
+            }
+            if total_vested_schedule > DEFAULT_MAX_SCHEDULE_NUMBER {
+                return Err(Error::ScheduleNumberTooHigh {
+                    schedule_number: total_vested_schedule,
+                    max_schedule_number: DEFAULT_MAX_SCHEDULE_NUMBER,
+                });
+            }
+
+            Ok(Self { 
+                asset_id, 
+                total_vested_schedule,
+                max_schedule_number: DEFAULT_MAX_SCHEDULE_NUMBER,
+                vested_balances: Vec::new(),
+                vesting_owner: caller,
+                next_request_id: 1,
+                requests: Mapping::default(),
+                next_payout_id: 1,
+                next_receipt_id: 1,
+                receipts: Mapping::default(),
+                beneficiary_receipts: Mapping::default(),
+                payouts: Mapping::default(),
+                payout_ids: Mapping::default(),
+                status_totals: Vec::new(),
+                total_original_balance: 0,
+                state_version: 0,
+                holders_index: Vec::new(),
+                failed_payouts: Lazy::new(),
+                unlock_times: Mapping::default(),
+                keeper_bounty: 0,
+                keeper_pool: 0,
+                arbiter: None,
+                disputes: Mapping::default(),
+                guardian: None,
+                owner_rotation_delay: 0,
+                pending_owner: None,
+                pending_guardian: None,
+                compliance_operator: None,
+                treasury: None,
+                approval_tiers: Vec::new(),
+                approvers: Vec::new(),
+                viewers: Vec::new(),
+                private: false,
+                approvals: Mapping::default(),
+                kyc_required: false,
+                compliance_profiles: Mapping::default(),
+                category_lockups: Mapping::default(),
+                compliance_hook: None,
+                transfer_callbacks: Mapping::default(),
+                callback_gas_limit: DEFAULT_CALLBACK_GAS_LIMIT,
+                templates: Mapping::default(),
+                next_template_id: 1,
+                tx_hashes: Mapping::default(),
+                applied_op_ids: Mapping::default(),
+                approval_reverts: Mapping::default(),
+                schedule_history: Mapping::default(),
+                schedule_particulars: Mapping::default(),
+                schedule_id_counter: 0,
+                nonces: Mapping::default(),
+                archived_balances: Mapping::default(),
+                backups: Mapping::default(),
+                last_activity: Mapping::default(),
+                recovery_configs: Mapping::default(),
+                recoveries: Mapping::default(),
+                suspensions: Mapping::default(),
+                suspended_count: 0,
+                amendments: Mapping::default(),
+                pledges: Mapping::default(),
+                governor: None,
+                next_snapshot_id: 1,
+                snapshots: Mapping::default(),
+                snapshot_weights: Mapping::default(),
+                delegates: Mapping::default(),
+                price_oracle: None,
+                price_observations: Lazy::new(),
+                price_gates: Mapping::default(),
+                curves: Mapping::default(),
+                escrows: Mapping::default(),
+                escrow_approvals: Mapping::default(),
+                next_amendment_id: 1,
+                amendment_history: Mapping::default(),
+                reentrancy_lock: Lazy::new(),
+                request_ttl: None,
+                fee_bps: 0,
+                donations_enabled: false,
+                strict_mode: false,
+                dead_man_period: None,
+                last_approval_block: 0,
+                asset_kind: AssetKind::Asset,
+                native_pool: 0,
+                enforcement_backend: EnforcementBackend::Contract,
+                xcm_destinations: Mapping::default(),
+                eth_recipients: Mapping::default(),
+                recipient_commitments: Mapping::default(),
+                deposit_per_byte: 0,
+                supply_check: None,
+                asset_metadata: AssetMetadata::default(),
+                program_name: Vec::new(),
+                program_uri: Vec::new(),
+                label: Vec::new(),
+                storage_version: STORAGE_VERSION,
+            })
+
+        }
+
+        /// Default
+        #[ink(constructor)]
+        #[allow(clippy::should_implement_trait)]
+        pub fn default() -> Result<Self, Error> {
+
+            Self::new(0u128, 1u16)
+
+        }
+
+        /// Set the vested asset, only allowed before any vested balance is added
+        #[ink(message)]
+        pub fn set_asset_id(&mut self,
+            asset_id: u128,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if !self.vested_balances.is_empty() {
+                self.fail(caller, Error::VestedBalancesExist)?;
+                return Ok(());
+            }
+
+            self.asset_id = asset_id;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::AssetIdSet));
+
+            Ok(())
+        }
+
+        /// Set the kind of the vested asset, only allowed before any vested balance is added
+        #[ink(message)]
+        pub fn set_asset_kind(&mut self,
+            asset_kind: AssetKind,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if !self.vested_balances.is_empty() {
+                self.fail(caller, Error::VestedBalancesExist)?;
+                return Ok(());
+            }
+
+            self.asset_kind = asset_kind;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::AssetKindSet));
+
+            Ok(())
+        }
+
+        /// Escrow the transferred native balance to pay out native vesting, by the owner. Refused funding is
+        /// reverted so the balance is not left outside the escrow
+        #[ink(message, payable)]
+        pub fn fund_native(&mut self) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if caller != self.vesting_owner {
+                return self.refuse_funded(caller, Error::BadOrigin);
+            }
+
+            if self.asset_kind != AssetKind::Native {
+                return self.refuse_funded(caller, Error::NativeVestingDisabled);
+            }
+
+            self.native_pool += self.env().transferred_value();
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::NativeFunded));
+
+            Ok(())
+        }
+
+        /// Get the asset kind and the native escrow
+        #[ink(message)]
+        pub fn get_native_info(&self,) -> (AssetKind, Balance) {
+            (self.asset_kind, self.native_pool)
+        }
+
+        /// Set the backend enforcing the lock of transferred native vesting, the pallet-vesting backend
+        /// needs the chain extension in the runtime
+        #[ink(message)]
+        pub fn set_enforcement_backend(&mut self,
+            enforcement_backend: EnforcementBackend,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = match enforcement_backend {
+                EnforcementBackend::PalletVesting { unlock_blocks, .. }
+                    if self.asset_kind != AssetKind::Native || unlock_blocks == 0 => Err(Error::InvalidEnforcementBackend),
+                _ => {
+                    self.enforcement_backend = enforcement_backend;
+                    Ok(())
+                }
+            };
+
+            self.finish(caller, result, Success::EnforcementBackendSet)
+        }
+
+        /// Get the backend enforcing the lock of transferred native vesting
+        #[ink(message)]
+        pub fn get_enforcement_backend(&self,) -> EnforcementBackend {
+            self.enforcement_backend
+        }
+
+        /// Set the number of schedules generated for new vested balances
+        #[ink(message)]
+        pub fn set_default_schedule_count(&mut self,
+            total_vested_schedule: u16,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if total_vested_schedule == 0 {
+                self.fail(caller, Error::InvalidScheduleCount { total_vested_schedule })?;
+                return Ok(());
+            }
+            if total_vested_schedule > self.max_schedule_number {
+                self.fail(caller, Error::ScheduleNumberTooHigh {
+                    schedule_number: total_vested_schedule,
+                    max_schedule_number: self.max_schedule_number,
+                })?;
+                return Ok(());
+            }
+
+            self.total_vested_schedule = total_vested_schedule;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::DefaultScheduleCountSet));
+
+            Ok(())
+        }
+
+        /// Set the highest schedule number of a vested balance, it cannot go below the default schedule
+        /// count or a schedule number already in use
+        #[ink(message)]
+        pub fn set_max_schedule_number(&mut self,
+            max_schedule_number: u16,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let highest = self.vested_balances.iter()
+                .flat_map(|v| v.vested_balance_schedules.iter().map(|s| s.schedule_number))
+                .max()
+                .unwrap_or(0)
+                .max(self.total_vested_schedule);
+            if max_schedule_number == 0 || highest > max_schedule_number {
+                self.fail(caller, Error::ScheduleNumberTooHigh {
+                    schedule_number: highest,
+                    max_schedule_number,
+                })?;
+                return Ok(());
+            }
+
+            self.max_schedule_number = max_schedule_number;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::MaxScheduleNumberSet));
+
+            Ok(())
+        }
+
+        /// Get the highest schedule number of a vested balance
+        #[ink(message)]
+        pub fn get_max_schedule_number(&self,) -> u16 {
+            self.max_schedule_number
+        }
+
+        /// Set the time after which a transfer request expires, none to never expire
+        #[ink(message)]
+        pub fn set_request_ttl(&mut self,
+            request_ttl: Option<Timestamp>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if request_ttl == Some(0) {
+                self.fail(caller, Error::InvalidRequestTtl)?;
+                return Ok(());
+            }
+
+            self.request_ttl = request_ttl;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::RequestTtlSet));
+
+            Ok(())
+        }
+
+        /// Set the transfer fee in basis points
+        #[ink(message)]
+        pub fn set_fee_bps(&mut self,
+            fee_bps: u16,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if fee_bps > 10_000 {
+                self.fail(caller, Error::InvalidFeeBps { fee_bps })?;
+                return Ok(());
+            }
+
+            self.fee_bps = fee_bps;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::FeeBpsSet));
+
+            Ok(())
+        }
+
+        /// Get the request time-to-live and the transfer fee in basis points
+        #[ink(message)]
+        pub fn get_request_config(&self,) -> (Option<Timestamp>, u16) {
+            (self.request_ttl, self.fee_bps)
+        }
+
+        /// Get vesting information (asset id, schedules per balance, owner, strict mode)
+        #[ink(message)]
+        pub fn get_vesting_info(&self,) -> (u128, u16, AccountId, bool) {
+            (
+                self.asset_id,
+                self.total_vested_schedule,
+                self.vesting_owner,
+                self.strict_mode,
+            )
+        }
+
+        /// Turn strict mode on or off. In strict mode an invalid operation reverts with its error, leaving no
+        /// state change or event behind, and a bad item reverts a whole batch. Failed payouts and recipient
+        /// callbacks are still only reported. Off keeps emitting the error event and returning Ok for legacy
+        /// integrations
+        #[ink(message)]
+        pub fn set_strict_mode(&mut self,
+            strict_mode: bool,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.strict_mode = strict_mode;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::StrictModeSet));
+
+            Ok(())
+        }
+
+        /// Get the storage layout version
+        #[ink(message)]
+        pub fn get_storage_version(&self,) -> u16 {
+            self.storage_version
+        }
+
+        /// Set the storage deposit per byte of the chain, used by `estimate_storage_deposit`
+        #[ink(message)]
+        pub fn set_deposit_per_byte(&mut self,
+            deposit_per_byte: Balance,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.deposit_per_byte = deposit_per_byte;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::DepositPerByteSet));
+
+            Ok(())
+        }
+
+        /// Set the supply of the asset (total or owner-held, read from pallet-assets) that grants may oblige,
+        /// none to stop checking
+        #[ink(message)]
+        pub fn set_supply_check(&mut self,
+            supply_check: Option<SupplyScope>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.supply_check = supply_check;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::AssetSupplySet));
+
+            Ok(())
+        }
+
+        /// Set the program name, URI and label
+        #[ink(message)]
+        pub fn set_program_metadata(&mut self,
+            program_name: Vec<u8>,
+            program_uri: Vec<u8>,
+            label: Vec<u8>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if let Some(length) = [&program_name, &program_uri, &label].iter()
+                .map(|value| value.len())
+                .find(|length| *length > MAX_METADATA_LENGTH) {
+                self.fail(caller, Error::MetadataTooLong { length: length as u32 })?;
+                return Ok(());
+            }
+
+            self.program_name = program_name.clone();
+            self.program_uri = program_uri.clone();
+            self.label = label.clone();
+
+            self.env().emit_event(ProgramMetadataChanged {
+                program_name,
+                program_uri,
+                label,
+            });
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ProgramMetadataSet));
+
+            Ok(())
+        }
+
+        /// Get the program name, URI and label
+        #[ink(message)]
+        pub fn get_program_metadata(&self,) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+            (self.program_name.clone(), self.program_uri.clone(), self.label.clone())
+        }
+
+        /// Set the decimals and minimum balance of the asset, as registered in pallet-assets
+        #[ink(message)]
+        pub fn set_asset_metadata(&mut self,
+            decimals: u8,
+            min_balance: Balance,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // 10^39 overflows u128
+            if decimals > 38 {
+                self.fail(caller, Error::InvalidAssetDecimals { decimals })?;
+                return Ok(());
+            }
+
+            self.asset_metadata.decimals = decimals;
+            self.asset_metadata.min_balance = min_balance;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::AssetMetadataSet));
+
+            Ok(())
+        }
+
+        /// Refresh the cached metadata of the asset with the values registered in pallet-assets
+        #[ink(message)]
+        pub fn refresh_asset_metadata(&mut self,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let (name, symbol, decimals, min_balance) = match self.env().extension().asset_metadata(self.asset_id) {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    self.fail(caller, Error::AssetMetadataUnavailable { error })?;
+                    return Ok(());
+                }
+            };
+
+            // 10^39 overflows u128
+            if decimals > 38 {
+                self.fail(caller, Error::InvalidAssetDecimals { decimals })?;
+                return Ok(());
+            }
+
+            if let Some(length) = [&name, &symbol].iter()
+                .map(|value| value.len())
+                .find(|length| *length > MAX_METADATA_LENGTH) {
+                self.fail(caller, Error::MetadataTooLong { length: length as u32 })?;
+                return Ok(());
+            }
+
+            self.asset_metadata = AssetMetadata {
+                name,
+                symbol,
+                decimals,
+                min_balance,
+                refreshed_at: self.env().block_timestamp(),
+            };
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::AssetMetadataSet));
+
+            Ok(())
+        }
+
+        /// Get the decimals and minimum balance of the asset
+        #[ink(message)]
+        pub fn get_asset_metadata(&self,) -> (u8, Balance) {
+            (self.asset_metadata.decimals, self.asset_metadata.min_balance)
+        }
+
+        /// Get the cached metadata of the asset
+        #[ink(message)]
+        pub fn get_cached_asset_metadata(&self,) -> AssetMetadata {
+            self.asset_metadata.clone()
+        }
+
+        /// Convert a raw amount to asset units
+        #[ink(message)]
+        pub fn to_decimal_amount(&self,
+            raw: u128,) -> DecimalAmount {
+            let decimals = self.asset_metadata.decimals;
+            let unit = 10u128.pow(decimals as u32);
+            DecimalAmount {
+                raw,
+                whole: raw / unit,
+                fraction: raw % unit,
+                decimals,
+            }
+        }
+
+        /// Get the schedule amounts of an address, raw and in asset units, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_decimal_schedules(&self,
+            address: AccountId,) -> Vec<(u16, DecimalAmount)> {
+            if !self.can_view(address) {
+                return Vec::new();
+            }
+            self.vested_balances
+                .iter()
+                .find(|v| v.address == address)
+                .map(|v| v.vested_balance_schedules.iter()
+                    .map(|s| (s.schedule_number, self.to_decimal_amount(s.schedule_balance)))
+                    .collect())
+                .unwrap_or_default()
+        }
+
+        /// Estimate the storage deposit of adding new beneficiaries with the given number of schedules each.
+        /// Counts the vested balance records and their holders index entries, before any request or history
+        #[ink(message)]
+        pub fn estimate_storage_deposit(&self,
+            new_beneficiaries: u32,
+            schedules_each: u32,) -> Balance {
+
+            // An empty record already encodes the one byte length of its empty schedule list
+            let record = Self::new_balance(AccountId::from([0; 32]), Vec::new()).encoded_size() as u128 - 1;
+            let schedules = scale::Compact(schedules_each).encoded_size() as u128
+                + schedules_each as u128 * VestedBalanceSchedule::default().encoded_size() as u128;
+            let holder = (AccountId::from([0; 32]), 0u128).encoded_size() as u128;
+
+            (record + schedules + holder)
+                .saturating_mul(new_beneficiaries as u128)
+                .saturating_mul(self.deposit_per_byte)
+        }
+
+        /// Add vested balances, returns the generated schedules. In native mode the transferred
+        /// value must fund exactly the original balance.
+        #[ink(message, payable)]
+        pub fn add_vested_balance(&mut self,
+            address: AccountId,
+            original_balance: u128,
+            op_id: Option<[u8; 32]>) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            let schedules = self.equal_schedules(original_balance);
+
+            self.add_grant(address, original_balance, schedules, op_id)
+        }
+
+        /// Add several vested balances, each item is (address, original balance). A bad item does not
+        /// stop the rest, returns the result of each item. Native vesting is funded with the sum of the
+        /// original balances in the same call, then a bad item reverts the whole batch.
+        #[ink(message, payable)]
+        pub fn add_vested_balances(&mut self,
+            items: Vec<(AccountId, u128)>,
+            op_id: Option<[u8; 32]>) -> Result<BatchResult, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            let transferred = self.env().transferred_value();
+            if self.env().caller() != self.vesting_owner {
+                self.refuse_funded(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                if transferred > 0 {
+                    return Err(Error::FundingMismatch { expected: 0, transferred });
+                }
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            // The batch is funded in the same call, a mismatch reverts the transfer
+            let expected = match self.asset_kind {
+                AssetKind::Native => items.iter().fold(0u128, |sum, (_, balance)| sum.saturating_add(*balance)),
+                AssetKind::Asset => 0,
+            };
+            if transferred != expected {
+                return Err(Error::FundingMismatch { expected, transferred });
+            }
+
+            let mut results = Vec::with_capacity(items.len());
+            for (index, (address, original_balance)) in items.into_iter().enumerate() {
+                let schedules = self.equal_schedules(original_balance);
+                let funding = if transferred > 0 { original_balance } else { 0 };
+                let result = self.insert_grant(address, original_balance, schedules, funding).map(|_| ());
+
+                // Funded items cannot be refused one by one, the transfer is reverted instead
+                if let (Err(error), true) = (&result, transferred > 0) {
+                    return Err(error.clone());
+                }
+                self.emit_result(caller, result.clone(), Success::VestedBalanceAdded);
+
+                results.push(BatchItemResult {
+                    index: index as u32,
+                    result: result.map(|()| Success::VestedBalanceAdded),
+                });
+            }
+
+            self.record_op_id(op_id);
+
+            self.finish_batch(results)
+        }
+
+        /// Add vested balances shaped by a template, returns the generated schedules. The original
+        /// balance is split by the template weights, the last schedule takes the rounding remainder.
+        #[ink(message, payable)]
+        pub fn add_vested_balance_from_template(&mut self,
+            address: AccountId,
+            original_balance: u128,
+            template_id: u32,
+            op_id: Option<[u8; 32]>) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            let caller = self.env().caller();
+            let template = match self.templates.get(template_id) {
+                Some(template) => template,
+                None => return self.refuse_funded(caller, Error::TemplateNotFound { template_id }).map(|_| Vec::new()),
+            };
+
+            // Weight the schedules, unlocking after the cliff and every interval after it
+            let curve = VestingCurve::Stepwise {
+                start: self.env().block_timestamp(),
+                cliff: template.cliff,
+                interval: template.interval,
+                weights: template.schedule_weights,
+            };
+            let schedules = match Self::curve_schedules(original_balance, &curve) {
+                Ok(schedules) => schedules,
+                Err(error) => return self.refuse_funded(caller, error).map(|_| Vec::new()),
+            };
+
+            self.add_grant(address, original_balance, schedules, op_id)
+        }
+
+        /// Add a vested balance released along a curve, returns the generated schedules. Each step of the
+        /// curve becomes a schedule unlocking at the step time
+        #[ink(message, payable)]
+        pub fn add_vested_balance_with_curve(&mut self,
+            address: AccountId,
+            original_balance: u128,
+            curve: VestingCurve,
+            op_id: Option<[u8; 32]>) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            let caller = self.env().caller();
+            let schedules = match Self::curve_schedules(original_balance, &curve) {
+                Ok(schedules) => schedules,
+                Err(error) => return self.refuse_funded(caller, error).map(|_| Vec::new()),
+            };
+
+            let schedules = self.add_grant(address, original_balance, schedules, op_id)?;
+            if !schedules.is_empty() {
+                self.curves.insert(address, &curve);
+            }
+
+            Ok(schedules)
+        }
+
+        /// Get the curve a vested balance was added with
+        #[ink(message)]
+        pub fn get_vesting_curve(&self,
+            address: AccountId,) -> Option<VestingCurve> {
+            self.curves.get(address)
+        }
+
+        /// Set the oracle reporting the asset price, e.g. a DIA price feed adapter
+        #[ink(message)]
+        pub fn set_price_oracle(&mut self,
+            price_oracle: Option<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // Observations of the previous oracle do not count
+            self.price_oracle = price_oracle;
+            self.price_observations.set(&Vec::new());
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::PriceOracleSet));
+
+            Ok(())
+        }
+
+        /// Report the asset price, by the price oracle
+        #[ink(message)]
+        pub fn report_price(&mut self,
+            price: u128,) -> Result<(), Error> {
+
+            // Check the caller, it must be the price oracle
+            let caller = self.env().caller();
+            if Some(caller) != self.price_oracle {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // One observation per time, so a gate cannot collect its observations within a block
+            let now = self.env().block_timestamp();
+            let mut prices = self.price_observations.get_or_default();
+            if prices.last().is_some_and(|(reported_at, _)| *reported_at == now) {
+                self.fail(caller, Error::PriceAlreadyReported { reported_at: now })?;
+                return Ok(());
+            }
+            prices.push((now, price));
+            if prices.len() > MAX_PRICE_OBSERVATIONS {
+                prices.remove(0);
+            }
+            self.price_observations.set(&prices);
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::PriceReported));
+
+            Ok(())
+        }
+
+        /// Get the latest oracle price observations (time, price), oldest first
+        #[ink(message)]
+        pub fn get_price_observations(&self,) -> Vec<(Timestamp, u128)> {
+            self.price_observations.get_or_default()
+        }
+
+        /// Set or clear the price condition of a frozen schedule
+        #[ink(message)]
+        pub fn set_price_gate(&mut self,
+            address: AccountId,
+            schedule_number: u16,
+            price_gate: Option<PriceGate>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let status = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)
+                .map(|(vested_balance, index)| vested_balance.vested_balance_schedules[index].status);
+            let result = match status {
+                Err(error) => Err(error),
+                Ok(status) if status != ScheduleStatus::Frozen => Err(Error::VestedBalanceScheduleNotFrozen {
+                    address,
+                    schedule_number,
+                    status,
+                }),
+                Ok(_) => match price_gate {
+                    Some(price_gate) if price_gate.observations == 0
+                        || price_gate.observations as usize > MAX_PRICE_OBSERVATIONS
+                        || price_gate.twap_window == Some(0) => Err(Error::InvalidPriceGate),
+                    Some(price_gate) => {
+                        self.price_gates.insert((address, schedule_number), &price_gate);
+                        Ok(())
+                    }
+                    None => {
+                        self.price_gates.remove((address, schedule_number));
+                        Ok(())
+                    }
+                },
+            };
+
+            self.finish(caller, result, Success::PriceGateSet)
+        }
+
+        /// Get the price condition of a schedule and whether it is met
+        #[ink(message)]
+        pub fn get_price_gate(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Option<(PriceGate, bool)> {
+            let prices = self.price_observations.get_or_default();
+            let now = self.env().block_timestamp();
+            self.price_gates.get((address, schedule_number))
+                .map(|price_gate| (price_gate, Self::price_gate_open(&self.price_gates, &prices, now, (address, schedule_number))))
+        }
+
+        /// Add a vested balance on the terms of an existing grant, returns the generated schedules.
+        /// The schedule count, relative weights and unlock times are copied, the amount is new.
+        #[ink(message, payable)]
+        pub fn clone_grant_terms(&mut self,
+            from: AccountId,
+            to: AccountId,
+            original_balance: u128,
+            op_id: Option<[u8; 32]>) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            let caller = self.env().caller();
+            let source = match self.vested_balances.iter().find(|v| v.address == from) {
+                Some(source) => source,
+                None => return self.refuse_funded(caller, Error::VestedBalanceNotFound { address: from }).map(|_| Vec::new()),
+            };
+
+            let weights: Vec<u128> = source.vested_balance_schedules.iter().map(|s| s.schedule_balance).collect();
+            let schedules = Self::split_by_weights(original_balance, &weights)
+                .into_iter()
+                .zip(source.vested_balance_schedules.iter())
+                .map(|(schedule_balance, s)| Self::new_schedule(s.schedule_number, schedule_balance, s.unlock_time))
+                .collect();
+
+            self.add_grant(to, original_balance, schedules, op_id)
+        }
+
+        /// Create a vesting template, returns the template id
+        #[ink(message)]
+        pub fn create_template(&mut self,
+            name: Vec<u8>,
+            schedule_weights: Vec<u32>,
+            cliff: Timestamp,
+            interval: Timestamp,) -> Result<Option<u32>, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(None);
+            }
+
+            // The weights must give a positive total and fit in the schedule numbers
+            if schedule_weights.len() > u16::MAX as usize || schedule_weights.iter().all(|w| *w == 0) {
+                self.fail(caller, Error::InvalidTemplate)?;
+                return Ok(None);
+            }
+
+            let template_id = self.next_template_id;
+            self.next_template_id += 1;
+            self.templates.insert(template_id, &VestingTemplate {
+                name,
+                schedule_weights,
+                cliff,
+                interval,
+            });
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::TemplateCreated));
+
+            Ok(Some(template_id))
+        }
+
+        /// Get a vesting template
+        #[ink(message)]
+        pub fn get_template(&self,
+            template_id: u32,) -> Option<VestingTemplate> {
+            self.templates.get(template_id)
+        }
+
+        /// Get a vested balance per address, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_vested_balance(
+            &self,
+            address: AccountId,
+        ) -> Option<VestedBalance> {
+            if !self.can_view(address) {
+                return None;
+            }
+            self.vested_balances
+                .iter()
+                .find(|v| v.address == address)
+                .cloned()
+                .map(|v| self.to_view(v))
+        }
+
+        /// Get a single schedule of a vested balance, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_schedule(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Option<VestedBalanceSchedule> {
+            if !self.can_view(address) {
+                return None;
+            }
+            self.vested_balances
+                .iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .cloned()
+                .map(|mut schedule| {
+                    schedule.particulars = self.schedule_particulars.get(schedule.schedule_id).unwrap_or_default();
+                    schedule
+                })
+        }
+
+        /// Get a vested balance per address, tagged with its encoding version
+        #[ink(message)]
+        pub fn get_vested_balance_versioned(&self,
+            address: AccountId,) -> Option<VersionedVestedBalance> {
+            self.get_vested_balance(address).map(VersionedVestedBalance::from)
+        }
+
+        /// Export a page of vested balances, tagged with their encoding version
+        #[ink(message)]
+        pub fn export_vested_balances(&self,
+            offset: u32,
+            limit: u32,) -> Vec<VersionedVestedBalance> {
+            if !self.can_view_all() {
+                return Vec::new();
+            }
+            self.vested_balances
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .cloned()
+                .map(|v| VersionedVestedBalance::from(self.to_view(v)))
+                .collect()
+        }
+
+        /// Get the number of state chunks, see `get_state_chunk`
+        #[ink(message)]
+        pub fn get_state_chunk_count(&self,) -> u32 {
+            self.vested_balances.len().div_ceil(STATE_CHUNK_SIZE) as u32
+        }
+
+        /// Get a SCALE-encoded chunk of the vested balances, `STATE_CHUNK_SIZE` balances per chunk in storage order.
+        /// Empty past the last chunk
+        #[ink(message)]
+        pub fn get_state_chunk(&self,
+            chunk_index: u32,) -> Vec<u8> {
+            if !self.can_view_all() {
+                return Vec::new();
+            }
+            self.vested_balances
+                .chunks(STATE_CHUNK_SIZE)
+                .nth(chunk_index as usize)
+                .map(|chunk| self.chunk_view(chunk).encode())
+                .unwrap_or_default()
+        }
+
+        /// Get the digest of the whole program: the Blake2x256 hash of the encoded list of the Blake2x256 hashes
+        /// of every state chunk, so a synced copy can be checked without re-reading per-account getters
+        #[ink(message)]
+        pub fn get_state_digest(&self,) -> Hash {
+            let chunk_hashes: Vec<Hash> = self.vested_balances
+                .chunks(STATE_CHUNK_SIZE)
+                .map(|chunk| Hash::from(self.env().hash_bytes::<Blake2x256>(&self.chunk_view(chunk).encode())))
+                .collect();
+            Hash::from(self.env().hash_encoded::<Blake2x256, _>(&chunk_hashes))
+        }
+
+        /// Get all vested balances, empty for others than the owner and the viewers when private
+        #[ink(message)]
+        pub fn get_all_vested_balance(&self,) -> Vec<VestedBalance> {
+            if !self.can_view_all() {
+                return Vec::new();
+            }
+            self.vested_balances.iter().cloned().map(|v| self.to_view(v)).collect()
+        }
+
+        /// Get a page of vested balance summaries, without the schedules
+        #[ink(message)]
+        pub fn get_summaries(&self,
+            offset: u32,
+            limit: u32,) -> Vec<VestedSummary> {
+            if !self.can_view_all() {
+                return Vec::new();
+            }
+            self.vested_balances
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(Self::summary)
+                .collect()
+        }
+
+        /// Get the summaries of a list of addresses in the order given, none for addresses without a vested balance.
+        /// When private, others than the owner and the viewers only get their own summary
+        #[ink(message)]
+        pub fn get_balances_for(&self,
+            addresses: Vec<AccountId>,) -> Vec<Option<VestedSummary>> {
+            let caller = self.env().caller();
+            let view_all = self.can_view_all();
+            addresses
+                .iter()
+                .map(|address| {
+                    if !view_all && *address != caller {
+                        return None;
+                    }
+                    self.vested_balances.iter().find(|v| v.address == *address).map(Self::summary)
+                })
+                .collect()
+        }
+
+        /// Get the position of an address as a report for direct rendering, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_position_report(&self,
+            address: AccountId,) -> Option<PositionReport> {
+
+            if !self.can_view(address) {
+                return None;
+            }
+            let vested_balance = self.vested_balances.iter().find(|v| v.address == address)?;
+
+            let mut schedules: Vec<ScheduleReport> = vested_balance.vested_balance_schedules
+                .iter()
+                .map(|s| ScheduleReport {
+                    schedule_number: s.schedule_number,
+                    status: s.status,
+                    amount: self.to_decimal_amount(s.schedule_balance),
+                    unlock_time: s.unlock_time,
+                    recipient: s.recipient_address,
+                    // The tx-hash is kept in the particulars once approved
+                    settlement_hash: match s.status {
+                        ScheduleStatus::Transferred | ScheduleStatus::Approved => self.schedule_particulars.get(s.schedule_id).filter(|p| !p.is_empty()),
+                        _ => None,
+                    },
+                })
+                .collect();
+            schedules.sort_by_key(|s| s.schedule_number);
+
+            Some(PositionReport {
+                address,
+                original: self.to_decimal_amount(vested_balance.original_balance),
+                remaining: self.to_decimal_amount(Self::remaining_balance(vested_balance)),
+                transferred: self.to_decimal_amount(vested_balance.transferred_balance),
+                suspended: self.suspensions.contains(address),
+                version: vested_balance.version,
+                schedules,
+            })
+        }
+
+        /// Get the health of the contract in one view, for monitoring bots. The balance totals of an address
+        /// are checked with `verify_balances`
+        #[ink(message)]
+        pub fn health(&self,) -> HealthReport {
+
+            let now = self.env().block_timestamp();
+            let obligations = self.obligations();
+            let reserves = (self.asset_kind == AssetKind::Native).then_some(self.native_pool);
+
+            let mut overdue_unlocks: u32 = 0;
+            let mut stale_requests: u32 = 0;
+            for vested_balance in self.vested_balances.iter() {
+                let suspended = self.suspensions.contains(vested_balance.address);
+                for schedule in vested_balance.vested_balance_schedules.iter() {
+                    if !suspended && schedule.status == ScheduleStatus::Frozen && schedule.unlock_time.is_some_and(|t| t <= now) {
+                        overdue_unlocks += 1;
+                    }
+                    if schedule.status == ScheduleStatus::Requested && self.request_ttl.is_some_and(|ttl| {
+                        schedule.requested_at.is_some_and(|t| t.saturating_add(ttl) <= now)
+                    }) {
+                        stale_requests += 1;
+                    }
+                }
+            }
+
+            HealthReport {
+                suspended: self.suspended_count,
+                obligations,
+                reserves,
+                solvent: reserves.is_none_or(|reserves| obligations <= reserves),
+                overdue_unlocks,
+                stale_requests,
+                state_version: self.state_version,
+            }
+        }
+
+        /// Get the position report of an address, tagged with its encoding version
+        #[ink(message)]
+        pub fn get_position_report_versioned(&self,
+            address: AccountId,) -> Option<VersionedPositionReport> {
+            self.get_position_report(address).map(VersionedPositionReport::from)
+        }
+
+        /// Get the number of schedules and total amount per status across all vested balances
+        #[ink(message)]
+        pub fn get_status_breakdown(&self,) -> Vec<(ScheduleStatus, u32, u128)> {
+            ScheduleStatus::ALL
+                .iter()
+                .map(|status| {
+                    let (count, amount) = self.status_totals
+                        .get(*status as usize)
+                        .copied()
+                        .unwrap_or((0, 0));
+                    (*status, count, amount)
+                })
+                .collect()
+        }
+
+        /// Get the contract-wide balance totals
+        #[ink(message)]
+        pub fn get_totals(&self,) -> VestingTotals {
+            let amount = |status: ScheduleStatus| self.status_totals
+                .get(status as usize)
+                .map(|(_, amount)| *amount)
+                .unwrap_or(0);
+
+            VestingTotals {
+                original_balance: self.total_original_balance,
+                frozen_balance: amount(ScheduleStatus::Frozen),
+                liquid_balance: amount(ScheduleStatus::Liquid),
+                requested_balance: amount(ScheduleStatus::Requested) + amount(ScheduleStatus::Approved),
+                transferred_balance: amount(ScheduleStatus::Transferred),
+                disputed_balance: amount(ScheduleStatus::Disputed),
+                forfeited_balance: amount(ScheduleStatus::Forfeited),
+                state_version: self.state_version,
+            }
+        }
+
+        /// Get the version of the contract state, unchanged versions mean nothing changed since the last poll
+        #[ink(message)]
+        pub fn get_state_version(&self,) -> u64 {
+            self.state_version
+        }
+
+        /// Get the n largest holders by remaining (non-transferred) balance, empty for others than the owner and
+        /// the viewers when private
+        #[ink(message)]
+        pub fn get_top_holders(&self, n: u32) -> Vec<(AccountId, u128)> {
+            if !self.can_view_all() {
+                return Vec::new();
+            }
+            self.holders_index
+                .iter()
+                .take(n as usize)
+                .copied()
+                .collect()
+        }
+
+        /// Get the vested balance of the caller
+        #[ink(message)]
+        pub fn my_vested_balance(&self,) -> Option<VestedBalance> {
+            self.get_vested_balance(self.env().caller())
+        }
+
+        /// Get the requested (and approved, not yet transferred) schedules of the caller
+        #[ink(message)]
+        pub fn my_pending_requests(&self,) -> Vec<VestedBalanceSchedule> {
+            let caller = self.env().caller();
+            self.vested_balances
+                .iter()
+                .find(|v| v.address == caller)
+                .map(|v| v.vested_balance_schedules
+                    .iter()
+                    .filter(|s| matches!(s.status, ScheduleStatus::Requested | ScheduleStatus::Approved))
+                    .cloned()
+                    .collect())
+                .unwrap_or_default()
+        }
+    
+        /// Set the unlock time of a schedule number for all frozen and future vested balances.
+        /// Governed programs set unlock times through the governor only
+        #[ink(message)]
+        pub fn set_unlock_time(&mut self,
+            schedule_number: u16,
+            unlock_time: Timestamp,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            if let Err(error) = self.check_not_governed() {
+                self.fail(caller, error)?;
+                return Ok(());
+            }
+
+            self.apply_unlock_time(schedule_number, unlock_time);
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::VestedBalanceScheduleUnlockTimeSet));
+
+            Ok(())
+        }
+
+        /// Get the frozen schedules (beneficiary, schedule number, amount) unlocking within a time window. When private the others only see their own
+        #[ink(message)]
+        pub fn get_unlocks_between(&self,
+            from: Timestamp,
+            to: Timestamp,) -> Vec<(AccountId, u16, u128)> {
+            let mut unlocks = Vec::new();
+            for vested_balance in self.vested_balances.iter().filter(|v| self.can_view(v.address)) {
+                for schedule in vested_balance.vested_balance_schedules.iter() {
+                    if schedule.status != ScheduleStatus::Frozen {
+                        continue;
+                    }
+                    if let Some(unlock_time) = schedule.unlock_time {
+                        if unlock_time >= from && unlock_time <= to {
+                            unlocks.push((vested_balance.address, schedule.schedule_number, schedule.schedule_balance));
+                        }
+                    }
+                }
+            }
+            unlocks
+        }
+
+        /// Thaw frozen balances, returns the result of each frozen schedule of the schedule number, indexed by
+        /// the position of its vested balance. Suspended and price-gated schedules stay frozen
+        #[ink(message)]
+        pub fn thaw_vested_balances(&mut self,
+            schedule_number: u16,
+            op_id: Option<[u8; 32]>) -> Result<BatchResult, Error> {
+            
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            } 
+
+            // Governed programs thaw through the governor only
+            if let Err(error) = self.check_not_governed() {
+                self.fail(caller, error)?;
+                return Ok(Vec::new());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            let results = self.thaw_schedule_number(schedule_number);
+
+            self.record_op_id(op_id);
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::VestedBalanceScheduleThawed));
+
+            // Schedules left frozen are expected, they do not revert the thaw in strict mode
+            Ok(results)
+        }
+
+        /// Hand the thaws to a governance contract. Once set, only the governor can change it, through
+        /// `execute_governance_action`
+        #[ink(message)]
+        pub fn set_governor(&mut self,
+            governor: AccountId,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = self.check_not_governed();
+            if result.is_ok() {
+                self.governor = Some(governor);
+            }
+
+            self.finish(caller, result, Success::GovernorSet)
+        }
+
+        /// Get the governance contract controlling the thaws
+        #[ink(message)]
+        pub fn get_governor(&self,) -> Option<AccountId> {
+            self.governor
+        }
+
+        /// Execute a passed governance proposal, by the governor only. A thaw returns the result of each
+        /// frozen schedule of the schedule number like `thaw_vested_balances`, the other actions none
+        #[ink(message)]
+        pub fn execute_governance_action(&mut self,
+            action: GovernanceAction,) -> Result<BatchResult, Error> {
+
+            // Check the caller, it must be the governor
+            let caller = self.env().caller();
+            if Some(caller) != self.governor {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            let result = match action {
+                GovernanceAction::ThawVestedBalances { schedule_number } => {
+                    let results = self.thaw_schedule_number(schedule_number);
+                    Ok((Success::VestedBalanceScheduleThawed, results))
+                }
+                GovernanceAction::ThawSchedule { address, schedule_number } => self
+                    .thaw_schedule(address, schedule_number)
+                    .map(|()| (Success::VestedBalanceScheduleThawed, Vec::new())),
+                GovernanceAction::SetUnlockTime { schedule_number, unlock_time } => {
+                    self.apply_unlock_time(schedule_number, unlock_time);
+                    Ok((Success::VestedBalanceScheduleUnlockTimeSet, Vec::new()))
+                }
+                GovernanceAction::SetGovernor { governor } => {
+                    self.governor = governor;
+                    Ok((Success::GovernorSet, Vec::new()))
+                }
+            };
+
+            // Schedules left frozen are expected, they do not revert the thaw in strict mode
+            match result {
+                Ok((success, results)) => {
+                    self.emit_status(caller, VestingStatus::EmitSuccess(success));
+                    Ok(results)
+                }
+                Err(error) => {
+                    self.fail(caller, error)?;
+                    Ok(Vec::new())
+                }
+            }
+        }
+
+        /// Snapshot the voting weights, the frozen and liquid balance of every beneficiary, by the owner or the
+        /// governor. Returns the snapshot id
+        #[ink(message)]
+        pub fn take_snapshot(&mut self,) -> Result<Option<u32>, Error> {
+
+            // Check the caller, it must be the owner or the governor
+            let caller = self.env().caller();
+            if caller != self.vesting_owner && Some(caller) != self.governor {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(None);
+            }
+
+            let snapshot_id = self.next_snapshot_id;
+            self.next_snapshot_id += 1;
+
+            // Weights accrue to the delegate of each beneficiary
+            let mut total_weight: u128 = 0;
+            for vested_balance in self.vested_balances.iter() {
+                let weight = Self::voting_weight(vested_balance);
+                if weight > 0 {
+                    let holder = self.delegates.get(vested_balance.address).unwrap_or(vested_balance.address);
+                    let key = (snapshot_id, holder);
+                    let accrued = self.snapshot_weights.get(key).unwrap_or(0);
+                    self.snapshot_weights.insert(key, &(accrued + weight));
+                    total_weight += weight;
+                }
+            }
+            self.snapshots.insert(snapshot_id, &SnapshotInfo {
+                block_number: self.env().block_number(),
+                taken_at: self.env().block_timestamp(),
+                total_weight,
+            });
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::SnapshotTaken));
+
+            Ok(Some(snapshot_id))
+        }
+
+        /// Delegate the voting weight of the caller to another address, replacing any previous delegate.
+        /// Delegating to itself undelegates. Applies from the next snapshot
+        #[ink(message)]
+        pub fn delegate_weight(&mut self,
+            to: AccountId,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let to_delegate = if to == caller { None } else { Some(to) };
+            let result = self.set_delegate(caller, to_delegate);
+
+            self.finish(caller, result, Success::DelegateSet)
+        }
+
+        /// Take back the voting weight of the caller from its delegate. Applies from the next snapshot
+        #[ink(message)]
+        pub fn undelegate_weight(&mut self,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let result = self.set_delegate(caller, None);
+
+            self.finish(caller, result, Success::DelegateSet)
+        }
+
+        /// Get the delegate receiving the voting weight of an address, none when it votes itself
+        #[ink(message)]
+        pub fn get_delegate(&self,
+            address: AccountId,) -> Option<AccountId> {
+            self.delegates.get(address)
+        }
+
+        /// Get a voting weight snapshot
+        #[ink(message)]
+        pub fn get_snapshot(&self,
+            snapshot_id: u32,) -> Option<SnapshotInfo> {
+            self.snapshots.get(snapshot_id)
+        }
+
+        /// Get the voting weight of an address at a snapshot, its own and delegated to it, zero for unknown snapshots
+        #[ink(message)]
+        pub fn voting_weight_of(&self,
+            address: AccountId,
+            snapshot_id: u32,) -> u128 {
+            self.snapshot_weights.get((snapshot_id, address)).unwrap_or(0)
+        }
+
+        /// Report what `thaw_vested_balances` would thaw for a schedule number, without changing anything.
+        /// Empty for others than the owner and the viewers when private
+        #[ink(message)]
+        pub fn simulate_thaw(&self,
+            schedule_number: u16,) -> SimulationResult {
+
+            if !self.can_view_all() {
+                return SimulationResult::default();
+            }
+
+            let prices = self.price_observations.get_or_default();
+            let now = self.env().block_timestamp();
+            let mut simulation = SimulationResult::default();
+            for vested_balance in self.vested_balances.iter() {
+                let frozen: Vec<u128> = vested_balance.vested_balance_schedules
+                    .iter()
+                    .filter(|s| s.schedule_number == schedule_number && s.status == ScheduleStatus::Frozen)
+                    .filter(|_| Self::price_gate_open(&self.price_gates, &prices, now, (vested_balance.address, schedule_number)))
+                    .map(|s| s.schedule_balance)
+                    .collect();
+                if frozen.is_empty() {
+                    continue;
+                }
+
+                // Suspended balances stay frozen
+                if self.suspensions.contains(vested_balance.address) {
+                    simulation.suspended.push(vested_balance.address);
+                    continue;
+                }
+
+                let amount: u128 = frozen.iter().sum();
+                simulation.schedule_count += frozen.len() as u32;
+                simulation.total_amount += amount;
+                simulation.beneficiaries.push((vested_balance.address, amount));
+            }
+
+            simulation
+        }
+
+        /// Thaw individual frozen schedules, each item is (address, schedule number).
+        /// A bad item does not stop the rest, returns the result of each item
+        #[ink(message)]
+        pub fn thaw_schedules(&mut self,
+            items: Vec<(AccountId, u16)>,
+            op_id: Option<[u8; 32]>) -> Result<BatchResult, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            // Governed programs thaw through the governor only
+            if let Err(error) = self.check_not_governed() {
+                self.fail(caller, error)?;
+                return Ok(Vec::new());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            let mut results = Vec::with_capacity(items.len());
+            for (index, (address, schedule_number)) in items.into_iter().enumerate() {
+                let result = self.thaw_schedule(address, schedule_number);
+                self.emit_result(caller, result.clone(), Success::VestedBalanceScheduleThawed);
+
+                results.push(BatchItemResult {
+                    index: index as u32,
+                    result: result.map(|()| Success::VestedBalanceScheduleThawed),
+                });
+            }
+
+            self.record_op_id(op_id);
+
+            self.finish_batch(results)
+        }
+
+        /// Thaw frozen schedules whose unlock time has passed, callable by anyone or by the governor only
+        /// in governed programs. At most `limit` schedules are thawed per call, returns the number of thawed schedules
+        #[ink(message)]
+        pub fn process_due_unlocks(&mut self,
+            limit: u32,) -> Result<u32, Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(0);
+            }
+
+            // Governed programs thaw through the governor only
+            if let Some(governor) = self.governor.filter(|governor| *governor != caller) {
+                self.fail(caller, Error::GovernorRequired { governor })?;
+                return Ok(0);
+            }
+
+            self.set_locked(true);
+            let result = self.do_process_due_unlocks(limit);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Body of `process_due_unlocks`, run under the reentrancy lock
+        fn do_process_due_unlocks(&mut self,
+            limit: u32,) -> Result<u32, Error> {
+
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+            let prices = self.price_observations.get_or_default();
+
+            let mut thawed: u32 = 0;
+            for vested_balance in self.vested_balances.iter_mut() {
+                if thawed >= limit {
+                    break;
+                }
+                if self.suspensions.contains(vested_balance.address) {
+                    continue;
+                }
+
+                let address = vested_balance.address;
+                let thawed_before = thawed;
+                let mut thawed_balance: u128 = 0;
+                for schedule in vested_balance.vested_balance_schedules.iter_mut() {
+                    if thawed >= limit {
+                        break;
+                    }
+                    if schedule.status == ScheduleStatus::Frozen && schedule.unlock_time.is_some_and(|t| t <= now)
+                        && Self::price_gate_open(&self.price_gates, &prices, now, (address, schedule.schedule_number)) {
+                        schedule.status = ScheduleStatus::Liquid;
+                        Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Frozen), Some(ScheduleStatus::Liquid), schedule.schedule_balance);
+                        Self::record_history(&mut self.schedule_history, (address, schedule.schedule_number), Self::status_entry(ScheduleStatus::Frozen, ScheduleStatus::Liquid, caller));
+                        thawed_balance += schedule.schedule_balance;
+                        thawed += 1;
+                    }
+                }
+
+                if thawed > thawed_before {
+                    Self::move_balance(vested_balance, ScheduleStatus::Frozen, ScheduleStatus::Liquid, thawed_balance);
+                }
+            }
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::DueUnlocksProcessed));
+
+            self.pay_keeper_bounty(caller, thawed);
+
+            Ok(thawed)
+        }
+
+        /// Return requested schedules older than the request time-to-live to liquid, callable by anyone.
+        /// At most `limit` requests are expired per call, returns the number of expired requests
+        #[ink(message)]
+        pub fn expire_requests(&mut self,
+            limit: u32,) -> Result<u32, Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(0);
+            }
+
+            self.set_locked(true);
+            let result = self.do_expire_requests(limit);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Body of `expire_requests`, run under the reentrancy lock
+        fn do_expire_requests(&mut self,
+            limit: u32,) -> Result<u32, Error> {
+
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            let mut expired: u32 = 0;
+            if let Some(request_ttl) = self.request_ttl {
+                for vested_balance in self.vested_balances.iter_mut() {
+                    if expired >= limit {
+                        break;
+                    }
+
+                    let expired_before = expired;
+                    let mut expired_balance: u128 = 0;
+                    for schedule in vested_balance.vested_balance_schedules.iter_mut() {
+                        if expired >= limit {
+                            break;
+                        }
+                        if schedule.status == ScheduleStatus::Requested && schedule.requested_at.is_some_and(|t| t.saturating_add(request_ttl) <= now) {
+                            schedule.status = ScheduleStatus::Liquid;
+                            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Requested), Some(ScheduleStatus::Liquid), schedule.schedule_balance);
+                            schedule.recipient_address = None;
+                            if let Some(request_id) = schedule.request_id.take() {
+                                self.requests.remove(request_id);
+                            }
+                            schedule.requested_at = None;
+                            schedule.memo = Vec::new();
+                            expired_balance += schedule.schedule_balance;
+                            expired += 1;
+                        }
+                    }
+
+                    if expired > expired_before {
+                        Self::move_balance(vested_balance, ScheduleStatus::Requested, ScheduleStatus::Liquid, expired_balance);
+                    }
+                }
+            }
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ExpiredRequestsProcessed));
+
+            self.pay_keeper_bounty(caller, expired);
+
+            Ok(expired)
+        }
+
+        /// Set the bounty paid to keepers per processed item
+        #[ink(message)]
+        pub fn set_keeper_bounty(&mut self,
+            keeper_bounty: Balance,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.keeper_bounty = keeper_bounty;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::KeeperBountySet));
+
+            Ok(())
+        }
+
+        /// Fund the keeper pool with the transferred native balance, by the owner. Refused funding is reverted
+        #[ink(message, payable)]
+        pub fn fund_keeper_pool(&mut self) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if caller != self.vesting_owner {
+                return self.refuse_funded(caller, Error::BadOrigin);
+            }
+
+            self.keeper_pool += self.env().transferred_value();
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::KeeperPoolFunded));
+
+            Ok(())
+        }
+
+        /// Get the keeper bounty per item and the remaining keeper pool
+        #[ink(message)]
+        pub fn get_keeper_info(&self,) -> (Balance, Balance) {
+            (self.keeper_bounty, self.keeper_pool)
+        }
+
+        /// Request for transfer with an optional memo for the approver, returns the request id
+        #[ink(message)]
+        pub fn request_transfer(&mut self,
+            schedule_number: u16,
+            recipient_address: AccountId,
+            memo: Vec<u8>) -> Result<Option<u64>, Error> {
+
+            let caller = self.env().caller();
+
+            let result = self.create_transfer_request(caller, schedule_number, Recipient::Account(recipient_address), memo);
+            let request_id = result.as_ref().ok().copied();
+            if request_id.is_some() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::VestedBalanceScheduleRequested)?;
+
+            Ok(request_id)
+        }
+
+        /// Request for transfer to an account on a sibling parachain. The approval dispatches an XCM reserve
+        /// transfer and records the message hash as the tx-hash of the schedule
+        #[ink(message)]
+        pub fn request_xcm_transfer(&mut self,
+            schedule_number: u16,
+            destination: XcmDestination,
+            memo: Vec<u8>) -> Result<Option<u64>, Error> {
+
+            let caller = self.env().caller();
+
+            let result = self.create_transfer_request(caller, schedule_number, Recipient::Xcm(destination), memo);
+            let request_id = result.as_ref().ok().copied();
+            if request_id.is_some() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::VestedBalanceScheduleRequested)?;
+
+            Ok(request_id)
+        }
+
+        /// Request for transfer to an account or to an Ethereum-style address
+        #[ink(message)]
+        pub fn request_transfer_to(&mut self,
+            schedule_number: u16,
+            recipient: Recipient,
+            memo: Vec<u8>) -> Result<Option<u64>, Error> {
+
+            let caller = self.env().caller();
+
+            let result = self.create_transfer_request(caller, schedule_number, recipient, memo);
+            let request_id = result.as_ref().ok().copied();
+            if request_id.is_some() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::VestedBalanceScheduleRequested)?;
+
+            Ok(request_id)
+        }
+
+        /// Commit to the recipient of a liquid schedule without announcing it, the commitment is the Blake2x256
+        /// hash of the encoded (recipient, salt). The request is made by `reveal_recipient` just before approval
+        #[ink(message)]
+        pub fn request_transfer_commit(&mut self,
+            schedule_number: u16,
+            commitment: Hash,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = self.commit_recipient(caller, schedule_number, commitment);
+            if result.is_ok() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result, Success::RecipientCommitted)
+        }
+
+        /// Reveal the committed recipient of a liquid schedule and request its transfer, returns the request id
+        #[ink(message)]
+        pub fn reveal_recipient(&mut self,
+            schedule_number: u16,
+            recipient: Recipient,
+            salt: [u8; 32],
+            memo: Vec<u8>) -> Result<Option<u64>, Error> {
+
+            let caller = self.env().caller();
+
+            let result = self.check_recipient_commitment(caller, schedule_number, recipient, salt)
+                .and_then(|_| self.create_transfer_request(caller, schedule_number, recipient, memo));
+            let request_id = result.as_ref().ok().copied();
+            if request_id.is_some() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::VestedBalanceScheduleRequested)?;
+
+            Ok(request_id)
+        }
+
+        /// Get the recipient commitment of a liquid schedule, none once revealed
+        #[ink(message)]
+        pub fn get_recipient_commitment(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Option<Hash> {
+            self.recipient_commitments.get((address, schedule_number))
+        }
+
+        /// Get the recipient of a requested or transferred schedule
+        #[ink(message)]
+        pub fn get_recipient(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Option<Recipient> {
+            self.recipient_of(address, schedule_number)
+        }
+
+        /// Get the sibling parachain account a requested schedule is transferred to, none for local transfers
+        #[ink(message)]
+        pub fn get_xcm_destination(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Option<XcmDestination> {
+            self.xcm_destinations.get((address, schedule_number))
+        }
+
+        /// Set or clear the dead-man switch. When no approver approves anything for the given number of blocks,
+        /// pending requests of native vesting can be executed by the beneficiaries or by a keeper
+        #[ink(message)]
+        pub fn set_dead_man_period(&mut self,
+            dead_man_period: Option<BlockNumber>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // Setting the switch counts as owner activity
+            self.dead_man_period = dead_man_period;
+            self.last_approval_block = self.env().block_number();
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::DeadManPeriodSet));
+
+            Ok(())
+        }
+
+        /// Get the dead-man switch period and the block of the last approval
+        #[ink(message)]
+        pub fn get_dead_man_switch(&self,) -> (Option<BlockNumber>, BlockNumber) {
+            (self.dead_man_period, self.last_approval_block)
+        }
+
+        /// Execute a pending request without the owner once the dead-man switch fired, by anyone.
+        /// The switch fires a period after the last approval or the request, whichever is later, and stands
+        /// in for a single approval of the amount tier. The payout goes to the requested recipient, so a
+        /// keeper can run it for the beneficiary
+        #[ink(message)]
+        pub fn execute_abandoned_request(&mut self,
+            address: AccountId,
+            schedule_number: u16,) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            // Only native vesting can be paid out by the contract itself
+            let requested_block = self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .and_then(|s| s.request_id)
+                .and_then(|request_id| self.requests.get(request_id))
+                .map_or(0, |request| request.requested_block);
+            let executable_at = self.dead_man_period
+                .filter(|_| self.asset_kind == AssetKind::Native)
+                .map(|period| self.last_approval_block.max(requested_block).saturating_add(period));
+
+            self.set_locked(true);
+            let result = match executable_at {
+                Some(block_number) if self.env().block_number() >= block_number => self
+                    .check_verified(address, schedule_number)
+                    .and_then(|()| self.check_abandoned_approvals(address, schedule_number))
+                    .and_then(|()| self.approve_requested(caller, address, schedule_number, Vec::new(), Vec::new())),
+                _ => Err(Error::RequestNotExecutable { executable_at }),
+            };
+            self.set_locked(false);
+
+            self.finish(caller, result.clone().map(|_| ()), Success::AbandonedRequestExecuted)?;
+
+            if let Ok((recipient, amount)) = result {
+                self.notify_recipient(caller, recipient, address, amount);
+            }
+
+            Ok(())
+        }
+
+        /// Request for transfer to the caller's own address, the default recipient, returns the request id
+        #[ink(message)]
+        pub fn request_transfer_to_self(&mut self,
+            schedule_number: u16,) -> Result<Option<u64>, Error> {
+
+            let caller = self.env().caller();
+            self.request_transfer(schedule_number, caller, Vec::new())
+        }
+
+        /// Request for transfer on behalf of a beneficiary holding an ECDSA account, submitted by any relayer.
+        /// The beneficiary signs the blake2x256 hash of the SCALE encoded
+        /// (contract, "request_transfer", beneficiary, schedule_number, recipient_address, memo, nonce)
+        #[ink(message)]
+        pub fn request_transfer_signed(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            recipient_address: AccountId,
+            memo: Vec<u8>,
+            nonce: u64,
+            signature: [u8; 65]) -> Result<Option<u64>, Error> {
+
+            let caller = self.env().caller();
+
+            let payload = (
+                self.env().account_id(),
+                b"request_transfer",
+                beneficiary,
+                schedule_number,
+                recipient_address,
+                &memo,
+                nonce,
+            );
+            let result = self.use_signature(beneficiary, &payload, nonce, &signature)
+                .and_then(|()| self.create_transfer_request(beneficiary, schedule_number, Recipient::Account(recipient_address), memo));
+            let request_id = result.as_ref().ok().copied();
+            if request_id.is_some() {
+                self.record_activity(beneficiary);
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::VestedBalanceScheduleRequested)?;
+
+            Ok(request_id)
+        }
+
+        /// Get a pending transfer request
+        #[ink(message)]
+        pub fn get_request(&self,
+            request_id: u64,) -> Option<TransferRequest> {
+            self.find_request(request_id).ok()
+        }
+
+        /// Approve a pending transfer request by its id
+        #[ink(message)]
+        pub fn approve_request(&mut self,
+            request_id: u64,
+            tx_hash: Vec<u8>,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            // Check the caller, it must be an approver
+            if !self.is_approver(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let request = match self.find_request(request_id) {
+                Ok(request) => request,
+                Err(error) => {
+                    self.fail(caller, error)?;
+                    return Ok(());
+                }
+            };
+
+            // Collect the approvals required by the amount tier
+            let approved = self.collect_approval(caller, request.beneficiary, request.schedule_number, op_id)
+                .or_else(|error| self.fail(caller, error).map(|()| false))?;
+            if !approved {
+                return Ok(());
+            }
+
+            self.set_locked(true);
+            let result = self.do_approve_transfer(request.beneficiary, request.schedule_number, tx_hash, Vec::new(), op_id);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Set or clear the escrow of a grant. Requests above the threshold need `escrow_approve` from the escrow
+        /// before any approval goes through. The owner sets an escrow or lowers its threshold, removing,
+        /// replacing or raising the threshold of an escrow is done by the escrow itself
+        #[ink(message)]
+        pub fn set_grant_escrow(&mut self,
+            address: AccountId,
+            escrow: Option<AccountId>,
+            threshold: u128,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner or the current escrow
+            let caller = self.env().caller();
+            let current = self.escrows.get(address);
+            if caller != self.vesting_owner && current.as_ref().map(|c| c.escrow) != Some(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = if !self.vested_balances.iter().any(|v| v.address == address) {
+                Err(Error::VestedBalanceNotFound { address })
+            } else if let Some(current) = current.filter(|c| {
+                c.escrow != caller && (escrow != Some(c.escrow) || threshold > c.threshold)
+            }) {
+                Err(Error::EscrowConsentRequired { escrow: current.escrow })
+            } else {
+                match escrow {
+                    Some(escrow) => {
+                        self.escrows.insert(address, &EscrowConfig { escrow, threshold });
+                    }
+                    None => self.escrows.remove(address),
+                }
+                Ok(())
+            };
+
+            self.finish(caller, result, Success::GrantEscrowSet)
+        }
+
+        /// Get the escrow of a grant
+        #[ink(message)]
+        pub fn get_grant_escrow(&self,
+            address: AccountId,) -> Option<EscrowConfig> {
+            self.escrows.get(address)
+        }
+
+        /// Approve a pending transfer request as the escrow of its grant
+        #[ink(message)]
+        pub fn escrow_approve(&mut self,
+            request_id: u64,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = self.find_request(request_id).and_then(|request| {
+                match self.escrows.get(request.beneficiary) {
+                    Some(config) if config.escrow == caller => {
+                        self.escrow_approvals.insert(request_id, &caller);
+                        Ok(())
+                    }
+                    _ => Err(Error::BadOrigin),
+                }
+            });
+
+            self.finish(caller, result, Success::EscrowApproved)
+        }
+
+        /// Verify a pending transfer request, by the compliance operator
+        #[ink(message)]
+        pub fn mark_verified(&mut self,
+            request_id: u64,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if self.compliance_operator != Some(caller) {
+                Err(Error::BadOrigin)
+            } else {
+                self.find_request(request_id).map(|mut request| {
+                    request.verified = true;
+                    self.requests.insert(request_id, &request);
+                })
+            };
+
+            self.finish(caller, result, Success::TransferRequestVerified)
+        }
+
+        /// Execute a verified transfer request, by the owner or the treasury
+        #[ink(message)]
+        pub fn execute_transfer(&mut self,
+            request_id: u64,
+            tx_hash: Vec<u8>,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            // Check the caller, it must be the owner or the treasury
+            if caller != self.vesting_owner && self.treasury != Some(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let request = match self.find_request(request_id) {
+                Ok(request) if !request.verified => {
+                    self.fail(caller, Error::RequestNotVerified { request_id })?;
+                    return Ok(());
+                }
+                Ok(request) => request,
+                Err(error) => {
+                    self.fail(caller, error)?;
+                    return Ok(());
+                }
+            };
+
+            // Collect the approvals required by the amount tier
+            let approved = self.collect_approval(caller, request.beneficiary, request.schedule_number, op_id)
+                .or_else(|error| self.fail(caller, error).map(|()| false))?;
+            if !approved {
+                return Ok(());
+            }
+
+            self.set_locked(true);
+            let result = self.do_approve_transfer(request.beneficiary, request.schedule_number, tx_hash, Vec::new(), op_id);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Reject a pending transfer request, the schedule returns to liquid
+        #[ink(message)]
+        pub fn reject_request(&mut self,
+            request_id: u64,
+            reason: Vec<u8>,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if caller != self.vesting_owner {
+                Err(Error::BadOrigin)
+            } else {
+                self.release_request(caller, request_id, reason)
+            };
+
+            self.finish(caller, result, Success::TransferRequestRejected)
+        }
+
+        /// Cancel a pending transfer request of the caller, the schedule returns to liquid
+        #[ink(message)]
+        pub fn cancel_request(&mut self,
+            request_id: u64,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = match self.find_request(request_id) {
+                Ok(request) if request.beneficiary != caller => Err(Error::BadOrigin),
+                Ok(_) => self.release_request(caller, request_id, Vec::new()),
+                Err(error) => Err(error),
+            };
+            if result.is_ok() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result, Success::TransferRequestCancelled)
+        }
+
+        /// Get the next nonce expected in a signed request of an address
+        #[ink(message)]
+        pub fn get_nonce(&self,
+            address: AccountId,) -> u64 {
+            self.nonces.get(address).unwrap_or(0)
+        }
+
+        /// Preview the approval of a schedule by the caller, the amount, recipient, fee and blocking conditions.
+        /// The conditions are checked by the same helpers as the approval, missing tier approvals included
+        #[ink(message)]
+        pub fn can_approve(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Result<ApprovalPreview, Error> {
+
+            let vested_balance = self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .ok_or(Error::VestedBalanceNotFound { address })?;
+            let schedule = vested_balance.vested_balance_schedules.iter()
+                .find(|s| s.schedule_number == schedule_number)
+                .ok_or(Error::VestedBalanceScheduleNotFound { address, schedule_number })?;
+
+            let amount = schedule.schedule_balance;
+            let caller = self.env().caller();
+            let mut blocking = Vec::new();
+
+            if self.is_locked() {
+                blocking.push(Error::ReentrantCall);
+            }
+            if !self.is_approver(caller) {
+                blocking.push(Error::BadOrigin);
+            }
+            if schedule.status != ScheduleStatus::Requested {
+                blocking.push(Error::VestedBalanceScheduleNotRequested {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            } else {
+                let checks = [
+                    self.check_tier_approvals(caller, address, schedule_number),
+                    self.check_verified(address, schedule_number),
+                    self.check_requested_transfer(address, schedule_number),
+                    self.check_escrow_approval(address, schedule_number),
+                ];
+                blocking.extend(checks.into_iter().filter_map(Result::err));
+            }
+
+            // Native vesting and native XCM transfers are paid the full amount out of the escrow
+            if self.asset_kind == AssetKind::Native && self.native_pool < amount {
+                blocking.push(Error::InsufficientNativeEscrow {
+                    available: self.native_pool,
+                    required: amount,
+                });
+            }
+
+            Ok(ApprovalPreview {
+                amount,
+                recipient: self.recipient_of(address, schedule_number).unwrap_or(Recipient::Account(address)),
+                fee: self.fee_of(amount),
+                blocking,
+            })
+        }
+
+        /// Approve transfer only if the vested balance is still at the version the owner reviewed
+        #[ink(message)]
+        pub fn approve_transfer_checked(&mut self,
+            requesting_address: AccountId,
+            schedule_number: u16,
+            tx_hash: Vec<u8>,
+            expected_version: u32,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            // Check the caller, it must be an approver
+            if !self.is_approver(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // Reject approvals of a vested balance that changed since it was reviewed
+            let current = self.vested_balances.iter()
+                .find(|v| v.address == requesting_address)
+                .map(|v| v.version);
+            if let Some(current) = current.filter(|current| *current != expected_version) {
+                self.fail(caller, Error::VersionMismatch {
+                    address: requesting_address,
+                    expected: expected_version,
+                    current,
+                })?;
+                return Ok(());
+            }
+
+            // Collect the approvals required by the amount tier
+            let approved = self.collect_approval(caller, requesting_address, schedule_number, op_id)
+                .or_else(|error| self.fail(caller, error).map(|()| false))?;
+            if !approved {
+                return Ok(());
+            }
+
+            self.set_locked(true);
+            let result = self.do_approve_transfer(requesting_address, schedule_number, tx_hash, Vec::new(), op_id);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Approve transfer
+        #[ink(message)]
+        pub fn approve_transfer(&mut self,
+            requesting_address: AccountId,
+            schedule_number: u16,
+            tx_hash: Vec<u8>,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            self.approve_transfer_with_memo(requesting_address, schedule_number, tx_hash, Vec::new(), op_id)
+        }
+
+        /// Approve transfer with a memo for the auditors, recorded with the approver in the schedule history
+        #[ink(message)]
+        pub fn approve_transfer_with_memo(&mut self,
+            requesting_address: AccountId,
+            schedule_number: u16,
+            tx_hash: Vec<u8>,
+            memo: Vec<u8>,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            // Check the caller, it must be an approver
+            if !self.is_approver(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // Collect the approvals required by the amount tier
+            let approved = self.collect_approval(caller, requesting_address, schedule_number, op_id)
+                .or_else(|error| self.fail(caller, error).map(|()| false))?;
+            if !approved {
+                return Ok(());
+            }
+
+            self.set_locked(true);
+            let result = self.do_approve_transfer(requesting_address, schedule_number, tx_hash, memo, op_id);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Body of `approve_transfer`, run under the reentrancy lock
+        fn do_approve_transfer(&mut self,
+            requesting_address: AccountId,
+            schedule_number: u16,
+            tx_hash: Vec<u8>,
+            memo: Vec<u8>,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+            
+            // The caller is checked by the public message
+            let caller = self.env().caller();
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(());
+            }
+
+            // A retried approval of a transferred schedule is a no-op
+            if self.is_transferred(requesting_address, schedule_number) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::AlreadyTransferred));
+                return Ok(());
+            }
+
+            let result = self.approve_requested(caller, requesting_address, schedule_number, tx_hash, memo);
+            if result.is_ok() {
+                self.record_op_id(op_id);
+            }
+
+            self.finish(caller, result.clone().map(|_| ()), Success::VestedBalanceScheduleApproved)?;
+
+            if let Ok((recipient, amount)) = result {
+                self.notify_recipient(caller, recipient, requesting_address, amount);
+            }
+
+            Ok(())
+        }
+
+        /// Approve several requested transfers, each item is (beneficiary, schedule number, tx-hash).
+        /// A bad item does not stop the rest, returns the result of each item
+        #[ink(message)]
+        pub fn approve_transfers(&mut self,
+            items: Vec<(AccountId, u16, Vec<u8>)>,
+            op_id: Option<[u8; 32]>) -> Result<BatchResult, Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(Vec::new());
+            }
+
+            // Check the caller, it must be an approver
+            if !self.is_approver(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            self.set_locked(true);
+            let mut results = Vec::with_capacity(items.len());
+            for (index, (address, schedule_number, tx_hash)) in items.into_iter().enumerate() {
+
+                // Collect the approvals required by the amount tier
+                let result = match self.collect_approval(caller, address, schedule_number, None) {
+                    Err(error) => {
+                        self.emit_status(caller, VestingStatus::EmitError(error.clone()));
+                        Err(error)
+                    }
+                    Ok(false) => Ok(Success::ApprovalRecorded),
+                    Ok(true) if self.is_transferred(address, schedule_number) => {
+                        self.emit_status(caller, VestingStatus::EmitSuccess(Success::AlreadyTransferred));
+                        Ok(Success::AlreadyTransferred)
+                    }
+                    Ok(true) => {
+                        let approved = self.approve_requested(caller, address, schedule_number, tx_hash, Vec::new());
+                        self.emit_result(caller, approved.clone().map(|_| ()), Success::VestedBalanceScheduleApproved);
+                        if let Ok((recipient, amount)) = approved {
+                            self.notify_recipient(caller, recipient, address, amount);
+                        }
+                        approved.map(|_| Success::VestedBalanceScheduleApproved)
+                    }
+                };
+
+                results.push(BatchItemResult { index: index as u32, result });
+            }
+            self.set_locked(false);
+
+            self.record_op_id(op_id);
+
+            self.finish_batch(results)
+        }
+
+        /// Approve a requested transfer for payout by an off-chain payout bot, returns the payout id
+        #[ink(message)]
+        pub fn approve_payout(&mut self,
+            requesting_address: AccountId,
+            schedule_number: u16,
+            op_id: Option<[u8; 32]>) -> Result<Option<u64>, Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(None);
+            }
+
+            self.set_locked(true);
+            let result = self.do_approve_payout(requesting_address, schedule_number, op_id);
+            self.set_locked(false);
+
+            result
+        }
+
+        /// Body of `approve_payout`, run under the reentrancy lock
+        fn do_approve_payout(&mut self,
+            requesting_address: AccountId,
+            schedule_number: u16,
+            op_id: Option<[u8; 32]>) -> Result<Option<u64>, Error> {
+
+            // Check the caller, it must be an approver
+            let caller = self.env().caller();
+            if !self.is_approver(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(None);
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(None);
+            }
+
+            // Collect the approvals required by the amount tier
+            let approved = self.collect_approval(caller, requesting_address, schedule_number, op_id)
+                .or_else(|error| self.fail(caller, error).map(|()| false))?;
+            if !approved {
+                return Ok(None);
+            }
+
+            // XCM transfers are dispatched by the approval itself
+            if self.xcm_destinations.contains((requesting_address, schedule_number)) {
+                self.fail(caller, Error::XcmTransferRequired {
+                    address: requesting_address,
+                    schedule_number,
+                })?;
+                return Ok(None);
+            }
+
+            // Ask the compliance operator, the escrow and the compliance hook before the payout
+            if let Err(error) = self.check_verified(requesting_address, schedule_number)
+                .and_then(|()| self.check_escrow_approval(requesting_address, schedule_number))
+                .and_then(|()| self.check_requested_transfer(requesting_address, schedule_number)) {
+                self.fail(caller, error)?;
+                return Ok(None);
+            }
+
+            let block_number = self.env().block_number();
+
+            let vested_balance = match self.vested_balances.iter_mut().find(|v| v.address == requesting_address) {
+                Some(v) => v,
+                None => {
+                    self.fail(caller, Error::VestedBalanceNotFound { address: requesting_address })?;
+                    return Ok(None);
+                }
+            };
+
+            let schedule = match vested_balance.vested_balance_schedules.iter_mut()
+                .find(|s| s.schedule_number == schedule_number) {
+                Some(s) => s,
+                None => {
+                    self.fail(caller, Error::VestedBalanceScheduleNotFound { address: requesting_address, schedule_number })?;
+                    return Ok(None);
+                }
+            };
+
+            if schedule.status != ScheduleStatus::Requested {
+                let status = schedule.status;
+                self.fail(caller, Error::VestedBalanceScheduleNotRequested {
+                    address: requesting_address,
+                    schedule_number,
+                    status,
+                })?;
+                return Ok(None);
+            }
+
+            // Native vesting is paid out of the escrow right away, without a payout instruction
+            if self.asset_kind == AssetKind::Native {
+                let amount = schedule.schedule_balance;
+                let recipient = schedule.recipient_address.unwrap_or(requesting_address);
+                let schedule_id = schedule.schedule_id;
+                if let Err(error) = Self::pay_native(&mut self.native_pool, self.enforcement_backend, recipient, amount) {
+                    self.record_failed_payout(requesting_address, schedule_number, schedule_id, recipient, amount, error.clone());
+                    self.fail(caller, error)?;
+                    return Ok(None);
+                }
+
+                if let Some(request_id) = schedule.request_id {
+                    self.requests.remove(request_id);
+                }
+                schedule.status = ScheduleStatus::Transferred;
+                Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Requested), Some(ScheduleStatus::Transferred), amount);
+                Self::move_balance(vested_balance, ScheduleStatus::Requested, ScheduleStatus::Transferred, amount);
+                Self::pin_recipient(vested_balance, recipient);
+                Self::track_holder(&mut self.holders_index, requesting_address, Self::remaining_balance(vested_balance));
+                Self::record_history(&mut self.schedule_history, (requesting_address, schedule_number), ScheduleHistoryEntry {
+                    previous_status: ScheduleStatus::Requested,
+                    status: ScheduleStatus::Transferred,
+                    particulars: Vec::new(),
+                    reason: Vec::new(),
+                    operator: caller,
+                    block_number,
+                    timestamp: Self::env().block_timestamp(),
+                });
+                self.clear_failed_payout(requesting_address, schedule_number);
+                self.record_receipt(requesting_address, schedule_number, amount, Vec::new());
+                self.last_approval_block = block_number;
+
+                self.record_op_id(op_id);
+
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::NativePayoutTransferred));
+
+                self.notify_recipient(caller, Recipient::Account(recipient), requesting_address, amount);
+
+                return Ok(None);
+            }
+
+            // Update the schedule
+            if let Some(request_id) = schedule.request_id {
+                self.requests.remove(request_id);
+            }
+            schedule.status = ScheduleStatus::Approved;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Requested), Some(ScheduleStatus::Approved), schedule.schedule_balance);
+            let amount = schedule.schedule_balance;
+            let recipient = schedule.recipient_address.unwrap_or(requesting_address);
+            let schedule_id = schedule.schedule_id;
+
+            // Update balances
+            Self::move_balance(vested_balance, ScheduleStatus::Requested, ScheduleStatus::Approved, amount);
+            Self::record_history(&mut self.schedule_history, (requesting_address, schedule_number), ScheduleHistoryEntry {
+                previous_status: ScheduleStatus::Requested,
+                status: ScheduleStatus::Approved,
+                particulars: Vec::new(),
+                reason: Vec::new(),
+                operator: caller,
+                block_number,
+                timestamp: Self::env().block_timestamp(),
+            });
+
+            self.last_approval_block = block_number;
+
+            self.record_op_id(op_id);
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::PayoutInstructed));
+
+            // Register the payout
+            let payout_id = self.instruct_payout(requesting_address, schedule_number, schedule_id, recipient, amount);
+
+            Ok(Some(payout_id))
+        }
+
+        /// Acknowledge the settlement of a payout
+        #[ink(message)]
+        pub fn ack_payout(&mut self,
+            payout_id: u64,
+            tx_hash: Vec<u8>,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(());
+            }
+
+            let result = self.settle_payout(payout_id, tx_hash);
+            if result.is_ok() {
+                self.record_op_id(op_id);
+            }
+
+            self.finish(caller, result, Success::PayoutAcknowledged)
+        }
+
+        /// Retry a failed outbound payout from the retry queue. A payout whose schedule is no longer
+        /// requested is dropped from the queue
+        #[ink(message)]
+        pub fn retry_payout(&mut self,
+            payout_id: u64,) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            // Check the caller, it must be an approver
+            if !self.is_approver(caller) {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.set_locked(true);
+            let result = match self.failed_payouts.get_or_default().into_iter().find(|p| p.payout_id == payout_id) {
+                Some(failed_payout) => {
+                    let result = self.approve_requested(caller, failed_payout.beneficiary, failed_payout.schedule_number, Vec::new(), Vec::new());
+                    if let Err(Error::VestedBalanceScheduleNotRequested { .. }) = result {
+                        self.clear_failed_payout(failed_payout.beneficiary, failed_payout.schedule_number);
+                    }
+                    result.map(|(recipient, amount)| (failed_payout.beneficiary, recipient, amount))
+                }
+                None => Err(Error::PayoutNotFound { payout_id }),
+            };
+            self.set_locked(false);
+
+            self.finish(caller, result.clone().map(|_| ()), Success::PayoutRetried)?;
+
+            if let Ok((beneficiary, recipient, amount)) = result {
+                self.notify_recipient(caller, recipient, beneficiary, amount);
+            }
+
+            Ok(())
+        }
+
+        /// Get the failed outbound payouts waiting for a retry
+        #[ink(message)]
+        pub fn get_stuck_payouts(&self,) -> Vec<FailedPayout> {
+            self.failed_payouts.get_or_default()
+        }
+
+        /// Acknowledge the settlement of several payouts in one call
+        #[ink(message)]
+        pub fn ack_payouts(&mut self,
+            items: Vec<(u64, Vec<u8>)>,
+            op_id: Option<[u8; 32]>) -> Result<BatchResult, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            // Each item is settled on its own, a bad item does not stop the rest
+            let mut results = Vec::with_capacity(items.len());
+            for (index, (payout_id, tx_hash)) in items.into_iter().enumerate() {
+                let result = self.settle_payout(payout_id, tx_hash);
+                self.emit_result(caller, result.clone(), Success::PayoutAcknowledged);
+
+                results.push(BatchItemResult {
+                    index: index as u32,
+                    result: result.map(|()| Success::PayoutAcknowledged),
+                });
+            }
+
+            self.record_op_id(op_id);
+
+            self.finish_batch(results)
+        }
+
+        /// Voluntarily return frozen or liquid schedules of the caller to the treasury.
+        /// The schedules are kept as forfeited for audit, returns the result of each schedule
+        #[ink(message)]
+        pub fn forfeit(&mut self,
+            schedule_numbers: Vec<u16>,) -> Result<BatchResult, Error> {
+
+            let caller = self.env().caller();
+
+            let mut results = Vec::with_capacity(schedule_numbers.len());
+            for (index, schedule_number) in schedule_numbers.into_iter().enumerate() {
+                let result = self.forfeit_schedule(caller, schedule_number);
+                if result.is_ok() {
+                    self.record_activity(caller);
+                }
+                self.emit_result(caller, result.clone(), Success::VestedBalanceScheduleForfeited);
+
+                results.push(BatchItemResult {
+                    index: index as u32,
+                    result: result.map(|()| Success::VestedBalanceScheduleForfeited),
+                });
+            }
+
+            self.finish_batch(results)
+        }
+
+        /// Allow or disallow beneficiaries to donate liquid schedules to each other
+        #[ink(message)]
+        pub fn set_donations_enabled(&mut self,
+            donations_enabled: bool,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.donations_enabled = donations_enabled;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::DonationsEnabledSet));
+
+            Ok(())
+        }
+
+        /// Re-assign a liquid schedule of the caller to another beneficiary, returns the schedule number
+        /// the donation got in the receiving position
+        #[ink(message)]
+        pub fn donate_schedule(&mut self,
+            schedule_number: u16,
+            to: AccountId,) -> Result<Option<u16>, Error> {
+
+            let caller = self.env().caller();
+
+            let result = if !self.donations_enabled {
+                Err(Error::DonationsDisabled)
+            } else if to == caller {
+                Err(Error::InvalidDonationTarget { address: to })
+            } else {
+                self.move_schedule(caller, schedule_number, to)
+            };
+            let new_schedule_number = result.as_ref().ok().copied();
+            if new_schedule_number.is_some() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::VestedBalanceScheduleDonated)?;
+
+            Ok(new_schedule_number)
+        }
+
+        /// Set or clear the backup address of the caller's position. If the caller does not act for the
+        /// inactivity period, the backup can claim the position with `claim_as_backup`
+        #[ink(message)]
+        pub fn set_backup(&mut self,
+            backup: Option<AccountId>,
+            inactivity_period: Timestamp,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if !self.vested_balances.iter().any(|v| v.address == caller) {
+                Err(Error::VestedBalanceNotFound { address: caller })
+            } else if backup == Some(caller) {
+                Err(Error::BadOrigin)
+            } else {
+                match backup {
+                    Some(backup) => {
+                        self.backups.insert(caller, &BackupConfig { backup, inactivity_period });
+                    }
+                    None => self.backups.remove(caller),
+                }
+                self.record_activity(caller);
+                Ok(())
+            };
+
+            self.finish(caller, result, Success::BackupSet)
+        }
+
+        /// Record that the caller is still active, restarting the inactivity period of its backup
+        #[ink(message)]
+        pub fn heartbeat(&mut self,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if self.vested_balances.iter().any(|v| v.address == caller) {
+                self.record_activity(caller);
+                Ok(())
+            } else {
+                Err(Error::VestedBalanceNotFound { address: caller })
+            };
+
+            self.finish(caller, result, Success::ActivityRecorded)
+        }
+
+        /// Claim the position of an inactive beneficiary, callable by its backup address.
+        /// The position moves to the backup, which must not hold a position and no request may be pending
+        #[ink(message)]
+        pub fn claim_as_backup(&mut self,
+            beneficiary: AccountId,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            let result = match self.backups.get(beneficiary) {
+                Some(config) if config.backup == caller => {
+                    let inactive_at = self.last_activity.get(beneficiary)
+                        .unwrap_or(0)
+                        .saturating_add(config.inactivity_period);
+                    if now < inactive_at {
+                        Err(Error::BeneficiaryStillActive { inactive_at })
+                    } else {
+                        self.migrate_position(beneficiary, caller)
+                    }
+                }
+                _ => Err(Error::BackupNotSet { address: beneficiary }),
+            };
+
+            self.finish(caller, result, Success::PositionClaimedByBackup)
+        }
+
+        /// Get the backup address of a beneficiary and its last activity
+        #[ink(message)]
+        pub fn get_backup(&self,
+            beneficiary: AccountId,) -> Option<(BackupConfig, Timestamp)> {
+            self.backups
+                .get(beneficiary)
+                .map(|config| (config, self.last_activity.get(beneficiary).unwrap_or(0)))
+        }
+
+        /// Pledge frozen schedules of the caller to a lender. Pledged schedules cannot be requested, forfeited
+        /// or donated until the lender releases them. A lender contract is notified with
+        /// `on_collateral_locked(beneficiary, schedule_numbers, amount)` and must return true to accept
+        #[ink(message)]
+        pub fn lock_as_collateral(&mut self,
+            schedule_numbers: Vec<u16>,
+            lender: AccountId,) -> Result<(), Error> {
+
+            // Reject reentrant calls
+            let caller = self.env().caller();
+            if self.is_locked() {
+                self.fail(caller, Error::ReentrantCall)?;
+                return Ok(());
+            }
+
+            self.set_locked(true);
+            let result = self.pledge_schedules(caller, &schedule_numbers, lender);
+            self.set_locked(false);
+
+            if result.is_ok() {
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result, Success::CollateralLocked)
+        }
+
+        /// Release schedules of a beneficiary pledged to the caller
+        #[ink(message)]
+        pub fn release_collateral(&mut self,
+            beneficiary: AccountId,
+            schedule_numbers: Vec<u16>,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            // All schedules must be pledged to the caller
+            let result = match schedule_numbers.iter()
+                .find(|n| self.pledges.get((beneficiary, **n)) != Some(caller)) {
+                Some(schedule_number) => Err(Error::ScheduleNotPledged { address: beneficiary, schedule_number: *schedule_number }),
+                None => {
+                    for schedule_number in schedule_numbers.iter() {
+                        self.pledges.remove((beneficiary, *schedule_number));
+                    }
+                    Ok(())
+                }
+            };
+
+            self.finish(caller, result, Success::CollateralReleased)
+        }
+
+        /// Get the lender a schedule is pledged to
+        #[ink(message)]
+        pub fn get_pledge(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Option<AccountId> {
+            self.pledges.get((address, schedule_number))
+        }
+
+        /// Set the recovery guardians of the caller's position, an empty list removes them.
+        /// Any ongoing recovery is dropped
+        #[ink(message)]
+        pub fn set_recovery_guardians(&mut self,
+            guardians: Vec<AccountId>,
+            threshold: u8,
+            challenge_period: Timestamp,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if !self.vested_balances.iter().any(|v| v.address == caller) {
+                Err(Error::VestedBalanceNotFound { address: caller })
+            } else if guardians.is_empty() {
+                self.recovery_configs.remove(caller);
+                Ok(())
+            } else if threshold == 0 || threshold as usize > guardians.len() || guardians.contains(&caller) {
+                Err(Error::InvalidRecoveryConfig)
+            } else {
+                self.recovery_configs.insert(caller, &RecoveryConfig { guardians, threshold, challenge_period });
+                Ok(())
+            };
+            if result.is_ok() {
+                self.recoveries.remove(caller);
+                self.record_activity(caller);
+            }
+
+            self.finish(caller, result, Success::RecoveryGuardiansSet)
+        }
+
+        /// Vote to move a beneficiary's position to a new address, callable by its recovery guardians.
+        /// Once the threshold agrees on the same address, the challenge period starts
+        #[ink(message)]
+        pub fn vote_recovery(&mut self,
+            beneficiary: AccountId,
+            new_address: AccountId,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            let result = match self.recovery_configs.get(beneficiary) {
+                Some(config) if config.guardians.contains(&caller) => {
+                    let mut recovery = self.recoveries.get(beneficiary).unwrap_or_default();
+                    recovery.votes.retain(|(guardian, _)| *guardian != caller);
+                    recovery.votes.push((caller, new_address));
+
+                    let votes = recovery.votes.iter().filter(|(_, address)| *address == new_address).count();
+                    if recovery.agreed.is_none() && votes >= config.threshold as usize {
+                        recovery.agreed = Some((new_address, now.saturating_add(config.challenge_period)));
+                    }
+
+                    self.recoveries.insert(beneficiary, &recovery);
+                    Ok(())
+                }
+                _ => Err(Error::BadOrigin),
+            };
+
+            self.finish(caller, result, Success::RecoveryVoteRecorded)
+        }
+
+        /// Cancel the ongoing recovery of the caller's position
+        #[ink(message)]
+        pub fn cancel_recovery(&mut self,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = match self.recoveries.take(caller) {
+                Some(_) => {
+                    self.record_activity(caller);
+                    Ok(())
+                }
+                None => Err(Error::RecoveryNotReady { executable_at: None }),
+            };
+
+            self.finish(caller, result, Success::RecoveryCancelled)
+        }
+
+        /// Move a beneficiary's position to the address agreed on by its recovery guardians,
+        /// callable by anyone once the challenge period has passed
+        #[ink(message)]
+        pub fn execute_recovery(&mut self,
+            beneficiary: AccountId,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            let agreed = self.recoveries.get(beneficiary).and_then(|recovery| recovery.agreed);
+            let result = match agreed {
+                Some((new_address, executable_at)) if now >= executable_at => {
+                    let config = self.recovery_configs.get(beneficiary);
+                    // The guardians keep protecting the position at its new address
+                    self.migrate_position(beneficiary, new_address).map(|()| {
+                        if let Some(config) = config {
+                            self.recovery_configs.insert(new_address, &config);
+                        }
+                    })
+                }
+                _ => Err(Error::RecoveryNotReady { executable_at: agreed.map(|(_, t)| t) }),
+            };
+
+            self.finish(caller, result, Success::PositionRecovered)
+        }
+
+        /// Get the recovery guardians of a beneficiary and its ongoing recovery
+        #[ink(message)]
+        pub fn get_recovery(&self,
+            beneficiary: AccountId,) -> (Option<RecoveryConfig>, Option<Recovery>) {
+            (self.recovery_configs.get(beneficiary), self.recoveries.get(beneficiary))
+        }
+
+        /// Change the unlock times of frozen schedules of a grant, each item is (schedule number, unlock time).
+        /// Nothing changes when any of the schedules is missing or no longer frozen. Later unlock times need
+        /// the beneficiary's consent, returns the id of the amendment waiting for it
+        #[ink(message)]
+        pub fn reschedule_grant(&mut self,
+            address: AccountId,
+            new_unlock_times: Vec<(u16, Timestamp)>,) -> Result<Option<u64>, Error> {
+
+            let change = AmendmentChange::UnlockTimes(
+                new_unlock_times.into_iter().map(|(n, t)| (n, Some(t))).collect(),
+            );
+
+            self.propose_amendment(address, change)
+        }
+
+        /// Amend the frozen schedules of a grant. Changes in the beneficiary's favour (earlier unlocks, higher
+        /// amounts) apply right away, adverse ones wait for `accept_amendment`. Returns the id of a waiting amendment
+        #[ink(message)]
+        pub fn propose_amendment(&mut self,
+            address: AccountId,
+            change: AmendmentChange,) -> Result<Option<u64>, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(None);
+            }
+
+            match self.amend_grant(caller, address, change) {
+                Ok(Some(amendment_id)) => {
+                    self.emit_status(caller, VestingStatus::EmitSuccess(Success::AmendmentProposed));
+                    Ok(Some(amendment_id))
+                }
+                Ok(None) => {
+                    self.emit_status(caller, VestingStatus::EmitSuccess(Success::AmendmentApplied));
+                    Ok(None)
+                }
+                Err(error) => {
+                    self.fail(caller, error)?;
+                    Ok(None)
+                }
+            }
+        }
+
+        /// Accept an adverse amendment of the caller's grant
+        #[ink(message)]
+        pub fn accept_amendment(&mut self,
+            amendment_id: u64,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = match self.amendments.get(amendment_id) {
+                Some(amendment) if amendment.address != caller => Err(Error::BadOrigin),
+                Some(amendment) => {
+                    // The schedules may have changed since the proposal
+                    self.amendment_previous(caller, &amendment.change).map(|previous| {
+                        self.amendments.remove(amendment_id);
+                        self.apply_amendment(amendment_id, caller, amendment.change, previous, amendment.proposed_by, Some(caller));
+                    })
+                }
+                None => Err(Error::AmendmentNotFound { amendment_id }),
+            };
+
+            self.finish(caller, result, Success::AmendmentApplied)
+        }
+
+        /// Get an amendment waiting for the beneficiary's consent
+        #[ink(message)]
+        pub fn get_amendment(&self,
+            amendment_id: u64,) -> Option<PendingAmendment> {
+            self.amendments.get(amendment_id)
+        }
+
+        /// Get the amendments applied to a grant, oldest first
+        #[ink(message)]
+        pub fn get_amendments(&self,
+            address: AccountId,) -> Vec<AmendmentRecord> {
+            self.amendment_history.get(address).unwrap_or_default()
+        }
+
+        /// Execute owner operations in order, all or nothing. Returns the outcome of each call, the first
+        /// failing call reverts the whole multicall with its error
+        #[ink(message)]
+        pub fn multicall(&mut self,
+            calls: Vec<VestingCall>,) -> Result<Vec<Success>, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            let mut results = Vec::with_capacity(calls.len());
+            for call in calls.into_iter() {
+                let success = self.execute_call(caller, call)?;
+                self.emit_status(caller, VestingStatus::EmitSuccess(success.clone()));
+                results.push(success);
+            }
+
+            Ok(results)
+        }
+
+        /// Execute owner operations in order, each on its own. A failing call does not stop the rest,
+        /// returns the result of each call
+        #[ink(message)]
+        pub fn best_effort_multicall(&mut self,
+            calls: Vec<VestingCall>,) -> Result<BatchResult, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            let mut results = Vec::with_capacity(calls.len());
+            for (index, call) in calls.into_iter().enumerate() {
+                let result = self.execute_call(caller, call);
+                match &result {
+                    Ok(success) => self.emit_status(caller, VestingStatus::EmitSuccess(success.clone())),
+                    Err(error) => self.emit_status(caller, VestingStatus::EmitError(error.clone())),
+                }
+
+                results.push(BatchItemResult {
+                    index: index as u32,
+                    result,
+                });
+            }
+
+            self.finish_batch(results)
+        }
+
+        /// Add synthetic beneficiaries with the given number of schedules each, for gas benchmarking on a
+        /// dev chain. Asset vesting only, returns the number of beneficiaries added
+        #[cfg(feature = "bench")]
+        #[ink(message)]
+        pub fn bench_populate(&mut self,
+            beneficiaries: u32,
+            schedules_each: u16,
+            schedule_balance: u128,) -> Result<u32, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(0);
+            }
+
+            if self.asset_kind == AssetKind::Native {
+                let expected = schedule_balance.saturating_mul(schedules_each as u128).saturating_mul(beneficiaries as u128);
+                self.fail(caller, Error::FundingMismatch { expected, transferred: 0 })?;
+                return Ok(0);
+            }
+
+            let mut added: u32 = 0;
+            let start = self.vested_balances.len() as u32;
+            for i in start..start.saturating_add(beneficiaries) {
+                let address = AccountId::from(self.env().hash_encoded::<Blake2x256, _>(&(b"bench", i)));
+                let schedules: Vec<VestedBalanceSchedule> = (1..=schedules_each)
+                    .map(|n| Self::new_schedule(n, schedule_balance, None))
+                    .collect();
+                let original_balance = schedule_balance.saturating_mul(schedules_each as u128);
+                if self.insert_grant(address, original_balance, schedules, 0).is_ok() {
+                    added += 1;
+                }
+            }
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::VestedBalanceAdded));
+
+            Ok(added)
+        }
+
+        /// Run the worst-case approval path for gas benchmarking: thaw, request and approve a schedule of the
+        /// last beneficiary, found after scanning all others. Pair with `thaw_vested_balances` for the thaw path
+        #[cfg(feature = "bench")]
+        #[ink(message)]
+        pub fn bench_approve_last(&mut self,
+            schedule_number: u16,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = match self.vested_balances.last().map(|v| v.address) {
+                Some(address) => self.thaw_schedule(address, schedule_number)
+                    .and_then(|()| self.create_transfer_request(address, schedule_number, Recipient::Account(address), Vec::new()))
+                    .and_then(|request_id| {
+                        let tx_hash = request_id.to_le_bytes().to_vec();
+                        self.approve_requested(caller, address, schedule_number, tx_hash, Vec::new())
+                    })
+                    .map(|_| ()),
+                None => Err(Error::VestedBalanceNotFound { address: caller }),
+            };
+
+            self.finish(caller, result, Success::VestedBalanceScheduleApproved)
+        }
+
+        /// Halt further unlocks of a beneficiary, e.g. during a leave of absence or an investigation
+        #[ink(message)]
+        pub fn suspend_vesting(&mut self,
+            address: AccountId,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = if !self.vested_balances.iter().any(|v| v.address == address) {
+                Err(Error::VestedBalanceNotFound { address })
+            } else if self.suspensions.contains(address) {
+                Err(Error::VestingSuspended { address })
+            } else {
+                self.suspensions.insert(address, &self.env().block_timestamp());
+                self.suspended_count += 1;
+                Ok(())
+            };
+
+            self.finish(caller, result, Success::VestingSuspended)
+        }
+
+        /// Resume the unlocks of a suspended beneficiary. With `extend` the unlock times of its frozen
+        /// schedules are shifted by the suspension duration
+        #[ink(message)]
+        pub fn resume_vesting(&mut self,
+            address: AccountId,
+            extend: bool,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = match self.suspensions.take(address) {
+                Some(suspended_at) => {
+                    self.suspended_count -= 1;
+                    let duration = self.env().block_timestamp().saturating_sub(suspended_at);
+                    if let Some(vested_balance) = self.vested_balances.iter_mut().find(|v| v.address == address) {
+                        if extend {
+                            for schedule in vested_balance.vested_balance_schedules.iter_mut().filter(|s| s.status == ScheduleStatus::Frozen) {
+                                schedule.unlock_time = schedule.unlock_time.map(|t| t.saturating_add(duration));
+                            }
+                        }
+                        Self::bump_version(vested_balance);
+                    }
+                    Ok(())
+                }
+                None => Err(Error::VestingNotSuspended { address }),
+            };
+
+            self.finish(caller, result, Success::VestingResumed)
+        }
+
+        /// Pin or stop pinning the transfers of a vested balance to the recipient of its first approved transfer
+        #[ink(message)]
+        pub fn set_recipient_pinning(&mut self,
+            address: AccountId,
+            pin_recipient: bool,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if caller != self.vesting_owner {
+                Err(Error::BadOrigin)
+            } else {
+                match self.vested_balances.iter_mut().find(|v| v.address == address) {
+                    Some(vested_balance) => {
+                        vested_balance.pin_recipient = pin_recipient;
+                        Self::bump_version(vested_balance);
+                        Ok(())
+                    }
+                    None => Err(Error::VestedBalanceNotFound { address }),
+                }
+            };
+
+            self.finish(caller, result, Success::RecipientPinningSet)
+        }
+
+        /// Unlock the pinned recipient of a vested balance, the next approved transfer pins it again
+        #[ink(message)]
+        pub fn unlock_recipient(&mut self,
+            address: AccountId,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if caller != self.vesting_owner {
+                Err(Error::BadOrigin)
+            } else {
+                match self.vested_balances.iter_mut().find(|v| v.address == address) {
+                    Some(vested_balance) => {
+                        vested_balance.pinned_recipient = None;
+                        Self::bump_version(vested_balance);
+                        Ok(())
+                    }
+                    None => Err(Error::VestedBalanceNotFound { address }),
+                }
+            };
+
+            self.finish(caller, result, Success::RecipientUnlocked)
+        }
+
+        /// Set the arbiter resolving disputed schedules
+        #[ink(message)]
+        pub fn set_arbiter(&mut self,
+            arbiter: Option<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.arbiter = arbiter;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ArbiterSet));
+
+            Ok(())
+        }
+
+        /// Dispute a requested or approved schedule, by the owner or the beneficiary. A disputed schedule is
+        /// frozen from further action until the arbiter resolves it, the payout of an approved one is withdrawn.
+        /// Transferred schedules were already paid and cannot be disputed
+        #[ink(message)]
+        pub fn dispute_schedule(&mut self,
+            address: AccountId,
+            schedule_number: u16,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let result = if caller != self.vesting_owner && caller != address {
+                Err(Error::BadOrigin)
+            } else {
+                self.mark_disputed(address, schedule_number)
+            };
+
+            self.finish(caller, result, Success::VestedBalanceScheduleDisputed)
+        }
+
+        /// Resolve a disputed schedule, by the arbiter. The decision is recorded in the schedule history
+        #[ink(message)]
+        pub fn resolve_dispute(&mut self,
+            address: AccountId,
+            schedule_number: u16,
+            outcome: DisputeOutcome,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let result = if Some(caller) != self.arbiter {
+                Err(Error::BadOrigin)
+            } else {
+                self.apply_dispute_outcome(caller, address, schedule_number, outcome)
+            };
+            if let Ok((status, returned_schedule_number)) = result {
+                self.env().emit_event(DisputeResolved {
+                    address,
+                    schedule_number,
+                    arbiter: caller,
+                    outcome,
+                    status,
+                    returned_schedule_number,
+                });
+            }
+
+            self.finish(caller, result.map(|_| ()), Success::DisputeResolved)
+        }
+
+        /// Set the compliance operator verifying transfer requests
+        #[ink(message)]
+        pub fn set_compliance_operator(&mut self,
+            compliance_operator: Option<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.compliance_operator = compliance_operator;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ComplianceOperatorSet));
+
+            Ok(())
+        }
+
+        /// Require beneficiaries to be KYC verified before they request transfers
+        #[ink(message)]
+        pub fn set_kyc_required(&mut self,
+            kyc_required: bool,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.kyc_required = kyc_required;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::KycRequiredSet));
+
+            Ok(())
+        }
+
+        /// Set the KYC verification of a beneficiary, by the compliance operator
+        #[ink(message)]
+        pub fn set_kyc_verified(&mut self,
+            address: AccountId,
+            kyc_verified: bool,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            let result = if self.compliance_operator != Some(caller) {
+                Err(Error::BadOrigin)
+            } else {
+                match self.vested_balances.iter_mut().find(|v| v.address == address) {
+                    Some(vested_balance) => {
+                        vested_balance.kyc_verified = kyc_verified;
+                        Self::bump_version(vested_balance);
+                        Ok(())
+                    }
+                    None => Err(Error::VestedBalanceNotFound { address }),
+                }
+            };
+
+            self.finish(caller, result, Success::KycVerifiedSet)
+        }
+
+        /// Set the owner notes of a vested balance, at most `MAX_NOTES_LENGTH` bytes
+        #[ink(message)]
+        pub fn set_notes(&mut self,
+            address: AccountId,
+            notes: Vec<u8>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = if notes.len() > MAX_NOTES_LENGTH {
+                Err(Error::NotesTooLong { length: notes.len() as u32 })
+            } else {
+                match self.vested_balances.iter_mut().find(|v| v.address == address) {
+                    Some(vested_balance) => {
+                        vested_balance.notes = notes;
+                        Self::bump_version(vested_balance);
+                        Ok(())
+                    }
+                    None => Err(Error::VestedBalanceNotFound { address }),
+                }
+            };
+
+            self.finish(caller, result, Success::NotesSet)
+        }
+
+        /// Set the compliance profile of a beneficiary
+        #[ink(message)]
+        pub fn set_compliance_profile(&mut self,
+            address: AccountId,
+            profile: Option<ComplianceProfile>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            match profile {
+                Some(profile) => self.compliance_profiles.insert(address, &profile),
+                None => {
+                    self.compliance_profiles.remove(address);
+                    None
+                }
+            };
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ComplianceProfileSet));
+
+            Ok(())
+        }
+
+        /// Get the compliance profile of a beneficiary
+        #[ink(message)]
+        pub fn get_compliance_profile(&self,
+            address: AccountId,) -> Option<ComplianceProfile> {
+            self.compliance_profiles.get(address)
+        }
+
+        /// Set the time before which an investor category may not request transfers
+        #[ink(message)]
+        pub fn set_category_lockup(&mut self,
+            category: u8,
+            until: Option<Timestamp>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            match until {
+                Some(until) => self.category_lockups.insert(category, &until),
+                None => {
+                    self.category_lockups.remove(category);
+                    None
+                }
+            };
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::CategoryLockupSet));
+
+            Ok(())
+        }
+
+        /// Get the beneficiaries of an investor category with their remaining balance, for reporting.
+        /// Empty for others than the owner and the viewers when private
+        #[ink(message)]
+        pub fn get_beneficiaries_by_category(&self,
+            category: u8,) -> Vec<(AccountId, ComplianceProfile, u128)> {
+            if !self.can_view_all() {
+                return Vec::new();
+            }
+            self.vested_balances.iter()
+                .filter_map(|v| self.compliance_profiles.get(v.address)
+                    .filter(|profile| profile.category == category)
+                    .map(|profile| (v.address, profile, Self::remaining_balance(v))))
+                .collect()
+        }
+
+        /// Set the external contract checking transfers, `None` disables the check
+        #[ink(message)]
+        pub fn set_compliance_hook(&mut self,
+            compliance_hook: Option<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.compliance_hook = compliance_hook;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ComplianceHookSet));
+
+            Ok(())
+        }
+
+        /// Register the selector the caller is notified on when it receives a transfer, `None` unregisters it
+        #[ink(message)]
+        pub fn register_transfer_callback(&mut self,
+            selector: Option<[u8; 4]>,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+
+            match selector {
+                Some(selector) => self.transfer_callbacks.insert(caller, &selector),
+                None => {
+                    self.transfer_callbacks.remove(caller);
+                    None
+                }
+            };
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::TransferCallbackSet));
+
+            Ok(())
+        }
+
+        /// Get the transfer callback selector registered by a recipient
+        #[ink(message)]
+        pub fn get_transfer_callback(&self,
+            recipient: AccountId,) -> Option<[u8; 4]> {
+            self.transfer_callbacks.get(recipient)
+        }
+
+        /// Set the gas limit of transfer callbacks, it must not be zero
+        #[ink(message)]
+        pub fn set_callback_gas_limit(&mut self,
+            callback_gas_limit: u64,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // A zero gas limit lets the callee use all the remaining gas
+            if callback_gas_limit == 0 {
+                self.fail(caller, Error::InvalidCallbackGasLimit)?;
+                return Ok(());
+            }
+
+            self.callback_gas_limit = callback_gas_limit;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::CallbackGasLimitSet));
+
+            Ok(())
+        }
+
+        /// Get the external contract checking transfers
+        #[ink(message)]
+        pub fn get_compliance_hook(&self,) -> Option<AccountId> {
+            self.compliance_hook
+        }
+
+        /// Set the treasury executing verified transfer requests
+        #[ink(message)]
+        pub fn set_treasury(&mut self,
+            treasury: Option<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.treasury = treasury;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::TreasurySet));
+
+            Ok(())
+        }
+
+        /// Set the approvals required per amount tier, a transfer uses the tier with the highest
+        /// minimum amount it reaches. Without a matching tier one approval is required
+        #[ink(message)]
+        pub fn set_approval_tiers(&mut self,
+            approval_tiers: Vec<ApprovalTier>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let mut approval_tiers = approval_tiers;
+            approval_tiers.sort_by_key(|tier| tier.min_amount);
+            self.approval_tiers = approval_tiers;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ApprovalTiersSet));
+
+            Ok(())
+        }
+
+        /// Set the accounts approving transfers besides the owner
+        #[ink(message)]
+        pub fn set_approvers(&mut self,
+            approvers: Vec<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.approvers = approvers;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ApproversSet));
+
+            Ok(())
+        }
+
+        /// Set the accounts reading the access-gated views besides the owner, e.g. auditors
+        #[ink(message)]
+        pub fn set_viewers(&mut self,
+            viewers: Vec<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.viewers = viewers;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::ViewersSet));
+
+            Ok(())
+        }
+
+        /// Get the accounts reading the access-gated views besides the owner
+        #[ink(message)]
+        pub fn get_viewers(&self,) -> Vec<AccountId> {
+            self.viewers.clone()
+        }
+
+        /// Check an account may read the access-gated views, the owner and the viewers
+        #[ink(message)]
+        pub fn is_viewer(&self,
+            account: AccountId,) -> bool {
+            account == self.vesting_owner || self.viewers.contains(&account)
+        }
+
+        /// Turn privacy on or off. When private the bulk views (all balances, exports, state chunks, summaries
+        /// and top holders) answer only the owner and the viewers, the others get an empty result
+        #[ink(message)]
+        pub fn set_private(&mut self,
+            private: bool,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.private = private;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::PrivateSet));
+
+            Ok(())
+        }
+
+        /// Check the privacy of the bulk views
+        #[ink(message)]
+        pub fn is_private(&self,) -> bool {
+            self.private
+        }
+
+        /// Get the approvals collected for a requested schedule
+        #[ink(message)]
+        pub fn get_approvals(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Vec<AccountId> {
+            self.approvals.get((address, schedule_number)).unwrap_or_default()
+        }
+
+        /// Set the guardian co-signing sensitive owner operations. The current guardian changes it right away.
+        /// Once a guardian or an owner rotation delay is set, the owner alone proposes the change and applies it
+        /// by calling again after the rotation delay, which needs a delay while a guardian is set
+        #[ink(message)]
+        pub fn set_guardian(&mut self,
+            guardian: Option<AccountId>,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner or the guardian
+            let caller = self.env().caller();
+            let by_guardian = self.guardian == Some(caller);
+            if caller != self.vesting_owner && !by_guardian {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let now = self.env().block_timestamp();
+            let guarded = self.guardian.is_some() || self.owner_rotation_delay > 0;
+            let result = match self.pending_guardian {
+                _ if guardian == Some(self.vesting_owner) => Err(Error::GuardianIsOwner),
+                _ if by_guardian || !guarded => Ok(Success::GuardianSet),
+                _ if self.guardian.is_some() && self.owner_rotation_delay == 0 => Err(Error::BadOrigin),
+                Some((pending, activates_at)) if pending == guardian && now < activates_at => {
+                    Err(Error::GuardianChangeNotReady { activates_at })
+                }
+                Some((pending, _)) if pending == guardian => Ok(Success::GuardianSet),
+                _ => {
+                    self.pending_guardian = Some((guardian, now.saturating_add(self.owner_rotation_delay)));
+                    Ok(Success::GuardianChangeProposed)
+                }
+            };
+            if result == Ok(Success::GuardianSet) {
+                self.guardian = guardian;
+                self.pending_guardian = None;
+            }
+
+            match result {
+                Ok(success) => self.emit_status(caller, VestingStatus::EmitSuccess(success)),
+                Err(error) => self.fail(caller, error)?,
+            }
+
+            Ok(())
+        }
+
+        /// Get the guardian change proposed by the owner alone and the time it can be applied
+        #[ink(message)]
+        pub fn get_pending_guardian(&self,) -> Option<(Option<AccountId>, Timestamp)> {
+            self.pending_guardian
+        }
+
+        /// Set the owner rotation delay. The owner can only raise it, lowering it needs the guardian
+        #[ink(message)]
+        pub fn set_owner_rotation_delay(&mut self,
+            owner_rotation_delay: Timestamp,) -> Result<(), Error> {
+
+            // Check the caller, the owner may raise the delay and the guardian may change it
+            let caller = self.env().caller();
+            let raising = owner_rotation_delay >= self.owner_rotation_delay;
+            if !(raising && caller == self.vesting_owner) && Some(caller) != self.guardian {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            self.owner_rotation_delay = owner_rotation_delay;
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::OwnerRotationDelaySet));
+
+            Ok(())
+        }
+
+        /// Propose a new owner, it can take over with `accept_owner` once the rotation delay has passed.
+        /// A new proposal replaces the pending one
+        #[ink(message)]
+        pub fn propose_owner(&mut self,
+            new_owner: AccountId,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            // The guardian cannot become the owner it co-signs for
+            if self.guardian == Some(new_owner) {
+                self.fail(caller, Error::GuardianIsOwner)?;
+                return Ok(());
+            }
+
+            let activates_at = self.env().block_timestamp().saturating_add(self.owner_rotation_delay);
+            self.pending_owner = Some((new_owner, activates_at));
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::OwnerProposed));
+
+            self.env().emit_event(OwnerProposed {
+                current_owner: caller,
+                new_owner,
+                activates_at,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel the pending owner rotation, callable by the owner or the guardian
+        #[ink(message)]
+        pub fn cancel_owner_rotation(&mut self,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner or the guardian
+            let caller = self.env().caller();
+            if caller != self.vesting_owner && Some(caller) != self.guardian {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = match self.pending_owner.take() {
+                Some(_) => Ok(()),
+                None => Err(Error::NoPendingOwner),
+            };
+
+            self.finish(caller, result, Success::OwnerRotationCancelled)
+        }
+
+        /// Take over as owner, callable by the proposed owner once the rotation delay has passed
+        #[ink(message)]
+        pub fn accept_owner(&mut self,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            let result = match self.pending_owner {
+                None => Err(Error::NoPendingOwner),
+                Some((new_owner, _)) if new_owner != caller => Err(Error::BadOrigin),
+                Some((_, activates_at)) if now < activates_at => Err(Error::OwnerRotationNotReady { activates_at }),
+                Some(_) if self.guardian == Some(caller) => Err(Error::GuardianIsOwner),
+                Some(_) => {
+                    self.vesting_owner = caller;
+                    self.pending_owner = None;
+                    Ok(())
+                }
+            };
+
+            self.finish(caller, result, Success::OwnerChanged)
+        }
+
+        /// Get the proposed owner and the time it can take over
+        #[ink(message)]
+        pub fn get_pending_owner(&self,) -> Option<(AccountId, Timestamp)> {
+            self.pending_owner
+        }
+
+        /// Revert an erroneous approval back to requested or liquid, co-signed by the owner and the guardian.
+        /// Both must call with the same target status, the original tx-hash is kept in the schedule history
+        #[ink(message)]
+        pub fn revert_approval(&mut self,
+            address: AccountId,
+            schedule_number: u16,
+            to_status: ScheduleStatus,
+            reason: Vec<u8>,) -> Result<(), Error> {
+
+            let caller = self.env().caller();
+            let is_owner = caller == self.vesting_owner;
+            let is_guardian = self.guardian == Some(caller);
+
+            let result = if !is_owner && !is_guardian {
+                Err(Error::BadOrigin)
+            } else if self.guardian.is_none() {
+                Err(Error::GuardianNotSet)
+            } else if !matches!(to_status, ScheduleStatus::Liquid | ScheduleStatus::Requested) {
+                Err(Error::InvalidRevertTarget { status: to_status })
+            } else {
+                // A signature on different terms replaces the pending revert
+                let mut revert = match self.approval_reverts.get((address, schedule_number)) {
+                    Some(revert) if revert.to_status == to_status && revert.reason == reason => revert,
+                    _ => ApprovalRevert {
+                        to_status,
+                        reason,
+                        owner_signer: None,
+                        guardian_signer: None,
+                    },
+                };
+                if is_owner {
+                    revert.owner_signer = Some(caller);
+                }
+                if is_guardian {
+                    revert.guardian_signer = Some(caller);
+                }
+
+                // Signatures count while their signer still holds the role, and the two must be different accounts
+                let owner_signed = revert.owner_signer == Some(self.vesting_owner);
+                let guardian_signed = revert.guardian_signer.is_some() && revert.guardian_signer == self.guardian;
+                if owner_signed && guardian_signed && revert.owner_signer == revert.guardian_signer {
+                    Err(Error::GuardianIsOwner)
+                } else if owner_signed && guardian_signed {
+                    self.approval_reverts.remove((address, schedule_number));
+                    self.apply_approval_revert(caller, address, schedule_number, revert)
+                        .map(|()| Success::ApprovalReverted)
+                } else {
+                    self.approval_reverts.insert((address, schedule_number), &revert);
+                    Ok(Success::ApprovalRevertSigned)
+                }
+            };
+
+            match result {
+                Ok(success) => self.emit_status(caller, VestingStatus::EmitSuccess(success)),
+                Err(error) => self.fail(caller, error)?,
+            }
+
+            Ok(())
+        }
+
+        /// Check that the balance totals of an address match its schedules
+        #[ink(message)]
+        pub fn verify_balances(&self,
+            address: AccountId,) -> bool {
+            match self.vested_balances.iter().find(|v| v.address == address) {
+                Some(vested_balance) => {
+                    let mut recalculated = vested_balance.clone();
+                    Self::calculate_balances(&mut recalculated);
+                    recalculated == *vested_balance
+                }
+                None => false,
+            }
+        }
+
+        /// Recalculate the balance totals of an address from its schedules
+        #[ink(message)]
+        pub fn repair_balances(&mut self,
+            address: AccountId,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = match self.vested_balances.iter_mut().find(|v| v.address == address) {
+                Some(vested_balance) => {
+                    Self::calculate_balances(vested_balance);
+                    Self::bump_version(vested_balance);
+                    Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+                    Ok(())
+                }
+                None => Err(Error::VestedBalanceNotFound { address }),
+            };
+
+            self.finish(caller, result, Success::VestedBalanceRepaired)
+        }
+
+        /// Get the beneficiary and schedule number settled by a tx-hash
+        #[ink(message)]
+        pub fn find_by_tx_hash(&self,
+            tx_hash: Vec<u8>,) -> Option<(AccountId, u16)> {
+            self.tx_hashes.get(tx_hash)
+        }
+
+        /// Get the history of a schedule, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_schedule_history(&self,
+            address: AccountId,
+            schedule_number: u16,) -> Vec<ScheduleHistoryEntry> {
+            if !self.can_view(address) {
+                return Vec::new();
+            }
+            self.schedule_history
+                .get((address, schedule_number))
+                .unwrap_or_default()
+        }
+
+        /// Get the statement of an account between two times, inclusive: the unlocks and requests of its
+        /// schedules and the settlements of its receipts. Answers the account itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_statement(&self,
+            address: AccountId,
+            from_ts: Timestamp,
+            to_ts: Timestamp,) -> Statement {
+
+            let in_period = |at: Timestamp| at >= from_ts && at <= to_ts;
+            let mut entries = Vec::new();
+
+            // Others than the account itself get an empty statement unless they may view all
+            if !self.can_view(address) {
+                return Statement {
+                    address,
+                    from: from_ts,
+                    to: to_ts,
+                    unlocked: 0,
+                    requested: 0,
+                    settled: 0,
+                    entries,
+                };
+            }
+
+            let schedules = self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .map(|v| v.vested_balance_schedules.as_slice())
+                .unwrap_or_default();
+            for schedule in schedules.iter() {
+                let schedule_number = schedule.schedule_number;
+
+                // Unlocks and requests are read from the history of the schedule
+                let history = self.schedule_history.get((address, schedule_number)).unwrap_or_default();
+                for entry in history.iter().filter(|entry| in_period(entry.timestamp)) {
+                    let (kind, recipient) = match (entry.previous_status, entry.status) {
+                        (ScheduleStatus::Frozen, ScheduleStatus::Liquid) => (StatementEntryKind::Unlock, None),
+                        (ScheduleStatus::Liquid, ScheduleStatus::Requested) => (StatementEntryKind::Request, self.recipient_of(address, schedule_number)),
+                        _ => continue,
+                    };
+                    entries.push(StatementEntry {
+                        kind,
+                        schedule_number,
+                        amount: schedule.schedule_balance,
+                        at: entry.timestamp,
+                        recipient,
+                        tx_hash: Vec::new(),
+                    });
+                }
+            }
+
+            for receipt_id in self.beneficiary_receipts.get(address).unwrap_or_default() {
+                if let Some(receipt) = self.receipts.get(receipt_id).filter(|r| in_period(r.settled_at)) {
+                    entries.push(StatementEntry {
+                        kind: StatementEntryKind::Settlement,
+                        schedule_number: receipt.schedule_number,
+                        amount: receipt.amount,
+                        at: receipt.settled_at,
+                        recipient: Some(receipt.recipient),
+                        tx_hash: receipt.tx_hash,
+                    });
+                }
+            }
+            entries.sort_by_key(|entry| entry.at);
+
+            let total = |kind: StatementEntryKind| entries.iter()
+                .filter(|entry| entry.kind == kind)
+                .map(|entry| entry.amount)
+                .sum();
+
+            Statement {
+                address,
+                from: from_ts,
+                to: to_ts,
+                unlocked: total(StatementEntryKind::Unlock),
+                requested: total(StatementEntryKind::Request),
+                settled: total(StatementEntryKind::Settlement),
+                entries,
+            }
+        }
+
+        /// Get a settlement receipt
+        #[ink(message)]
+        pub fn get_receipt(&self,
+            receipt_id: u64,) -> Option<Receipt> {
+            self.receipts.get(receipt_id)
+        }
+
+        /// Get a page of the settlement receipts of a beneficiary, oldest first, answers the beneficiary and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_receipts_for(&self,
+            address: AccountId,
+            offset: u32,
+            limit: u32,) -> Vec<Receipt> {
+            if !self.can_view(address) {
+                return Vec::new();
+            }
+            self.beneficiary_receipts
+                .get(address)
+                .unwrap_or_default()
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .filter_map(|receipt_id| self.receipts.get(receipt_id))
+                .collect()
+        }
+
+        /// Removes the balance and its schedules, the record is archived. An account with requested
+        /// schedules or transferred balance is only removed when forced
+        #[ink(message)]
+        pub fn remove_vested_balance(&mut self,
+            address: AccountId,
+            force: bool,
+            op_id: Option<[u8; 32]>) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            } 
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(());
+            }
+
+            let result = self.remove_balance(address, force);
+            if result.is_ok() {
+                self.record_op_id(op_id);
+            }
+
+            self.finish(caller, result, Success::VestedBalanceRemoved)
+        }
+
+        /// Removes several balances and their schedules, the records are archived. Accounts with activity
+        /// are only removed when forced. A bad address does not stop the rest, returns the result of each address
+        #[ink(message)]
+        pub fn remove_vested_balances(&mut self,
+            addresses: Vec<AccountId>,
+            force: bool,
+            op_id: Option<[u8; 32]>) -> Result<BatchResult, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(Vec::new());
+            }
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            let mut results = Vec::with_capacity(addresses.len());
+            for (index, address) in addresses.into_iter().enumerate() {
+                let result = self.remove_balance(address, force);
+                self.emit_result(caller, result.clone(), Success::VestedBalanceRemoved);
+
+                results.push(BatchItemResult {
+                    index: index as u32,
+                    result: result.map(|()| Success::VestedBalanceRemoved),
+                });
+            }
+
+            self.record_op_id(op_id);
+
+            self.finish_batch(results)
+        }
+
+        /// Get the last archived vested balance of an address, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_archived(&self,
+            address: AccountId,) -> Option<VestedBalance> {
+            if !self.can_view(address) {
+                return None;
+            }
+            self.archived_balances.get(address)
+                .and_then(|mut records| records.pop())
+                .map(|v| self.to_view(v))
+        }
+
+        /// Get every archived vested balance of an address, oldest first, answers the address itself and, when private, the owner and the viewers
+        #[ink(message)]
+        pub fn get_archived_records(&self,
+            address: AccountId,) -> Vec<VestedBalance> {
+            if !self.can_view(address) {
+                return Vec::new();
+            }
+            self.archived_balances.get(address)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|v| self.to_view(v))
+                .collect()
+        }
+
+        /// Bring the last archived vested balance back, the address must not have an active balance
+        #[ink(message)]
+        pub fn restore_archived(&mut self,
+            address: AccountId,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = if self.vested_balances.iter().any(|v| v.address == address) {
+                Err(Error::VestedBalanceAlreadyExist { address })
+            } else {
+                let mut records = self.archived_balances.take(address).unwrap_or_default();
+                let restored = records.pop();
+                if !records.is_empty() {
+                    self.archived_balances.insert(address, &records);
+                }
+                match restored {
+                    Some(archived) => {
+                        self.total_original_balance += archived.original_balance;
+                        for schedule in archived.vested_balance_schedules.iter() {
+                            Self::track_status(&mut self.status_totals, None, Some(schedule.status), schedule.schedule_balance);
+                        }
+                        Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(&archived));
+                        let mut archived = archived;
+                        for schedule in archived.vested_balance_schedules.iter_mut() {
+                            if !schedule.particulars.is_empty() {
+                                self.schedule_particulars.insert(schedule.schedule_id, &core::mem::take(&mut schedule.particulars));
+                            }
+                        }
+                        self.vested_balances.push(archived);
+                        Ok(())
+                    }
+                    None => Err(Error::ArchivedBalanceNotFound { address }),
+                }
+            };
+
+            self.finish(caller, result, Success::VestedBalanceRestored)
+        }
+
+        /// Permanently delete the settled archived vested balances and the storage kept for the account (schedule
+        /// history, approvals, disputes, amendments, recovery and compliance data), freeing its storage deposit.
+        /// Accounts that were added again are not purged, the storage belongs to their active balance.
+        /// Recorded tx-hashes and nonces stay to keep rejecting replays
+        #[ink(message)]
+        pub fn purge_archived(&mut self,
+            address: AccountId,) -> Result<(), Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            if self.env().caller() != self.vesting_owner {
+                self.fail(caller, Error::BadOrigin)?;
+                return Ok(());
+            }
+
+            let result = match self.archived_balances.get(address) {
+                Some(_) if self.vested_balances.iter().any(|v| v.address == address) => {
+                    Err(Error::VestedBalanceAlreadyExist { address })
+                }
+                Some(records) => match records.iter().find(|v| v.requested_balance > 0 || v.disputed_balance > 0) {
+                    Some(archived) => Err(Error::VestedBalanceNotSettled {
+                        address,
+                        requested_balance: archived.requested_balance,
+                        disputed_balance: archived.disputed_balance,
+                    }),
+                    None => {
+                        let bytes = self.release_storage(address, &records);
+                        self.env().emit_event(StorageReleased {
+                            address,
+                            bytes,
+                            deposit: (bytes as Balance).saturating_mul(self.deposit_per_byte),
+                        });
+                        Ok(())
+                    }
+                },
+                None => Err(Error::ArchivedBalanceNotFound { address }),
+            };
+
+            self.finish(caller, result, Success::ArchivedBalancePurged)
+        }
+
+        /// Helper function to remove the archived balances and the storage kept for their account,
+        /// returns the encoded bytes removed
+        fn release_storage(&mut self,
+            address: AccountId,
+            records: &[VestedBalance]) -> u32 {
+
+            let mut bytes = records.encoded_size();
+            self.archived_balances.remove(address);
+
+            for schedule in records.iter().flat_map(|v| v.vested_balance_schedules.iter()) {
+                let key = (address, schedule.schedule_number);
+                bytes += self.schedule_history.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.approvals.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.approval_reverts.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.disputes.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.pledges.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.price_gates.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.xcm_destinations.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.eth_recipients.take(key).map_or(0, |v| v.encoded_size());
+                bytes += self.recipient_commitments.take(key).map_or(0, |v| v.encoded_size());
+            }
+            bytes += self.amendment_history.take(address).map_or(0, |v| v.encoded_size());
+            bytes += self.backups.take(address).map_or(0, |v| v.encoded_size());
+            bytes += self.last_activity.take(address).map_or(0, |v| v.encoded_size());
+            bytes += self.recovery_configs.take(address).map_or(0, |v| v.encoded_size());
+            bytes += self.recoveries.take(address).map_or(0, |v| v.encoded_size());
+            bytes += self.compliance_profiles.take(address).map_or(0, |v| v.encoded_size());
+            if let Some(suspended_at) = self.suspensions.take(address) {
+                self.suspended_count -= 1;
+                bytes += suspended_at.encoded_size();
+            }
+            bytes += self.curves.take(address).map_or(0, |v| v.encoded_size());
+            bytes += self.delegates.take(address).map_or(0, |v| v.encoded_size());
+
+            bytes as u32
+        }
+
+        /// Helper function to archive a vested balance and remove its schedules from the totals.
+        /// Earlier archives of the address are kept
+        fn remove_balance(&mut self,
+            address: AccountId,
+            force: bool) -> Result<(), Error> {
+
+            let index = self.vested_balances.iter()
+                .position(|v| v.address == address)
+                .ok_or(Error::VestedBalanceNotFound { address })?;
+
+            // Requested (or approved) schedules and transferred balances are accounting data
+            let vested_balance = &self.vested_balances[index];
+            let has_activity = vested_balance.requested_balance > 0 || vested_balance.transferred_balance > 0;
+            if has_activity && !force {
+                return Err(Error::VestedBalanceHasActivity {
+                    address,
+                    requested_balance: vested_balance.requested_balance,
+                    transferred_balance: vested_balance.transferred_balance,
+                });
+            }
+
+            let removed = self.vested_balances.swap_remove(index);
+            self.env().emit_event(BalanceRemoved {
+                address,
+                forfeited: Self::remaining_balance(&removed),
+                transferred: removed.transferred_balance,
+                forced: has_activity,
+            });
+
+            self.total_original_balance = self.total_original_balance.saturating_sub(removed.original_balance);
+            for schedule in removed.vested_balance_schedules.iter() {
+                Self::track_status(&mut self.status_totals, Some(schedule.status), None, schedule.schedule_balance);
+            }
+            self.holders_index.retain(|(a, _)| *a != address);
+
+            // The archived record keeps the particulars of its schedules
+            let mut removed = removed;
+            for schedule in removed.vested_balance_schedules.iter_mut() {
+                if let Some(particulars) = self.schedule_particulars.take(schedule.schedule_id) {
+                    schedule.particulars = particulars;
+                }
+            }
+            let mut records = self.archived_balances.get(address).unwrap_or_default();
+            records.push(removed);
+            self.archived_balances.insert(address, &records);
+
+            Ok(())
+        }
+        
+        /// Helper function to check if an operation id was already applied
+        fn is_applied_op(&self, op_id: Option<[u8; 32]>) -> bool {
+            op_id.is_some_and(|id| self.applied_op_ids.contains(id))
+        }
+
+        /// Helper function to record an applied operation id
+        fn record_op_id(&mut self, op_id: Option<[u8; 32]>) {
+            if let Some(id) = op_id {
+                self.applied_op_ids.insert(id, &self.env().block_number());
+            }
+        }
+
+        /// Helper function to request the transfer of a liquid schedule, returns the request id
+        fn create_transfer_request(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            recipient: Recipient,
+            memo: Vec<u8>) -> Result<u64, Error> {
+
+            let recipient_address = self.recipient_account(recipient);
+
+            if memo.len() > MAX_MEMO_LENGTH {
+                return Err(Error::MemoTooLong { length: memo.len() as u32 });
+            }
+
+            let now = self.env().block_timestamp();
+
+            // Ensure the investor category of the beneficiary is out of its lockup
+            if let Some(profile) = self.compliance_profiles.get(beneficiary) {
+                if let Some(until) = self.category_lockups.get(profile.category).filter(|until| now < *until) {
+                    return Err(Error::CategoryLockedUp { category: profile.category, until });
+                }
+            }
+
+            // Ask the compliance hook before the request, the account of an XCM destination is not local
+            if let Some(amount) = self.vested_balances.iter()
+                .filter(|_| !matches!(recipient, Recipient::Xcm(_)))
+                .find(|v| v.address == beneficiary)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .map(|s| s.schedule_balance) {
+                self.check_compliance_hook(beneficiary, recipient_address, amount)?;
+            }
+
+            self.check_pledge(beneficiary, schedule_number)?;
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, beneficiary, schedule_number)?;
+
+            // Ensure the beneficiary is KYC verified when required
+            if self.kyc_required && !vested_balance.kyc_verified {
+                return Err(Error::KycNotVerified { address: beneficiary });
+            }
+
+            // Ensure the recipient is the pinned one, an XCM destination never is
+            if let Some(pinned) = vested_balance.pinned_recipient
+                .filter(|pinned| *pinned != recipient_address || matches!(recipient, Recipient::Xcm(_))) {
+                return Err(Error::RecipientPinned {
+                    address: beneficiary,
+                    recipient: pinned,
+                });
+            }
+
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            // Ensure the schedule is liquid
+            if schedule.status != ScheduleStatus::Liquid {
+                return Err(Error::VestedBalanceScheduleNotLiquid {
+                    address: beneficiary,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            // Update the schedule
+            let request_id = self.next_request_id;
+            self.next_request_id += 1;
+
+            schedule.status = ScheduleStatus::Requested;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Liquid), Some(ScheduleStatus::Requested), schedule.schedule_balance);
+            schedule.recipient_address = Some(recipient_address);
+            schedule.request_id = Some(request_id);
+            schedule.requested_at = Some(now);
+            schedule.memo = memo.clone();
+            let amount = schedule.schedule_balance;
+            let schedule_id = schedule.schedule_id;
+
+            // Update balances
+            Self::move_balance(vested_balance, ScheduleStatus::Liquid, ScheduleStatus::Requested, amount);
+            Self::record_history(&mut self.schedule_history, (beneficiary, schedule_number), Self::status_entry(ScheduleStatus::Liquid, ScheduleStatus::Requested, Self::env().caller()));
+
+            // Approvals, the XCM destination and the EVM address of a previous request do not carry over,
+            // a pending recipient commitment is superseded by the request
+            self.approvals.remove((beneficiary, schedule_number));
+            self.xcm_destinations.remove((beneficiary, schedule_number));
+            self.recipient_commitments.remove((beneficiary, schedule_number));
+            match recipient {
+                Recipient::EthAddress(eth_address) => {
+                    self.eth_recipients.insert((beneficiary, schedule_number), &eth_address);
+                }
+                Recipient::Xcm(destination) => {
+                    self.eth_recipients.remove((beneficiary, schedule_number));
+                    self.xcm_destinations.insert((beneficiary, schedule_number), &destination);
+                }
+                Recipient::Account(_) => self.eth_recipients.remove((beneficiary, schedule_number)),
+            }
+
+            self.requests.insert(request_id, &TransferRequest {
+                beneficiary,
+                schedule_number,
+                recipient_address,
+                amount,
+                requested_at: now,
+                requested_block: self.env().block_number(),
+                memo: memo.clone(),
+                verified: false,
+            });
+
+            self.env().emit_event(TransferRequested {
+                request_id,
+                beneficiary,
+                schedule_number,
+                schedule_id,
+                recipient,
+                amount,
+                memo,
+            });
+
+            Ok(request_id)
+        }
+
+        /// Helper function to commit to the recipient of a liquid schedule
+        fn commit_recipient(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            commitment: Hash) -> Result<(), Error> {
+
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, beneficiary, schedule_number)?;
+            let schedule = &vested_balance.vested_balance_schedules[index];
+
+            // Ensure the schedule is liquid
+            if schedule.status != ScheduleStatus::Liquid {
+                return Err(Error::VestedBalanceScheduleNotLiquid {
+                    address: beneficiary,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            self.recipient_commitments.insert((beneficiary, schedule_number), &commitment);
+
+            Ok(())
+        }
+
+        /// Helper function to check a revealed recipient and salt against the commitment of a schedule
+        fn check_recipient_commitment(&self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            recipient: Recipient,
+            salt: [u8; 32]) -> Result<(), Error> {
+
+            let commitment = self.recipient_commitments
+                .get((beneficiary, schedule_number))
+                .ok_or(Error::RecipientCommitmentNotFound { address: beneficiary, schedule_number })?;
+
+            if Hash::from(self.env().hash_encoded::<Blake2x256, _>(&(recipient, salt))) != commitment {
+                return Err(Error::RecipientCommitmentMismatch { address: beneficiary, schedule_number });
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to approve a requested schedule as transferred, paying native vesting out of
+        /// the escrow. Returns the recipient and the amount
+        fn approve_requested(&mut self,
+            operator: AccountId,
+            address: AccountId,
+            schedule_number: u16,
+            tx_hash: Vec<u8>,
+            memo: Vec<u8>) -> Result<(Recipient, u128), Error> {
+
+            if memo.len() > MAX_MEMO_LENGTH {
+                return Err(Error::MemoTooLong { length: memo.len() as u32 });
+            }
+
+            // Ask the compliance operator and hook before the transfer, XCM transfers are settled by their message hash
+            let destination = self.xcm_destinations.get((address, schedule_number));
+            self.check_verified(address, schedule_number)?;
+            self.check_requested_transfer(address, schedule_number)?;
+            self.check_escrow_approval(address, schedule_number)?;
+            if destination.is_none() {
+                self.check_tx_hash(address, schedule_number, &tx_hash)?;
+            }
+            let recipient = self.recipient_of(address, schedule_number).unwrap_or(Recipient::Account(address));
+
+            let block_number = self.env().block_number();
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            // Ensure the schedule is requested
+            if schedule.status != ScheduleStatus::Requested {
+                return Err(Error::VestedBalanceScheduleNotRequested {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            let amount = schedule.schedule_balance;
+            let recipient_address = schedule.recipient_address.unwrap_or(address);
+
+            // Native vesting is paid out of the escrow and XCM transfers are dispatched before the update
+            let schedule_id = schedule.schedule_id;
+            let paid = match destination {
+                Some(destination) => {
+                    let asset_id = (self.asset_kind == AssetKind::Asset).then_some(self.asset_id);
+                    Self::send_xcm(&mut self.native_pool, asset_id, destination, amount).map(Some)
+                }
+                None if self.asset_kind == AssetKind::Native => {
+                    Self::pay_native(&mut self.native_pool, self.enforcement_backend, recipient_address, amount).map(|_| None)
+                }
+                None => Ok(None),
+            };
+            let message_hash = match paid {
+                Ok(message_hash) => message_hash,
+                Err(error) => {
+                    self.record_failed_payout(address, schedule_number, schedule_id, recipient_address, amount, error.clone());
+                    return Err(error);
+                }
+            };
+            let tx_hash = message_hash.map_or(tx_hash, |message_hash| message_hash.to_vec());
+
+            // Update the schedule
+            if let Some(request_id) = schedule.request_id {
+                self.requests.remove(request_id);
+            }
+            schedule.status = ScheduleStatus::Transferred;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Requested), Some(ScheduleStatus::Transferred), amount);
+
+            // Record who approved the transfer and why
+            let entry = ScheduleHistoryEntry {
+                previous_status: ScheduleStatus::Requested,
+                status: ScheduleStatus::Transferred,
+                particulars: self.schedule_particulars.take(schedule_id).unwrap_or_default(),
+                reason: memo,
+                operator,
+                block_number,
+                timestamp: Self::env().block_timestamp(),
+            };
+
+            // Update balances
+            Self::move_balance(vested_balance, ScheduleStatus::Requested, ScheduleStatus::Transferred, amount);
+            if destination.is_none() {
+                Self::pin_recipient(vested_balance, recipient_address);
+            }
+            Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+            Self::record_history(&mut self.schedule_history, (address, schedule_number), entry);
+            self.schedule_particulars.insert(schedule_id, &tx_hash);
+            self.record_tx_hash(address, schedule_number, &tx_hash);
+            self.clear_failed_payout(address, schedule_number);
+            if self.is_approver(operator) {
+                self.last_approval_block = block_number;
+            }
+            self.record_receipt(address, schedule_number, amount, tx_hash);
+            if let (Some(destination), Some(message_hash)) = (destination, message_hash) {
+                self.xcm_destinations.remove((address, schedule_number));
+                self.env().emit_event(XcmTransferSent {
+                    beneficiary: address,
+                    schedule_number,
+                    destination,
+                    amount,
+                    message_hash,
+                });
+            }
+
+            Ok((recipient, amount))
+        }
+
+        /// Helper function to ask the compliance hook about the transfer of a requested schedule
+        fn check_requested_transfer(&self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            // Schedules that are not requested are reported by the approval, XCM destinations have no local account
+            match self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .filter(|s| s.status == ScheduleStatus::Requested)
+                .filter(|_| !self.xcm_destinations.contains((address, schedule_number))) {
+                Some(schedule) => self.check_compliance_hook(
+                    address,
+                    schedule.recipient_address.unwrap_or(address),
+                    schedule.schedule_balance,
+                ),
+                None => Ok(()),
+            }
+        }
+
+        /// Helper function to check the request of a requested schedule was verified, when a compliance operator is set
+        fn check_verified(&self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            if self.compliance_operator.is_none() {
+                return Ok(());
+            }
+
+            // Schedules that are not requested are reported by the approval
+            match self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .filter(|s| s.status == ScheduleStatus::Requested)
+                .and_then(|s| s.request_id) {
+                Some(request_id) if !self.requests.get(request_id).is_some_and(|request| request.verified) => {
+                    Err(Error::RequestNotVerified { request_id })
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// Helper function to check a requested transfer above the escrow threshold of its grant was approved by the escrow
+        fn check_escrow_approval(&self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            let Some(config) = self.escrows.get(address) else {
+                return Ok(());
+            };
+
+            // Schedules that are not requested are reported by the approval
+            match self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .filter(|s| s.status == ScheduleStatus::Requested && s.schedule_balance > config.threshold)
+                .and_then(|s| s.request_id) {
+                Some(request_id) if self.escrow_approvals.get(request_id) != Some(config.escrow) => {
+                    Err(Error::EscrowApprovalRequired { request_id, escrow: config.escrow })
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// Helper function to ask the compliance hook whether a transfer is allowed
+        fn check_compliance_hook(&self,
+            beneficiary: AccountId,
+            recipient: AccountId,
+            amount: u128) -> Result<(), Error> {
+
+            let hook = match self.compliance_hook {
+                Some(hook) => hook,
+                None => return Ok(()),
+            };
+
+            let allowed = build_call::<Environment>()
+                .call(hook)
+                .gas_limit(self.callback_gas_limit)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("is_allowed")))
+                        .push_arg(beneficiary)
+                        .push_arg(recipient)
+                        .push_arg(amount),
+                )
+                .returns::<bool>()
+                .try_invoke();
+
+            match allowed {
+                Ok(Ok(true)) => Ok(()),
+                Ok(Ok(false)) => Err(Error::TransferNotAllowed { beneficiary, recipient }),
+                _ => Err(Error::ComplianceHookFailed { hook }),
+            }
+        }
+
+        /// Helper function to pledge frozen schedules to a lender, all or none. A lender contract must accept the pledge
+        fn pledge_schedules(&mut self,
+            beneficiary: AccountId,
+            schedule_numbers: &[u16],
+            lender: AccountId) -> Result<(), Error> {
+
+            let vested_balance = self.vested_balances.iter()
+                .find(|v| v.address == beneficiary)
+                .ok_or(Error::VestedBalanceNotFound { address: beneficiary })?;
+
+            let mut amount: u128 = 0;
+            for (index, schedule_number) in schedule_numbers.iter().enumerate() {
+                if schedule_numbers[..index].contains(schedule_number) {
+                    return Err(Error::DuplicateScheduleNumber { schedule_number: *schedule_number });
+                }
+                self.check_pledge(beneficiary, *schedule_number)?;
+                let schedule = vested_balance.vested_balance_schedules.iter()
+                    .find(|s| s.schedule_number == *schedule_number)
+                    .ok_or(Error::VestedBalanceScheduleNotFound { address: beneficiary, schedule_number: *schedule_number })?;
+                if schedule.status != ScheduleStatus::Frozen {
+                    return Err(Error::VestedBalanceScheduleNotFrozen {
+                        address: beneficiary,
+                        schedule_number: *schedule_number,
+                        status: schedule.status,
+                    });
+                }
+                amount += schedule.schedule_balance;
+            }
+
+            if self.env().is_contract(&lender) {
+                let accepted = build_call::<Environment>()
+                    .call(lender)
+                    .gas_limit(self.callback_gas_limit)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ink::selector_bytes!("on_collateral_locked")))
+                            .push_arg(beneficiary)
+                            .push_arg(schedule_numbers)
+                            .push_arg(amount),
+                    )
+                    .returns::<bool>()
+                    .try_invoke();
+                if !matches!(accepted, Ok(Ok(true))) {
+                    return Err(Error::CollateralNotAccepted { lender });
+                }
+            }
+
+            for schedule_number in schedule_numbers.iter() {
+                self.pledges.insert((beneficiary, *schedule_number), &lender);
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to reject changes to a schedule pledged as collateral
+        fn check_pledge(&self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            match self.pledges.get((address, schedule_number)) {
+                Some(lender) => Err(Error::SchedulePledged { address, schedule_number, lender }),
+                None => Ok(()),
+            }
+        }
+
+        /// Helper function to call the transfer callback of a recipient with the callback gas limit.
+        /// A failed callback is reported but does not revert the transfer, XCM destinations are not called
+        fn notify_recipient(&mut self,
+            operator: AccountId,
+            recipient: Recipient,
+            beneficiary: AccountId,
+            amount: u128) {
+
+            let recipient = match recipient {
+                Recipient::Xcm(_) => return,
+                recipient => self.recipient_account(recipient),
+            };
+            let selector = match self.transfer_callbacks.get(recipient) {
+                Some(selector) => selector,
+                None => return,
+            };
+
+            let result = build_call::<Environment>()
+                .call(recipient)
+                .gas_limit(self.callback_gas_limit)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(selector))
+                        .push_arg(beneficiary)
+                        .push_arg(amount)
+                        .push_arg(self.asset_id),
+                )
+                .returns::<()>()
+                .try_invoke();
+
+            if !matches!(result, Ok(Ok(()))) {
+                self.emit_status(operator, VestingStatus::EmitError(Error::RecipientCallbackFailed { recipient }));
+            }
+        }
+
+        /// Helper function to check an account may approve transfers
+        fn is_approver(&self, account: AccountId) -> bool {
+            account == self.vesting_owner
+                || self.approvers.contains(&account)
+                || self.guardian == Some(account)
+        }
+
+        /// Helper function to record an approval of a requested schedule, returns true once the
+        /// approvals required by the tier of its amount are collected and the approval can proceed.
+        /// Tiers requiring the guardian fail while none is set
+        fn collect_approval(&mut self,
+            approver: AccountId,
+            address: AccountId,
+            schedule_number: u16,
+            op_id: Option<[u8; 32]>) -> Result<bool, Error> {
+
+            // Applied operations and schedules that are not requested are reported by the approval
+            if self.is_applied_op(op_id) {
+                return Ok(true);
+            }
+            let amount = match self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .filter(|s| s.status == ScheduleStatus::Requested) {
+                Some(schedule) => schedule.schedule_balance,
+                None => return Ok(true),
+            };
+
+            let key = (address, schedule_number);
+            let mut approvals = self.approvals.get(key).unwrap_or_default();
+            if !approvals.contains(&approver) {
+                approvals.push(approver);
+            }
+
+            if self.tier_approved(amount, &approvals)? {
+                self.approvals.remove(key);
+                return Ok(true);
+            }
+
+            self.approvals.insert(key, &approvals);
+            self.emit_status(approver, VestingStatus::EmitSuccess(Success::ApprovalRecorded));
+
+            Ok(false)
+        }
+
+        /// Helper function to check the approvals of an amount meet the requirements of its tier.
+        /// Tiers requiring the guardian fail while none is set
+        fn tier_approved(&self,
+            amount: u128,
+            approvals: &[AccountId]) -> Result<bool, Error> {
+
+            let (required, guardian_required) = self.approval_tiers.iter()
+                .rev()
+                .find(|tier| amount >= tier.min_amount)
+                .map_or((1, false), |tier| (tier.approvals, tier.guardian));
+
+            if guardian_required && self.guardian.is_none() {
+                return Err(Error::GuardianNotSet);
+            }
+
+            let approved = approvals.iter().filter(|a| Some(**a) != self.guardian).count();
+            let guardian_approved = self.guardian.is_some_and(|guardian| approvals.contains(&guardian));
+
+            Ok(approved >= required as usize && (!guardian_required || guardian_approved))
+        }
+
+        /// Helper function to check an approval of a requested schedule by the approver completes the approvals
+        /// of its amount tier, without recording it
+        fn check_tier_approvals(&self,
+            approver: AccountId,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            // Schedules that are not requested are reported by the approval
+            let amount = match self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .filter(|s| s.status == ScheduleStatus::Requested) {
+                Some(schedule) => schedule.schedule_balance,
+                None => return Ok(()),
+            };
+
+            let mut approvals = self.approvals.get((address, schedule_number)).unwrap_or_default();
+            if !approvals.contains(&approver) {
+                approvals.push(approver);
+            }
+
+            match self.tier_approved(amount, &approvals)? {
+                true => Ok(()),
+                false => Err(Error::ApprovalsMissing { address, schedule_number }),
+            }
+        }
+
+        /// Helper function to check the approvals collected for a requested schedule leave at most one
+        /// approval of its amount tier for the dead-man switch. The guardian approval is never substituted
+        fn check_abandoned_approvals(&mut self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            // Schedules that are not requested are reported by the approval
+            let amount = match self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .filter(|s| s.status == ScheduleStatus::Requested) {
+                Some(schedule) => schedule.schedule_balance,
+                None => return Ok(()),
+            };
+
+            let (required, guardian_required) = self.approval_tiers.iter()
+                .rev()
+                .find(|tier| amount >= tier.min_amount)
+                .map_or((1, false), |tier| (tier.approvals, tier.guardian));
+
+            if guardian_required && self.guardian.is_none() {
+                return Err(Error::GuardianNotSet);
+            }
+
+            let key = (address, schedule_number);
+            let approvals = self.approvals.get(key).unwrap_or_default();
+            let approved = approvals.iter().filter(|a| Some(**a) != self.guardian).count();
+            let guardian_approved = self.guardian.is_some_and(|guardian| approvals.contains(&guardian));
+            if approved.saturating_add(1) < required as usize || (guardian_required && !guardian_approved) {
+                return Err(Error::ApprovalsMissing { address, schedule_number });
+            }
+
+            self.approvals.remove(key);
+
+            Ok(())
+        }
+
+        /// Helper function to check whether a schedule was already transferred
+        fn is_transferred(&self,
+            address: AccountId,
+            schedule_number: u16) -> bool {
+
+            self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .is_some_and(|s| s.status == ScheduleStatus::Transferred)
+        }
+
+        /// Helper function to get a transfer request that is still pending on its schedule
+        fn find_request(&self,
+            request_id: u64) -> Result<TransferRequest, Error> {
+
+            let request = self.requests.get(request_id)
+                .ok_or(Error::RequestNotFound { request_id })?;
+
+            let pending = self.vested_balances.iter()
+                .find(|v| v.address == request.beneficiary)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == request.schedule_number))
+                .is_some_and(|s| s.status == ScheduleStatus::Requested && s.request_id == Some(request_id));
+            if !pending {
+                return Err(Error::RequestNotFound { request_id });
+            }
+
+            Ok(request)
+        }
+
+        /// Helper function to return a pending transfer request to liquid
+        fn release_request(&mut self,
+            operator: AccountId,
+            request_id: u64,
+            reason: Vec<u8>) -> Result<(), Error> {
+
+            let request = self.find_request(request_id)?;
+            let block_number = self.env().block_number();
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, request.beneficiary, request.schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            let entry = ScheduleHistoryEntry {
+                previous_status: ScheduleStatus::Requested,
+                status: ScheduleStatus::Liquid,
+                particulars: self.schedule_particulars.take(schedule.schedule_id).unwrap_or_default(),
+                reason,
+                operator,
+                block_number,
+                timestamp: Self::env().block_timestamp(),
+            };
+
+            let amount = schedule.schedule_balance;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Requested), Some(ScheduleStatus::Liquid), amount);
+            schedule.status = ScheduleStatus::Liquid;
+            schedule.recipient_address = None;
+            schedule.request_id = None;
+            schedule.requested_at = None;
+            schedule.memo = Vec::new();
+
+            Self::move_balance(vested_balance, ScheduleStatus::Requested, ScheduleStatus::Liquid, amount);
+            self.requests.remove(request_id);
+
+            Self::record_history(&mut self.schedule_history, (request.beneficiary, request.schedule_number), entry);
+
+            Ok(())
+        }
+
+        /// Helper function to verify an ECDSA signature of the signer over a payload and consume its nonce
+        fn use_signature<P: scale::Encode>(&mut self,
+            signer: AccountId,
+            payload: &P,
+            nonce: u64,
+            signature: &[u8; 65]) -> Result<(), Error> {
+
+            let expected_nonce = self.nonces.get(signer).unwrap_or(0);
+            if nonce != expected_nonce {
+                return Err(Error::InvalidNonce { expected: expected_nonce });
+            }
+
+            let message_hash = self.env().hash_encoded::<Blake2x256, _>(payload);
+
+            let public_key = self.env().ecdsa_recover(signature, &message_hash)
+                .map_err(|_| Error::InvalidSignature)?;
+
+            // ECDSA accounts are the blake2x256 hash of the compressed public key
+            let account = self.env().hash_bytes::<Blake2x256>(&public_key);
+            if AccountId::from(account) != signer {
+                return Err(Error::InvalidSignature);
+            }
+
+            self.nonces.insert(signer, &(expected_nonce + 1));
+
+            Ok(())
+        }
+
+        /// Helper function to split an amount by weights, the last part takes the rounding remainder.
+        /// All zero weights split the amount evenly
+        fn split_by_weights(amount: u128, weights: &[u128]) -> Vec<u128> {
+            let total_weight = weights.iter().fold(0u128, |total, weight| total.saturating_add(*weight));
+            let mut allocated: u128 = 0;
+
+            weights.iter().enumerate().map(|(i, weight)| {
+                let part = if i + 1 == weights.len() {
+                    amount - allocated
+                } else if total_weight == 0 {
+                    amount / weights.len() as u128
+                } else {
+                    Self::mul_div(amount, *weight, total_weight).min(amount - allocated)
+                };
+                allocated += part;
+                part
+            }).collect()
+        }
+
+        /// Helper function to compute `a * b / c` rounded down through a 256-bit product, the result
+        /// must fit in 128 bits, which holds for `b <= c`
+        fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+            if let Some(product) = a.checked_mul(b) {
+                return product / c;
+            }
+
+            // Product of the 64-bit halves, carried into a high and a low half
+            const MASK: u128 = u64::MAX as u128;
+            let (a_high, a_low) = (a >> 64, a & MASK);
+            let (b_high, b_low) = (b >> 64, b & MASK);
+            let low_low = a_low * b_low;
+            let high_low = a_high * b_low;
+            let low_high = a_low * b_high;
+            let middle = (low_low >> 64) + (high_low & MASK) + (low_high & MASK);
+            let low = (low_low & MASK) | (middle << 64);
+            let high = a_high * b_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+
+            // Long division, one bit of the product at a time
+            let mut quotient: u128 = 0;
+            let mut remainder: u128 = 0;
+            for i in (0..256u32).rev() {
+                let bit = if i >= 128 { (high >> (i - 128)) & 1 } else { (low >> i) & 1 };
+                let carry = remainder >> 127;
+                remainder = (remainder << 1) | bit;
+                quotient <<= 1;
+                if carry == 1 || remainder >= c {
+                    remainder = remainder.wrapping_sub(c);
+                    quotient |= 1;
+                }
+            }
+
+            quotient
+        }
+
+        /// Helper function to check the price condition of a schedule, schedules without one are always open
+        fn price_gate_open<K: ink::storage::traits::StorageKey>(price_gates: &Mapping<ScheduleKey, PriceGate, K>,
+            prices: &[(Timestamp, u128)],
+            now: Timestamp,
+            key: ScheduleKey) -> bool {
+
+            price_gates.get(key).is_none_or(|price_gate| {
+                let observations = price_gate.observations as usize;
+                match price_gate.twap_window {
+                    None => prices.len() >= observations
+                        && prices[prices.len() - observations..].iter().all(|(_, price)| *price > price_gate.threshold),
+                    Some(window) => {
+                        let start = now.saturating_sub(window);
+                        let inside = prices.iter().filter(|(time, _)| *time > start).count();
+                        inside >= observations
+                            && Self::twap(prices, start, now).is_some_and(|price| price > price_gate.threshold)
+                    }
+                }
+            })
+        }
+
+        /// Helper function to get the time-weighted average price between two times, each price holds until
+        /// the next observation. None if the observations do not reach back to the start
+        fn twap(prices: &[(Timestamp, u128)],
+            start: Timestamp,
+            end: Timestamp) -> Option<u128> {
+
+            if end <= start || prices.first().is_none_or(|(time, _)| *time > start) {
+                return None;
+            }
+
+            let mut weighted: u128 = 0;
+            for (i, (time, price)) in prices.iter().enumerate() {
+                let from = (*time).max(start);
+                let to = prices.get(i + 1).map_or(end, |(next, _)| (*next).min(end));
+                if to > from {
+                    weighted = weighted.saturating_add(price.saturating_mul((to - from) as u128));
+                }
+            }
+
+            Some(weighted / (end - start) as u128)
+        }
+
+        /// Helper function to turn the steps of a curve into frozen schedules, the amount is split by the step weights
+        fn curve_schedules(original_balance: u128,
+            curve: &VestingCurve) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            let unlocks = curve.unlocks()?;
+            let weights: Vec<u128> = unlocks.iter().map(|(_, weight)| *weight).collect();
+
+            Ok(Self::split_by_weights(original_balance, &weights)
+                .into_iter()
+                .zip(unlocks)
+                .enumerate()
+                .map(|(i, (schedule_balance, (unlock_time, _)))| {
+                    Self::new_schedule(i as u16 + 1, schedule_balance, Some(unlock_time))
+                })
+                .collect())
+        }
+
+        /// Helper function to summarize a vested balance without its schedules
+        fn summary(vested_balance: &VestedBalance) -> VestedSummary {
+            VestedSummary {
+                address: vested_balance.address,
+                original: vested_balance.original_balance,
+                frozen: vested_balance.frozen_balance,
+                liquid: vested_balance.liquid_balance,
+                requested: vested_balance.requested_balance,
+                transferred: vested_balance.transferred_balance,
+                schedule_count: vested_balance.vested_balance_schedules.len() as u16,
+                version: vested_balance.version,
+            }
+        }
+
+        /// Helper function to create a frozen schedule
+        fn new_schedule(schedule_number: u16,
+            schedule_balance: u128,
+            unlock_time: Option<Timestamp>) -> VestedBalanceSchedule {
+
+            VestedBalanceSchedule {
+                schedule_number,
+                schedule_id: Hash::default(),   // derived when the schedule is stored
+                schedule_balance,
+                status: ScheduleStatus::Frozen,
+                recipient_address: None,     // the address is the default recipient
+                particulars: Vec::new(),
+                request_id: None,
+                unlock_time,
+                requested_at: None,
+                memo: Vec::new(),
+            }
+        }
+
+        /// Helper function to derive the stable identifier of a schedule created in the current block. The counter
+        /// tells apart schedules created in the same block, e.g. a grant removed and added again
+        fn schedule_id(counter: &mut u64,
+            address: AccountId,
+            schedule_number: u16) -> Hash {
+
+            let block_number = Self::env().block_number();
+            let schedule_id = Self::env().hash_encoded::<Blake2x256, _>(&(address, schedule_number, block_number, *counter));
+            *counter = counter.wrapping_add(1);
+            Hash::from(schedule_id)
+        }
+
+        /// Helper function to add a vested balance with its schedules, funded by the transferred value in native mode
+        fn add_grant(&mut self,
+            address: AccountId,
+            original_balance: u128,
+            schedules: Vec<VestedBalanceSchedule>,
+            op_id: Option<[u8; 32]>) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            // Check the caller, it must be the owner
+            let caller = self.env().caller();
+            let transferred = self.env().transferred_value();
+            if self.env().caller() != self.vesting_owner {
+                return self.refuse_funded(caller, Error::BadOrigin).map(|_| Vec::new());
+            } 
+
+            // Skip operations that were already applied
+            if self.is_applied_op(op_id) {
+                if transferred > 0 {
+                    return Err(Error::FundingMismatch { expected: 0, transferred });
+                }
+                self.emit_status(caller, VestingStatus::EmitSuccess(Success::OperationAlreadyApplied));
+                return Ok(Vec::new());
+            }
+
+            // Check if the address already exist
+            if self.vested_balances.iter().any(|v| v.address == address)
+            {
+                return self.refuse_funded(caller, Error::VestedBalanceAlreadyExist { address }).map(|_| Vec::new());
+            }
+
+            // The grant is funded in the same call, a mismatch reverts the transfer
+            let expected = match self.asset_kind {
+                AssetKind::Native => original_balance,
+                AssetKind::Asset => 0,
+            };
+            if transferred != expected {
+                return Err(Error::FundingMismatch { expected, transferred });
+            }
+
+            let schedules = match self.insert_grant(address, original_balance, schedules, transferred) {
+                Ok(schedules) => schedules,
+                Err(error) => return self.refuse_funded(caller, error).map(|_| Vec::new()),
+            };
+
+            self.record_op_id(op_id);
+
+            self.emit_status(caller, VestingStatus::EmitSuccess(Success::VestedBalanceAdded));
+
+            Ok(schedules)
+        }
+
+        /// Helper function to save a new vested balance and its funding, returns the saved schedules with their ids
+        fn insert_grant(&mut self,
+            address: AccountId,
+            original_balance: u128,
+            mut schedules: Vec<VestedBalanceSchedule>,
+            funding: u128) -> Result<Vec<VestedBalanceSchedule>, Error> {
+
+            // Check if the address already exist
+            if self.vested_balances.iter().any(|v| v.address == address) {
+                return Err(Error::VestedBalanceAlreadyExist { address });
+            }
+            self.check_supply(original_balance)?;
+            for schedule in schedules.iter() {
+                if schedule.schedule_number > self.max_schedule_number {
+                    return Err(Error::ScheduleNumberTooHigh {
+                        schedule_number: schedule.schedule_number,
+                        max_schedule_number: self.max_schedule_number,
+                    });
+                }
+                self.check_min_balance(schedule.schedule_number, schedule.schedule_balance)?;
+            }
+            self.native_pool += funding;
+
+            let frozen_balance: u128 = schedules.iter().map(|s| s.schedule_balance).sum();
+            for schedule in schedules.iter_mut() {
+                schedule.schedule_id = Self::schedule_id(&mut self.schedule_id_counter, address, schedule.schedule_number);
+                Self::track_status(&mut self.status_totals, None, Some(schedule.status), schedule.schedule_balance);
+            }
+            Self::track_holder(&mut self.holders_index, address, frozen_balance);
+            self.total_original_balance += original_balance;
+
+            // Save the vested balance
+            self.vested_balances.push(VestedBalance {
+                original_balance,
+                frozen_balance,
+                ..Self::new_balance(address, schedules.clone())
+            });
+
+            Ok(schedules)
+        }
+
+        /// Helper function to execute a single owner operation
+        fn execute_call(&mut self,
+            caller: AccountId,
+            call: VestingCall) -> Result<Success, Error> {
+
+            match call {
+                VestingCall::AddVestedBalance { address, original_balance } => {
+                    // Native grants are funded by the payable add messages only
+                    if self.asset_kind == AssetKind::Native && original_balance > 0 {
+                        return Err(Error::FundingMismatch { expected: original_balance, transferred: 0 });
+                    }
+                    let schedules = self.equal_schedules(original_balance);
+                    self.insert_grant(address, original_balance, schedules, 0)
+                        .map(|_| Success::VestedBalanceAdded)
+                }
+                VestingCall::ThawSchedule { address, schedule_number } => self
+                    .check_not_governed()
+                    .and_then(|()| self.thaw_schedule(address, schedule_number))
+                    .map(|()| Success::VestedBalanceScheduleThawed),
+                VestingCall::ForfeitSchedule { address, schedule_number } => self
+                    .forfeit_schedule(address, schedule_number)
+                    .map(|()| Success::VestedBalanceScheduleForfeited),
+                VestingCall::AmendGrant { address, change } => self
+                    .amend_grant(caller, address, change)
+                    .map(|amendment_id| match amendment_id {
+                        Some(_) => Success::AmendmentProposed,
+                        None => Success::AmendmentApplied,
+                    }),
+                VestingCall::RemoveVestedBalance { address, force } => self
+                    .remove_balance(address, force)
+                    .map(|()| Success::VestedBalanceRemoved),
+            }
+        }
+
+        /// Helper function to apply an amendment, or keep it for the beneficiary's consent when adverse.
+        /// Returns the id of a waiting amendment
+        fn amend_grant(&mut self,
+            proposed_by: AccountId,
+            address: AccountId,
+            change: AmendmentChange) -> Result<Option<u64>, Error> {
+
+            let previous = self.amendment_previous(address, &change)?;
+
+            // Governed programs bring unlocks forward through the governor only
+            if Self::brings_unlocks_forward(&previous, &change) {
+                self.check_not_governed()?;
+            }
+
+            let amendment_id = self.next_amendment_id;
+            self.next_amendment_id += 1;
+
+            // Adverse changes wait for the beneficiary
+            if Self::is_adverse(&previous, &change) {
+                self.amendments.insert(amendment_id, &PendingAmendment {
+                    address,
+                    change,
+                    proposed_by,
+                    proposed_at: self.env().block_timestamp(),
+                });
+                return Ok(Some(amendment_id));
+            }
+
+            self.apply_amendment(amendment_id, address, change, previous, proposed_by, None);
+
+            Ok(None)
+        }
+
+        /// Helper function to check an amendment against the frozen schedules of a grant,
+        /// returns the current values of the amended schedules
+        fn amendment_previous(&self,
+            address: AccountId,
+            change: &AmendmentChange) -> Result<AmendmentChange, Error> {
+
+            let vested_balance = self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .ok_or(Error::VestedBalanceNotFound { address })?;
+
+            let frozen_schedule = |schedule_number: u16| {
+                let schedule = vested_balance.vested_balance_schedules.iter()
+                    .find(|s| s.schedule_number == schedule_number)
+                    .ok_or(Error::VestedBalanceScheduleNotFound { address, schedule_number })?;
+                if schedule.status != ScheduleStatus::Frozen {
+                    return Err(Error::VestedBalanceScheduleNotFrozen {
+                        address,
+                        schedule_number,
+                        status: schedule.status,
+                    });
+                }
+                Ok(schedule)
+            };
+
+            match change {
+                AmendmentChange::UnlockTimes(unlock_times) => unlock_times.iter()
+                    .map(|(n, _)| frozen_schedule(*n).map(|s| (*n, s.unlock_time)))
+                    .collect::<Result<Vec<_>, Error>>()
+                    .map(AmendmentChange::UnlockTimes),
+                AmendmentChange::ScheduleAmounts(amounts) => {
+                    let previous = amounts.iter()
+                        .map(|(n, _)| frozen_schedule(*n).map(|s| (*n, s.schedule_balance)))
+                        .collect::<Result<Vec<_>, Error>>()?;
+
+                    // Native grants are escrowed, a non-payable amendment cannot fund an increase
+                    let increase: u128 = amounts.iter().zip(previous.iter())
+                        .map(|((_, new), (_, old))| new.saturating_sub(*old))
+                        .sum();
+                    if self.asset_kind == AssetKind::Native && increase > 0 {
+                        return Err(Error::FundingMismatch { expected: increase, transferred: 0 });
+                    }
+                    self.check_supply(increase)?;
+                    for (schedule_number, amount) in amounts.iter() {
+                        self.check_min_balance(*schedule_number, *amount)?;
+                    }
+
+                    Ok(AmendmentChange::ScheduleAmounts(previous))
+                }
+            }
+        }
+
+        /// Helper function to check that the outstanding obligations plus an increase stay within the asset supply
+        fn check_supply(&self,
+            increase: u128) -> Result<(), Error> {
+
+            // Native grants are funded in full when added
+            if self.asset_kind == AssetKind::Native {
+                return Ok(());
+            }
+            let Some(supply) = self.read_asset_supply()? else {
+                return Ok(());
+            };
+
+            let obligations = self.obligations().saturating_add(increase);
+            if obligations > supply {
+                return Err(Error::AssetSupplyExceeded { supply, obligations });
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to read the supply of the asset the grants are checked against, none when not checked
+        fn read_asset_supply(&self) -> Result<Option<Balance>, Error> {
+            let Some(supply_check) = self.supply_check else {
+                return Ok(None);
+            };
+
+            let holder = match supply_check {
+                SupplyScope::Total => None,
+                SupplyScope::OwnerHeld => Some(self.vesting_owner),
+            };
+            self.env().extension()
+                .asset_supply(self.asset_id, holder)
+                .map(Some)
+                .map_err(|error| Error::AssetSupplyUnavailable { error })
+        }
+
+        /// Helper function to total the outstanding obligations. Transferred and forfeited schedules are no longer owed
+        fn obligations(&self) -> u128 {
+            self.status_totals.iter()
+                .zip(ScheduleStatus::ALL)
+                .filter(|(_, status)| !matches!(status, ScheduleStatus::Transferred | ScheduleStatus::Forfeited))
+                .map(|((_, amount), _)| *amount)
+                .sum()
+        }
+
+        /// Helper function to check that a non-zero schedule amount is at least the asset's minimum balance
+        fn check_min_balance(&self,
+            schedule_number: u16,
+            amount: u128) -> Result<(), Error> {
+
+            let min_balance = self.asset_metadata.min_balance;
+            if amount > 0 && amount < min_balance {
+                return Err(Error::BelowMinimumBalance {
+                    schedule_number,
+                    amount,
+                    min_balance,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to tell if an amendment unlocks any schedule earlier than before
+        fn brings_unlocks_forward(previous: &AmendmentChange, change: &AmendmentChange) -> bool {
+            match (previous, change) {
+                (AmendmentChange::UnlockTimes(old), AmendmentChange::UnlockTimes(new)) => old.iter()
+                    .zip(new.iter())
+                    .any(|((_, old), (_, new))| match (old, new) {
+                        (None, Some(_)) => true,
+                        (Some(old), Some(new)) => new < old,
+                        (_, None) => false,
+                    }),
+                _ => false,
+            }
+        }
+
+        /// Helper function to tell if an amendment is against the beneficiary: a later (or no) unlock time
+        /// or a lower amount
+        fn is_adverse(previous: &AmendmentChange, change: &AmendmentChange) -> bool {
+            match (previous, change) {
+                (AmendmentChange::UnlockTimes(old), AmendmentChange::UnlockTimes(new)) => old.iter()
+                    .zip(new.iter())
+                    .any(|((_, old), (_, new))| match (old, new) {
+                        (_, None) => old.is_some(),
+                        (None, Some(_)) => false,
+                        (Some(old), Some(new)) => new > old,
+                    }),
+                (AmendmentChange::ScheduleAmounts(old), AmendmentChange::ScheduleAmounts(new)) => old.iter()
+                    .zip(new.iter())
+                    .any(|((_, old), (_, new))| new < old),
+                _ => true,
+            }
+        }
+
+        /// Helper function to apply a checked amendment and record it in the grant's amendment history
+        fn apply_amendment(&mut self,
+            amendment_id: u64,
+            address: AccountId,
+            change: AmendmentChange,
+            previous: AmendmentChange,
+            proposed_by: AccountId,
+            accepted_by: Option<AccountId>) {
+
+            let Some(vested_balance) = self.vested_balances.iter_mut().find(|v| v.address == address) else {
+                return;
+            };
+
+            match &change {
+                AmendmentChange::UnlockTimes(unlock_times) => {
+                    for (schedule_number, unlock_time) in unlock_times.iter() {
+                        if let Some(schedule) = vested_balance.vested_balance_schedules.iter_mut()
+                            .find(|s| s.schedule_number == *schedule_number) {
+                            schedule.unlock_time = *unlock_time;
+                        }
+                    }
+                }
+                AmendmentChange::ScheduleAmounts(amounts) => {
+                    for (schedule_number, amount) in amounts.iter() {
+                        if let Some(schedule) = vested_balance.vested_balance_schedules.iter_mut()
+                            .find(|s| s.schedule_number == *schedule_number) {
+                            let old = schedule.schedule_balance;
+                            schedule.schedule_balance = *amount;
+                            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Frozen), None, old);
+                            Self::track_status(&mut self.status_totals, None, Some(ScheduleStatus::Frozen), *amount);
+                            vested_balance.original_balance = vested_balance.original_balance.saturating_sub(old) + amount;
+                            vested_balance.frozen_balance = vested_balance.frozen_balance.saturating_sub(old) + amount;
+                            self.total_original_balance = self.total_original_balance.saturating_sub(old) + amount;
+                        }
+                    }
+                    Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+                }
+            }
+            Self::bump_version(vested_balance);
+
+            if let (AmendmentChange::UnlockTimes(old), AmendmentChange::UnlockTimes(new)) = (&previous, &change) {
+                self.env().emit_event(GrantRescheduled {
+                    address,
+                    changes: old.iter().zip(new.iter()).map(|((n, old), (_, new))| (*n, *old, *new)).collect(),
+                });
+            }
+
+            let mut amendments = self.amendment_history.get(address).unwrap_or_default();
+            amendments.push(AmendmentRecord {
+                amendment_id,
+                change,
+                previous,
+                proposed_by,
+                accepted_by,
+                applied_at: self.env().block_timestamp(),
+            });
+            self.amendment_history.insert(address, &amendments);
+        }
+
+        /// Helper function to record the time a beneficiary acted on its position
+        fn record_activity(&mut self, address: AccountId) {
+            self.last_activity.insert(address, &self.env().block_timestamp());
+        }
+
+        /// Helper function to move a position to a new address with its schedule history and compliance
+        /// profile. Positions with pending requests or disputes are not moved, KYC must be done again
+        fn migrate_position(&mut self,
+            from: AccountId,
+            to: AccountId) -> Result<(), Error> {
+
+            if self.vested_balances.iter().any(|v| v.address == to) {
+                return Err(Error::VestedBalanceAlreadyExist { address: to });
+            }
+
+            let vested_balance = self.vested_balances.iter_mut()
+                .find(|v| v.address == from)
+                .ok_or(Error::VestedBalanceNotFound { address: from })?;
+
+            if vested_balance.requested_balance > 0 || vested_balance.disputed_balance > 0 {
+                return Err(Error::VestedBalanceHasActivity {
+                    address: from,
+                    requested_balance: vested_balance.requested_balance,
+                    transferred_balance: vested_balance.transferred_balance,
+                });
+            }
+
+            vested_balance.address = to;
+            vested_balance.kyc_verified = false;
+            Self::bump_version(vested_balance);
+
+            let schedules: Vec<(u16, Hash)> = vested_balance.vested_balance_schedules.iter()
+                .map(|s| (s.schedule_number, s.schedule_id))
+                .collect();
+            let remaining_balance = Self::remaining_balance(vested_balance);
+
+            for (schedule_number, schedule_id) in schedules {
+                self.rekey_schedule(schedule_id, (from, schedule_number), (to, schedule_number));
+            }
+
+            self.holders_index.retain(|(a, _)| *a != from);
+            Self::track_holder(&mut self.holders_index, to, remaining_balance);
+
+            if let Some(profile) = self.compliance_profiles.take(from) {
+                self.compliance_profiles.insert(to, &profile);
+            }
+            if let Some(suspended_at) = self.suspensions.take(from) {
+                self.suspensions.insert(to, &suspended_at);
+            }
+            if let Some(escrow) = self.escrows.take(from) {
+                self.escrows.insert(to, &escrow);
+            }
+            if let Some(amendment_history) = self.amendment_history.take(from) {
+                self.amendment_history.insert(to, &amendment_history);
+            }
+            self.backups.remove(from);
+            self.last_activity.remove(from);
+            self.recovery_configs.remove(from);
+            self.recoveries.remove(from);
+            if let Some(delegate) = self.delegates.take(from).filter(|d| *d != to) {
+                self.delegates.insert(to, &delegate);
+            }
+
+            self.env().emit_event(PositionMigrated { from, to });
+
+            Ok(())
+        }
+
+        /// Helper function to create a vested balance with the given schedules and zero totals
+        fn new_balance(address: AccountId,
+            vested_balance_schedules: Vec<VestedBalanceSchedule>) -> VestedBalance {
+
+            VestedBalance {
+                address,
+                vested_balance_schedules,
+                original_balance: 0,
+                frozen_balance: 0,
+                liquid_balance: 0,
+                requested_balance: 0,
+                transferred_balance: 0,
+                disputed_balance: 0,
+                forfeited_balance: 0,
+                version: 0,
+                pin_recipient: false,
+                pinned_recipient: None,
+                kyc_verified: false,
+                notes: Vec::new(),
+            }
+        }
+
+        /// Helper function to split an original balance into the configured number of equal schedules
+        fn equal_schedules(&self,
+            original_balance: u128) -> Vec<VestedBalanceSchedule> {
+
+            let schedule_balance = original_balance / self.total_vested_schedule as u128;
+
+            (1..=self.total_vested_schedule)
+                .map(|i| Self::new_schedule(i, schedule_balance, self.unlock_times.get(i)))
+                .collect()
+        }
+
+        /// Helper function to thaw a single frozen schedule
+        fn thaw_schedule(&mut self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            if self.suspensions.contains(address) {
+                return Err(Error::VestingSuspended { address });
+            }
+
+            let prices = self.price_observations.get_or_default();
+            let now = self.env().block_timestamp();
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            // Ensure the schedule is frozen
+            if schedule.status != ScheduleStatus::Frozen {
+                return Err(Error::VestedBalanceScheduleNotFrozen {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            if !Self::price_gate_open(&self.price_gates, &prices, now, (address, schedule_number)) {
+                return Err(Error::PriceGateClosed { address, schedule_number });
+            }
+
+            let amount = schedule.schedule_balance;
+            schedule.status = ScheduleStatus::Liquid;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Frozen), Some(ScheduleStatus::Liquid), amount);
+            Self::move_balance(vested_balance, ScheduleStatus::Frozen, ScheduleStatus::Liquid, amount);
+            Self::record_history(&mut self.schedule_history, (address, schedule_number), Self::status_entry(ScheduleStatus::Frozen, ScheduleStatus::Liquid, Self::env().caller()));
+
+            Ok(())
+        }
+
+        /// Helper function to thaw the frozen schedules of a schedule number. Returns the result of each frozen
+        /// schedule, indexed by the position of its vested balance
+        fn thaw_schedule_number(&mut self,
+            schedule_number: u16) -> BatchResult {
+
+            // Iterate all vested frozen balances on a given schedule number and thaw 
+            let caller = self.env().caller();
+            let prices = self.price_observations.get_or_default();
+            let now = self.env().block_timestamp();
+            let mut results = Vec::new();
+            for (index, vested_balance) in self.vested_balances.iter_mut().enumerate() {
+                let address = vested_balance.address;
+                let Some(schedule) = vested_balance.vested_balance_schedules.iter_mut()
+                    .find(|s| s.schedule_number == schedule_number && s.status == ScheduleStatus::Frozen) else {
+                    continue;
+                };
+
+                // Suspended balances stay frozen, price-gated schedules wait for their price
+                let result = if self.suspensions.contains(address) {
+                    Err(Error::VestingSuspended { address })
+                } else if !Self::price_gate_open(&self.price_gates, &prices, now, (address, schedule_number)) {
+                    Err(Error::PriceGateClosed { address, schedule_number })
+                } else {
+                    let amount = schedule.schedule_balance;
+                    schedule.status = ScheduleStatus::Liquid;
+                    Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Frozen), Some(ScheduleStatus::Liquid), amount);
+                    Self::record_history(&mut self.schedule_history, (address, schedule_number), Self::status_entry(ScheduleStatus::Frozen, ScheduleStatus::Liquid, caller));
+                    Self::move_balance(vested_balance, ScheduleStatus::Frozen, ScheduleStatus::Liquid, amount);
+                    Ok(Success::VestedBalanceScheduleThawed)
+                };
+
+                results.push(BatchItemResult { index: index as u32, result });
+            }
+
+            results
+        }
+
+        /// Helper function to get the transfer fee of an amount
+        fn fee_of(&self, amount: u128) -> u128 {
+            Self::mul_div(amount, self.fee_bps as u128, 10_000)
+        }
+
+        /// Helper function to check the caller may read the bulk views, anyone unless private
+        fn can_view_all(&self) -> bool {
+            !self.private || self.is_viewer(self.env().caller())
+        }
+
+        /// Helper function to check the caller may read the views of an address, the address itself or
+        /// those who may read the bulk views
+        fn can_view(&self, address: AccountId) -> bool {
+            self.env().caller() == address || self.can_view_all()
+        }
+
+        /// Helper function to prepare a state chunk for the views
+        fn chunk_view(&self, chunk: &[VestedBalance]) -> Vec<VestedBalance> {
+            chunk.iter().cloned().map(|v| self.to_view(v)).collect()
+        }
+
+        /// Helper function to prepare a vested balance for the views: fills in the particulars of its schedules
+        /// and hides the owner notes from callers other than the owner and the viewers
+        fn to_view(&self, mut vested_balance: VestedBalance) -> VestedBalance {
+            for schedule in vested_balance.vested_balance_schedules.iter_mut() {
+                if let Some(particulars) = self.schedule_particulars.get(schedule.schedule_id) {
+                    schedule.particulars = particulars;
+                }
+            }
+            if !self.is_viewer(self.env().caller()) {
+                vested_balance.notes.clear();
+            }
+            vested_balance
+        }
+
+        /// Helper function to get the voting weight of a vested balance, its locked frozen and liquid balance
+        fn voting_weight(vested_balance: &VestedBalance) -> u128 {
+            vested_balance.frozen_balance + vested_balance.liquid_balance
+        }
+
+        /// Helper function to get the account a recipient is paid to, EVM addresses map to the blake2x256
+        /// hash of "evm:" and the address. XCM destinations keep their foreign account for display only
+        fn recipient_account(&self, recipient: Recipient) -> AccountId {
+            match recipient {
+                Recipient::Account(account) => account,
+                Recipient::Xcm(destination) => AccountId::from(destination.account),
+                Recipient::EthAddress(eth_address) => {
+                    AccountId::from(self.env().hash_bytes::<Blake2x256>(&[&b"evm:"[..], &eth_address[..]].concat()))
+                }
+            }
+        }
+
+        /// Helper function to get the recipient of a schedule, none without a recipient
+        fn recipient_of(&self,
+            address: AccountId,
+            schedule_number: u16) -> Option<Recipient> {
+
+            let recipient_address = self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .and_then(|v| v.vested_balance_schedules.iter().find(|s| s.schedule_number == schedule_number))
+                .and_then(|s| s.recipient_address)?;
+
+            if let Some(destination) = self.xcm_destinations.get((address, schedule_number)) {
+                return Some(Recipient::Xcm(destination));
+            }
+            Some(match self.eth_recipients.get((address, schedule_number)) {
+                Some(eth_address) => Recipient::EthAddress(eth_address),
+                None => Recipient::Account(recipient_address),
+            })
+        }
+
+        /// Helper function to change the delegate of a beneficiary, announcing the change
+        fn set_delegate(&mut self,
+            delegator: AccountId,
+            to_delegate: Option<AccountId>) -> Result<(), Error> {
+
+            if !self.vested_balances.iter().any(|v| v.address == delegator) {
+                return Err(Error::VestedBalanceNotFound { address: delegator });
+            }
+
+            let from_delegate = self.delegates.get(delegator);
+            match to_delegate {
+                Some(to_delegate) => {
+                    self.delegates.insert(delegator, &to_delegate);
+                }
+                None => self.delegates.remove(delegator),
+            }
+            self.record_activity(delegator);
+
+            self.env().emit_event(DelegateChanged { delegator, from_delegate, to_delegate });
+
+            Ok(())
+        }
+
+        /// Helper function to set the unlock time of a schedule number, only frozen schedules are affected,
+        /// liquid ones are already unlocked
+        fn apply_unlock_time(&mut self,
+            schedule_number: u16,
+            unlock_time: Timestamp) {
+
+            self.unlock_times.insert(schedule_number, &unlock_time);
+
+            for vested_balance in self.vested_balances.iter_mut() {
+                let mut changed = false;
+                for schedule in vested_balance.vested_balance_schedules.iter_mut() {
+                    if schedule.schedule_number == schedule_number && schedule.status == ScheduleStatus::Frozen {
+                        schedule.unlock_time = Some(unlock_time);
+                        changed = true;
+                    }
+                }
+                if changed {
+                    Self::bump_version(vested_balance);
+                }
+            }
+        }
+
+        /// Helper function to tell if a message holding the reentrancy lock is executing
+        fn is_locked(&self) -> bool {
+            self.reentrancy_lock.get().unwrap_or(false)
+        }
+
+        /// Helper function to take or release the reentrancy lock, written to its storage cell right away
+        fn set_locked(&mut self, locked: bool) {
+            self.reentrancy_lock.set(&locked);
+        }
+
+        /// Helper function to reject owner thaws while a governor controls the unlocks
+        fn check_not_governed(&self) -> Result<(), Error> {
+            match self.governor {
+                Some(governor) => Err(Error::GovernorRequired { governor }),
+                None => Ok(()),
+            }
+        }
+
+        /// Helper function to reject a call, reverting it when it carries a transferred value
+        fn refuse_funded(&mut self,
+            operator: AccountId,
+            error: Error) -> Result<(), Error> {
+
+            if self.env().transferred_value() > 0 {
+                return Err(error);
+            }
+
+            self.fail(operator, error)
+        }
+
+        /// Helper function to emit a vesting event with the error code of its status
+        fn emit_status(&mut self,
+            operator: AccountId,
+            status: VestingStatus) {
+
+            // Successful operations bump the state version, except the no-ops
+            let error_code = match &status {
+                VestingStatus::EmitSuccess(Success::OperationAlreadyApplied | Success::AlreadyTransferred) => 0,
+                VestingStatus::EmitSuccess(_) => {
+                    self.state_version = self.state_version.wrapping_add(1);
+                    0
+                }
+                VestingStatus::EmitError(error) => error.error_code(),
+            };
+
+            self.env().emit_event(VestingEvent {
+                operator,
+                status,
+                error_code,
+            });
+        }
+
+        /// Helper function to end a message on an error. The error is emitted and the message returns Ok, in
+        /// strict mode the message returns the error instead, which reverts its state changes. Failed payouts
+        /// are kept in the retry queue either way
+        fn fail(&mut self,
+            operator: AccountId,
+            error: Error) -> Result<(), Error> {
+
+            if self.strict_mode && !error.is_payout_failure() {
+                return Err(error);
+            }
+
+            self.emit_status(operator, VestingStatus::EmitError(error));
+
+            Ok(())
+        }
+
+        /// Helper function to end a message on its outcome, see `fail`
+        fn finish(&mut self,
+            operator: AccountId,
+            result: Result<(), Error>,
+            success: Success) -> Result<(), Error> {
+
+            match result {
+                Ok(()) => {
+                    self.emit_status(operator, VestingStatus::EmitSuccess(success));
+                    Ok(())
+                }
+                Err(error) => self.fail(operator, error),
+            }
+        }
+
+        /// Helper function to end a batch message, in strict mode the first bad item reverts the whole batch
+        fn finish_batch(&self,
+            results: BatchResult) -> Result<BatchResult, Error> {
+
+            match results.iter().find_map(|item| item.result.clone().err()) {
+                Some(error) if self.strict_mode && !error.is_payout_failure() => Err(error),
+                _ => Ok(results),
+            }
+        }
+
+        /// Helper function to emit the outcome of an operation
+        fn emit_result(&mut self,
+            operator: AccountId,
+            result: Result<(), Error>,
+            success: Success) {
+
+            let status = match result {
+                Ok(()) => VestingStatus::EmitSuccess(success),
+                Err(error) => VestingStatus::EmitError(error),
+            };
+
+            self.emit_status(operator, status);
+        }
+
+        /// Helper function to find a vested balance and the position of one of its schedules
+        fn find_schedule_mut(vested_balances: &mut [VestedBalance],
+            address: AccountId,
+            schedule_number: u16) -> Result<(&mut VestedBalance, usize), Error> {
+
+            let vested_balance = vested_balances.iter_mut()
+                .find(|v| v.address == address)
+                .ok_or(Error::VestedBalanceNotFound { address })?;
+
+            let index = vested_balance.vested_balance_schedules.iter()
+                .position(|s| s.schedule_number == schedule_number)
+                .ok_or(Error::VestedBalanceScheduleNotFound { address, schedule_number })?;
+
+            Ok((vested_balance, index))
+        }
+
+        /// Helper function to number a new schedule after the last one of a position, up to the maximum
+        fn next_schedule_number(&self,
+            address: AccountId) -> Result<u16, Error> {
+
+            let vested_balance = self.vested_balances.iter()
+                .find(|v| v.address == address)
+                .ok_or(Error::VestedBalanceNotFound { address })?;
+
+            vested_balance.vested_balance_schedules.iter()
+                .map(|s| s.schedule_number)
+                .max()
+                .unwrap_or(0)
+                .checked_add(1)
+                .filter(|schedule_number| *schedule_number <= self.max_schedule_number)
+                .ok_or(Error::ScheduleLimitReached { address })
+        }
+
+        /// Helper function to move a liquid schedule to another vested balance, returns its new schedule number
+        fn move_schedule(&mut self,
+            from: AccountId,
+            schedule_number: u16,
+            to: AccountId) -> Result<u16, Error> {
+
+            self.check_pledge(from, schedule_number)?;
+
+            // Number the schedule after the last one of the receiving position
+            let new_schedule_number = self.next_schedule_number(to)?;
+
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, from, schedule_number)?;
+            let status = vested_balance.vested_balance_schedules[index].status;
+            if status != ScheduleStatus::Liquid {
+                return Err(Error::VestedBalanceScheduleNotLiquid {
+                    address: from,
+                    schedule_number,
+                    status,
+                });
+            }
+
+            let mut schedule = vested_balance.vested_balance_schedules.remove(index);
+            vested_balance.original_balance = vested_balance.original_balance.saturating_sub(schedule.schedule_balance);
+            vested_balance.liquid_balance = vested_balance.liquid_balance.saturating_sub(schedule.schedule_balance);
+            Self::bump_version(vested_balance);
+            Self::track_holder(&mut self.holders_index, from, Self::remaining_balance(vested_balance));
+
+            let receiver = self.vested_balances.iter_mut()
+                .find(|v| v.address == to)
+                .ok_or(Error::VestedBalanceNotFound { address: to })?;
+            let schedule_id = schedule.schedule_id;
+            schedule.schedule_number = new_schedule_number;
+            receiver.original_balance += schedule.schedule_balance;
+            receiver.liquid_balance += schedule.schedule_balance;
+            receiver.vested_balance_schedules.push(schedule);
+            Self::bump_version(receiver);
+            Self::track_holder(&mut self.holders_index, to, Self::remaining_balance(receiver));
+
+            // The particulars are kept by schedule id and stay where they are
+            self.rekey_schedule(schedule_id, (from, schedule_number), (to, new_schedule_number));
+
+            Ok(new_schedule_number)
+        }
+
+        /// Helper function to move the storage kept per schedule key (history, approvals, price gate, pledge and
+        /// recipient details) and the indexed tx-hash of a schedule to its new key
+        fn rekey_schedule(&mut self,
+            schedule_id: Hash,
+            from: ScheduleKey,
+            to: ScheduleKey) {
+
+            if let Some(history) = self.schedule_history.take(from) {
+                self.schedule_history.insert(to, &history);
+            }
+            if let Some(approvers) = self.approvals.take(from) {
+                self.approvals.insert(to, &approvers);
+            }
+            if let Some(revert) = self.approval_reverts.take(from) {
+                self.approval_reverts.insert(to, &revert);
+            }
+            if let Some(price_gate) = self.price_gates.take(from) {
+                self.price_gates.insert(to, &price_gate);
+            }
+            if let Some(lender) = self.pledges.take(from) {
+                self.pledges.insert(to, &lender);
+            }
+            if let Some(destination) = self.xcm_destinations.take(from) {
+                self.xcm_destinations.insert(to, &destination);
+            }
+            if let Some(eth_address) = self.eth_recipients.take(from) {
+                self.eth_recipients.insert(to, &eth_address);
+            }
+            if let Some(commitment) = self.recipient_commitments.take(from) {
+                self.recipient_commitments.insert(to, &commitment);
+            }
+            if let Some(tx_hash) = self.schedule_particulars.get(schedule_id) {
+                Self::reindex_tx_hash(&mut self.tx_hashes, &tx_hash, from, Some(to));
+            }
+        }
+
+        /// Helper function to move a frozen or liquid schedule into the forfeited status
+        fn forfeit_schedule(&mut self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            self.check_pledge(address, schedule_number)?;
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            if !matches!(schedule.status, ScheduleStatus::Frozen | ScheduleStatus::Liquid) {
+                return Err(Error::VestedBalanceScheduleNotForfeitable {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            let (previous_status, amount) = (schedule.status, schedule.schedule_balance);
+            Self::track_status(&mut self.status_totals, Some(previous_status), Some(ScheduleStatus::Forfeited), amount);
+            schedule.status = ScheduleStatus::Forfeited;
+
+            Self::move_balance(vested_balance, previous_status, ScheduleStatus::Forfeited, amount);
+            Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+
+            Ok(())
+        }
+
+        /// Helper function to move a schedule into the disputed status
+        fn mark_disputed(&mut self,
+            address: AccountId,
+            schedule_number: u16) -> Result<(), Error> {
+
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            if !matches!(schedule.status, ScheduleStatus::Requested | ScheduleStatus::Approved) {
+                return Err(Error::VestedBalanceScheduleNotDisputable {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            let (previous_status, amount) = (schedule.status, schedule.schedule_balance);
+            self.disputes.insert((address, schedule_number), &previous_status);
+            Self::track_status(&mut self.status_totals, Some(previous_status), Some(ScheduleStatus::Disputed), amount);
+            schedule.status = ScheduleStatus::Disputed;
+
+            Self::move_balance(vested_balance, previous_status, ScheduleStatus::Disputed, amount);
+            Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+
+            // The payout bots must not settle a disputed schedule, an upheld approval is instructed again
+            if previous_status == ScheduleStatus::Approved {
+                self.remove_payout(address, schedule_number);
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to apply the arbiter decision on a disputed schedule, recorded in the schedule history
+        /// with the encoded outcome as reason. Returns the new status and the schedule number returned by a split
+        fn apply_dispute_outcome(&mut self,
+            arbiter: AccountId,
+            address: AccountId,
+            schedule_number: u16,
+            outcome: DisputeOutcome) -> Result<(ScheduleStatus, Option<u16>), Error> {
+
+            let block_number = self.env().block_number();
+
+            // Number a split schedule after the last one of the position
+            let returned_schedule_number = match outcome {
+                DisputeOutcome::Split { .. } => Some(self.next_schedule_number(address)?),
+                _ => None,
+            };
+
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            if schedule.status != ScheduleStatus::Disputed {
+                return Err(Error::VestedBalanceScheduleNotDisputed {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            let returned = match outcome {
+                DisputeOutcome::Split { returned } if returned == 0 || returned >= schedule.schedule_balance => {
+                    return Err(Error::InvalidDisputeSplit {
+                        address,
+                        schedule_number,
+                        amount: schedule.schedule_balance,
+                    });
+                }
+                DisputeOutcome::Split { returned } => returned,
+                _ => 0,
+            };
+
+            // Paid schedules are never returned to the beneficiary, in whole or in part
+            let previous_status = self.disputes.get((address, schedule_number)).unwrap_or(ScheduleStatus::Requested);
+            if previous_status == ScheduleStatus::Transferred && outcome != DisputeOutcome::Uphold {
+                return Err(Error::DisputeOutcomeNotAllowed {
+                    address,
+                    schedule_number,
+                    status: previous_status,
+                });
+            }
+            self.disputes.remove((address, schedule_number));
+
+            let entry = ScheduleHistoryEntry {
+                previous_status: ScheduleStatus::Disputed,
+                status: previous_status,
+                particulars: self.schedule_particulars.get(schedule.schedule_id).unwrap_or_default(),
+                reason: outcome.encode(),
+                operator: arbiter,
+                block_number,
+                timestamp: Self::env().block_timestamp(),
+            };
+            let status = match outcome {
+                DisputeOutcome::Uphold | DisputeOutcome::Split { .. } => previous_status,
+                DisputeOutcome::Reverse => {
+                    if let Some(request_id) = schedule.request_id.take() {
+                        self.requests.remove(request_id);
+                    }
+                    schedule.recipient_address = None;
+                    schedule.requested_at = None;
+                    schedule.memo = Vec::new();
+                    let particulars = self.schedule_particulars.take(schedule.schedule_id).unwrap_or_default();
+                    Self::reindex_tx_hash(&mut self.tx_hashes, &particulars, (address, schedule_number), None);
+                    ScheduleStatus::Liquid
+                }
+            };
+
+            let amount = schedule.schedule_balance - returned;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Disputed), Some(status), amount);
+            schedule.status = status;
+            schedule.schedule_balance = amount;
+            let schedule_id = schedule.schedule_id;
+            let recipient = schedule.recipient_address.unwrap_or(address);
+
+            // A split request is kept for the upheld part
+            if let Some(request_id) = schedule.request_id.filter(|_| status == ScheduleStatus::Requested) {
+                if let Some(mut request) = self.requests.get(request_id) {
+                    request.amount = amount;
+                    self.requests.insert(request_id, &request);
+                }
+            }
+
+            Self::move_balance(vested_balance, ScheduleStatus::Disputed, status, amount);
+
+            // The returned part becomes a new liquid schedule of the beneficiary
+            if let Some(returned_schedule_number) = returned_schedule_number {
+                let mut returned_schedule = Self::new_schedule(returned_schedule_number, returned, None);
+                returned_schedule.schedule_id = Self::schedule_id(&mut self.schedule_id_counter, address, returned_schedule_number);
+                returned_schedule.status = ScheduleStatus::Liquid;
+                vested_balance.vested_balance_schedules.push(returned_schedule);
+
+                Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Disputed), Some(ScheduleStatus::Liquid), returned);
+                Self::move_balance(vested_balance, ScheduleStatus::Disputed, ScheduleStatus::Liquid, returned);
+                Self::record_history(&mut self.schedule_history, (address, returned_schedule_number), ScheduleHistoryEntry {
+                    previous_status: ScheduleStatus::Disputed,
+                    status: ScheduleStatus::Liquid,
+                    particulars: Vec::new(),
+                    reason: outcome.encode(),
+                    operator: arbiter,
+                    block_number,
+                    timestamp: Self::env().block_timestamp(),
+                });
+            }
+            Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+
+            Self::record_history(&mut self.schedule_history, (address, schedule_number), ScheduleHistoryEntry {
+                status,
+                ..entry
+            });
+
+            // The payout withdrawn by the dispute is instructed again for the upheld amount
+            if status == ScheduleStatus::Approved {
+                self.instruct_payout(address, schedule_number, schedule_id, recipient, amount);
+            }
+
+            Ok((status, returned_schedule_number))
+        }
+
+        /// Helper function to move an approved or transferred schedule back to the revert target status
+        fn apply_approval_revert(&mut self,
+            operator: AccountId,
+            address: AccountId,
+            schedule_number: u16,
+            revert: ApprovalRevert) -> Result<(), Error> {
+
+            let block_number = self.env().block_number();
+            let receipt = self.last_receipt(address, schedule_number);
+            let paid_by_contract = self.asset_kind == AssetKind::Native
+                || matches!(receipt.as_ref().map(|r| r.recipient), Some(Recipient::Xcm(_)));
+            let (vested_balance, index) = Self::find_schedule_mut(&mut self.vested_balances, address, schedule_number)?;
+            let schedule = &mut vested_balance.vested_balance_schedules[index];
+
+            if !matches!(schedule.status, ScheduleStatus::Transferred | ScheduleStatus::Approved) {
+                return Err(Error::VestedBalanceScheduleNotApprovedOrTransferred {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            // Only payouts settled off-chain can be reverted, the contract cannot take back what it paid itself
+            if schedule.status == ScheduleStatus::Transferred && paid_by_contract {
+                return Err(Error::PayoutNotRevertible { address, schedule_number });
+            }
+
+            // The tx-hash of the reverted payout no longer settles the schedule
+            let particulars = self.schedule_particulars.take(schedule.schedule_id).unwrap_or_default();
+            Self::reindex_tx_hash(&mut self.tx_hashes, &particulars, (address, schedule_number), None);
+
+            let entry = ScheduleHistoryEntry {
+                previous_status: schedule.status,
+                status: revert.to_status,
+                particulars,
+                reason: revert.reason,
+                operator,
+                block_number,
+                timestamp: Self::env().block_timestamp(),
+            };
+
+            let amount = schedule.schedule_balance;
+            Self::track_status(&mut self.status_totals, Some(entry.previous_status), Some(revert.to_status), amount);
+            schedule.status = revert.to_status;
+            if revert.to_status == ScheduleStatus::Liquid {
+                schedule.recipient_address = None;
+            }
+
+            Self::move_balance(vested_balance, entry.previous_status, revert.to_status, amount);
+            Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+
+            // The receipt of a reverted transfer is void
+            if entry.previous_status == ScheduleStatus::Approved {
+                self.remove_payout(address, schedule_number);
+            } else if let Some(receipt) = receipt {
+                self.void_receipt(address, receipt.receipt_id);
+            }
+            Self::record_history(&mut self.schedule_history, (address, schedule_number), entry);
+
+            Ok(())
+        }
+
+        /// Helper function to reject a tx-hash already recorded for another schedule
+        fn check_tx_hash(&self,
+            address: AccountId,
+            schedule_number: u16,
+            tx_hash: &[u8]) -> Result<(), Error> {
+
+            match self.tx_hashes.get(tx_hash) {
+                Some(key) if key != (address, schedule_number) => Err(Error::DuplicateTxHash {
+                    address: key.0,
+                    schedule_number: key.1,
+                }),
+                _ => Ok(()),
+            }
+        }
+
+        /// Helper function to index the tx-hash that settled a schedule, empty hashes are not indexed
+        fn record_tx_hash(&mut self,
+            address: AccountId,
+            schedule_number: u16,
+            tx_hash: &[u8]) {
+
+            if !tx_hash.is_empty() {
+                self.tx_hashes.insert(tx_hash, &(address, schedule_number));
+            }
+        }
+
+        /// Helper function to point the indexed tx-hash of a schedule at its new key, or to drop it without one.
+        /// Hashes indexed for another schedule are left alone
+        fn reindex_tx_hash<K: ink::storage::traits::StorageKey>(tx_hashes: &mut Mapping<Vec<u8>, ScheduleKey, K>,
+            tx_hash: &[u8],
+            from: ScheduleKey,
+            to: Option<ScheduleKey>) {
+
+            if tx_hash.is_empty() || tx_hashes.get(tx_hash) != Some(from) {
+                return;
+            }
+            match to {
+                Some(key) => { tx_hashes.insert(tx_hash, &key); }
+                None => tx_hashes.remove(tx_hash),
+            }
+        }
+
+        /// Helper function to build the history entry of a status change, without particulars or reason
+        fn status_entry(previous_status: ScheduleStatus,
+            status: ScheduleStatus,
+            operator: AccountId) -> ScheduleHistoryEntry {
+
+            ScheduleHistoryEntry {
+                previous_status,
+                status,
+                particulars: Vec::new(),
+                reason: Vec::new(),
+                operator,
+                block_number: Self::env().block_number(),
+                timestamp: Self::env().block_timestamp(),
+            }
+        }
+
+        /// Helper function to append an entry to the history of a schedule
+        fn record_history<K: ink::storage::traits::StorageKey>(schedule_history: &mut Mapping<ScheduleKey, Vec<ScheduleHistoryEntry>, K>,
+            key: ScheduleKey,
+            entry: ScheduleHistoryEntry) {
+
+            let mut history = schedule_history.get(key).unwrap_or_default();
+            history.push(entry);
+            schedule_history.insert(key, &history);
+        }
+
+        /// Helper function to record the settlement receipt of a transferred schedule
+        fn record_receipt(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            amount: u128,
+            tx_hash: Vec<u8>) {
+
+            let receipt_id = self.next_receipt_id;
+            self.next_receipt_id += 1;
+
+            let recipient = self.recipient_of(beneficiary, schedule_number)
+                .unwrap_or(Recipient::Account(beneficiary));
+            self.receipts.insert(receipt_id, &Receipt {
+                receipt_id,
+                beneficiary,
+                schedule_number,
+                recipient,
+                amount,
+                asset_id: self.asset_id,
+                tx_hash,
+                block: self.env().block_number(),
+                settled_at: self.env().block_timestamp(),
+            });
+
+            let mut receipt_ids = self.beneficiary_receipts.get(beneficiary).unwrap_or_default();
+            receipt_ids.push(receipt_id);
+            self.beneficiary_receipts.insert(beneficiary, &receipt_ids);
+        }
+
+        /// Helper function to get the latest settlement receipt of a schedule
+        fn last_receipt(&self,
+            beneficiary: AccountId,
+            schedule_number: u16) -> Option<Receipt> {
+            self.beneficiary_receipts
+                .get(beneficiary)
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+                .filter_map(|receipt_id| self.receipts.get(receipt_id))
+                .find(|receipt| receipt.schedule_number == schedule_number)
+        }
+
+        /// Helper function to remove the settlement receipt of a reverted transfer
+        fn void_receipt(&mut self,
+            beneficiary: AccountId,
+            receipt_id: u64) {
+            self.receipts.remove(receipt_id);
+            let mut receipt_ids = self.beneficiary_receipts.get(beneficiary).unwrap_or_default();
+            receipt_ids.retain(|id| *id != receipt_id);
+            self.beneficiary_receipts.insert(beneficiary, &receipt_ids);
+        }
+
+        /// Helper function to mark an approved payout as transferred
+        fn settle_payout(&mut self,
+            payout_id: u64,
+            tx_hash: Vec<u8>) -> Result<(), Error> {
+
+            let (address, schedule_number) = self.payouts.get(payout_id)
+                .ok_or(Error::PayoutNotFound { payout_id })?;
+            self.check_tx_hash(address, schedule_number, &tx_hash)?;
+
+            let vested_balance = self.vested_balances.iter_mut()
+                .find(|v| v.address == address)
+                .ok_or(Error::VestedBalanceNotFound { address })?;
+
+            let schedule = vested_balance.vested_balance_schedules.iter_mut()
+                .find(|s| s.schedule_number == schedule_number)
+                .ok_or(Error::VestedBalanceScheduleNotFound { address, schedule_number })?;
+
+            if schedule.status != ScheduleStatus::Approved {
+                return Err(Error::VestedBalanceScheduleNotApproved {
+                    address,
+                    schedule_number,
+                    status: schedule.status,
+                });
+            }
+
+            // Update the schedule
+            schedule.status = ScheduleStatus::Transferred;
+            Self::track_status(&mut self.status_totals, Some(ScheduleStatus::Approved), Some(ScheduleStatus::Transferred), schedule.schedule_balance);
+            self.schedule_particulars.insert(schedule.schedule_id, &tx_hash);
+            let amount = schedule.schedule_balance;
+            let recipient = schedule.recipient_address.unwrap_or(address);
+
+            // Update balances
+            Self::move_balance(vested_balance, ScheduleStatus::Approved, ScheduleStatus::Transferred, amount);
+            Self::pin_recipient(vested_balance, recipient);
+            Self::track_holder(&mut self.holders_index, address, Self::remaining_balance(vested_balance));
+
+            self.payouts.remove(payout_id);
+            self.payout_ids.remove((address, schedule_number));
+            self.record_tx_hash(address, schedule_number, &tx_hash);
+            self.record_receipt(address, schedule_number, amount, tx_hash);
+
+            let operator = self.env().caller();
+            self.notify_recipient(operator, Recipient::Account(recipient), address, amount);
+
+            Ok(())
+        }
+
+        /// Helper function to register the payout of an approved schedule and instruct the payout bots,
+        /// returns the payout id
+        fn instruct_payout(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            schedule_id: Hash,
+            recipient: AccountId,
+            amount: u128) -> u64 {
+
+            let payout_id = self.next_payout_id;
+            self.next_payout_id += 1;
+            self.payouts.insert(payout_id, &(beneficiary, schedule_number));
+            self.payout_ids.insert((beneficiary, schedule_number), &payout_id);
+
+            let recipient = self.recipient_of(beneficiary, schedule_number).unwrap_or(Recipient::Account(recipient));
+            self.env().emit_event(PayoutInstruction {
+                payout_id,
+                asset_id: self.asset_id,
+                amount,
+                recipient,
+                beneficiary,
+                schedule_id,
+            });
+
+            payout_id
+        }
+
+        /// Helper function to withdraw the pending payout of an approved schedule, it can no longer be acknowledged
+        fn remove_payout(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16) {
+
+            if let Some(payout_id) = self.payout_ids.take((beneficiary, schedule_number)) {
+                self.payouts.remove(payout_id);
+            }
+        }
+
+        /// Helper function to move a schedule amount from one status total to another
+        fn track_status(status_totals: &mut Vec<(u32, u128)>,
+            from: Option<ScheduleStatus>,
+            to: Option<ScheduleStatus>,
+            amount: u128) {
+
+            if let Some(from) = from {
+                if let Some(total) = status_totals.get_mut(from as usize) {
+                    total.0 = total.0.saturating_sub(1);
+                    total.1 = total.1.saturating_sub(amount);
+                }
+            }
+
+            if let Some(to) = to {
+                if status_totals.len() <= to as usize {
+                    status_totals.resize(to as usize + 1, (0, 0));
+                }
+                status_totals[to as usize].0 += 1;
+                status_totals[to as usize].1 += amount;
+            }
+        }
+
+        /// Helper function to transfer native balance to a recipient out of the native escrow, through
+        /// pallet-vesting when it enforces the lock
+        fn pay_native(native_pool: &mut Balance,
+            enforcement_backend: EnforcementBackend,
+            recipient: AccountId,
+            amount: Balance) -> Result<(), Error> {
+
+            if *native_pool < amount {
+                return Err(Error::InsufficientNativeEscrow {
+                    available: *native_pool,
+                    required: amount,
+                });
+            }
+
+            match enforcement_backend {
+                EnforcementBackend::Contract => {
+                    Self::env().transfer(recipient, amount)
+                        .map_err(|_| Error::NativeTransferFailed { recipient })?;
+                }
+                EnforcementBackend::PalletVesting { unlock_blocks, start_delay } => {
+                    let per_block = (amount / unlock_blocks as u128).max(1);
+                    let starting_block = Self::env().block_number().saturating_add(start_delay);
+                    Self::env().extension()
+                        .vested_transfer(recipient, amount, per_block, starting_block)
+                        .map_err(|error| Error::PalletVestingFailed { recipient, error })?;
+                }
+            }
+            *native_pool -= amount;
+
+            Ok(())
+        }
+
+        /// Helper function to reserve transfer the asset, or the native balance out of the native escrow when
+        /// none, to a sibling parachain. Returns the hash of the XCM message
+        fn send_xcm(native_pool: &mut Balance,
+            asset_id: Option<u128>,
+            destination: XcmDestination,
+            amount: Balance) -> Result<[u8; 32], Error> {
+
+            if asset_id.is_none() && *native_pool < amount {
+                return Err(Error::InsufficientNativeEscrow {
+                    available: *native_pool,
+                    required: amount,
+                });
+            }
+
+            let message_hash = Self::env().extension()
+                .reserve_transfer(asset_id, amount, destination)
+                .map_err(|error| Error::XcmTransferFailed { para_id: destination.para_id, error })?;
+            if asset_id.is_none() {
+                *native_pool -= amount;
+            }
+
+            Ok(message_hash)
+        }
+
+        /// Helper function to queue and report a failed outbound payout, the schedule is left requested.
+        /// A schedule failing again keeps its payout id and counts the attempt
+        fn record_failed_payout(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16,
+            schedule_id: Hash,
+            recipient: AccountId,
+            amount: u128,
+            reason: Error) {
+
+            let block_number = self.env().block_number();
+            let mut failed_payouts = self.failed_payouts.get_or_default();
+            let payout_id = match failed_payouts.iter_mut()
+                .find(|p| p.beneficiary == beneficiary && p.schedule_number == schedule_number) {
+                Some(failed_payout) => {
+                    failed_payout.recipient = recipient;
+                    failed_payout.amount = amount;
+                    failed_payout.attempts += 1;
+                    failed_payout.last_error_code = reason.error_code();
+                    failed_payout.last_attempt_at = block_number;
+                    failed_payout.payout_id
+                }
+                None => {
+                    let payout_id = self.next_payout_id;
+                    self.next_payout_id += 1;
+                    failed_payouts.push(FailedPayout {
+                        payout_id,
+                        beneficiary,
+                        schedule_number,
+                        recipient,
+                        amount,
+                        attempts: 1,
+                        last_error_code: reason.error_code(),
+                        last_attempt_at: block_number,
+                    });
+                    payout_id
+                }
+            };
+            self.failed_payouts.set(&failed_payouts);
+
+            self.env().emit_event(PayoutFailed {
+                payout_id,
+                beneficiary,
+                schedule_number,
+                schedule_id,
+                recipient,
+                amount,
+                reason,
+            });
+        }
+
+        /// Helper function to drop a schedule from the retry queue once it is paid out
+        fn clear_failed_payout(&mut self,
+            beneficiary: AccountId,
+            schedule_number: u16) {
+
+            let mut failed_payouts = self.failed_payouts.get_or_default();
+            let count = failed_payouts.len();
+            failed_payouts.retain(|p| p.beneficiary != beneficiary || p.schedule_number != schedule_number);
+            if failed_payouts.len() != count {
+                self.failed_payouts.set(&failed_payouts);
+            }
+        }
+
+        /// Helper function to pay the keeper bounty for the processed items out of the keeper pool
+        fn pay_keeper_bounty(&mut self, keeper: AccountId, items: u32) {
+            let bounty = self.keeper_bounty
+                .saturating_mul(items as Balance)
+                .min(self.keeper_pool);
+
+            if bounty == 0 {
+                return;
+            }
+
+            // A failed payment must not revert the keeper work
+            if self.env().transfer(keeper, bounty).is_ok() {
+                self.keeper_pool -= bounty;
+
+                self.emit_status(keeper, VestingStatus::EmitSuccess(Success::KeeperBountyPaid));
+            }
+        }
+
+        /// Helper function to re-position a holder in the holders index
+        fn track_holder(holders_index: &mut Vec<(AccountId, u128)>,
+            address: AccountId,
+            remaining_balance: u128) {
+
+            holders_index.retain(|(a, _)| *a != address);
+            let position = holders_index.partition_point(|(_, b)| *b >= remaining_balance);
+            holders_index.insert(position, (address, remaining_balance));
+        }
+
+        /// Helper function to get the remaining (non-transferred) balance
+        fn remaining_balance(vested_balance: &VestedBalance) -> u128 {
+            vested_balance.frozen_balance
+                + vested_balance.liquid_balance
+                + vested_balance.requested_balance
+                + vested_balance.disputed_balance
+        }
+
+        /// Helper function to move an amount between the balance totals of two statuses
+        fn move_balance(vested_balance: &mut VestedBalance,
+            from: ScheduleStatus,
+            to: ScheduleStatus,
+            amount: u128) {
+
+            let balance = Self::balance_of_status(vested_balance, from);
+            *balance = balance.saturating_sub(amount);
+            *Self::balance_of_status(vested_balance, to) += amount;
+            Self::bump_version(vested_balance);
+        }
+
+        /// Helper function to pin the recipient of the first approved transfer when the grant asks for it
+        fn pin_recipient(vested_balance: &mut VestedBalance, recipient: AccountId) {
+            if vested_balance.pin_recipient && vested_balance.pinned_recipient.is_none() {
+                vested_balance.pinned_recipient = Some(recipient);
+            }
+        }
+
+        /// Helper function to mark a vested balance as changed
+        fn bump_version(vested_balance: &mut VestedBalance) {
+            vested_balance.version = vested_balance.version.wrapping_add(1);
+        }
+
+        /// Helper function to get the balance total a status is accounted in
+        fn balance_of_status(vested_balance: &mut VestedBalance, status: ScheduleStatus) -> &mut u128 {
+            match status {
+                ScheduleStatus::Frozen => &mut vested_balance.frozen_balance,
+                ScheduleStatus::Liquid => &mut vested_balance.liquid_balance,
+                // Approved, the payout is in flight
+                ScheduleStatus::Requested | ScheduleStatus::Approved => &mut vested_balance.requested_balance,
+                ScheduleStatus::Transferred => &mut vested_balance.transferred_balance,
+                ScheduleStatus::Disputed => &mut vested_balance.disputed_balance,
+                ScheduleStatus::Forfeited => &mut vested_balance.forfeited_balance,
+            }
+        }
+
+        /// Helper function to calculate balances from scratch, used to verify and repair the totals
+        fn calculate_balances(vested_balance: &mut VestedBalance) {
+            vested_balance.frozen_balance = 0;
+            vested_balance.liquid_balance = 0;
+            vested_balance.requested_balance = 0;
+            vested_balance.transferred_balance = 0;
+            vested_balance.disputed_balance = 0;
+            vested_balance.forfeited_balance = 0;
+
+            let amounts: Vec<(ScheduleStatus, u128)> = vested_balance.vested_balance_schedules.iter()
+                .map(|s| (s.status, s.schedule_balance))
+                .collect();
+            for (status, amount) in amounts {
+                *Self::balance_of_status(vested_balance, status) += amount;
+            }
+        }
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        type Event = <Vesting as ::ink::reflect::ContractEventBase>::Type;
+
+        /// Status of the last vesting event
+        fn last_status() -> Option<VestingStatus> {
+            ink::env::test::recorded_events()
+                .filter_map(|event| match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                    Ok(Event::VestingEvent(vesting_event)) => Some(vesting_event.status),
+                    _ => None,
+                })
+                .last()
+        }
+
+        /// Assert the last vesting event is the given error
+        fn assert_error(error: Error) {
+            assert_eq!(last_status(), Some(VestingStatus::EmitError(error)));
+        }
+
+        /// Default accounts and a program of the asset and schedule count, owned by alice
+        fn setup_with(asset_id: u128,
+            total_vested_schedule: u16) -> (ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment>, Vesting) {
+
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            set_caller(accounts.alice);
+            (accounts, Vesting::new(asset_id, total_vested_schedule).unwrap())
+        }
+
+        /// Default accounts and a program of asset 1 with two schedules, owned by alice
+        fn setup() -> (ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment>, Vesting) {
+            setup_with(1, 2)
+        }
+
+        /// Make the account the caller of the next messages
+        fn set_caller(account: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(account);
+        }
+
+        /// We test if the default constructor does its job.
+        #[ink::test]
+        fn default_works() {
+            let vesting = Vesting::default().unwrap();
+            assert_eq!(vesting.get_vesting_info().0, 0);
+            assert_eq!(vesting.get_storage_version(), STORAGE_VERSION);
+        }
+
+        /// We test the payout instruction and its acknowledgement.
+        #[ink::test]
+        fn payout_instruction_works() {
+            let (accounts, mut vesting) = setup();
+
+            let schedules = vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            assert_eq!(schedules.len(), 2);
+            assert_eq!(schedules[0].schedule_balance, 50);
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap(), vec![
+                BatchItemResult { index: 0, result: Ok(Success::VestedBalanceScheduleThawed) },
+            ]);
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap(), Some(1));
+
+            set_caller(accounts.alice);
+            assert_eq!(vesting.approve_payout(accounts.bob, 1, None).unwrap(), Some(1));
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Approved);
+            assert_eq!(vested_balance.requested_balance, 50);
+
+            vesting.ack_payout(1, vec![1, 2, 3], None).unwrap();
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.vested_balance_schedules[0].particulars, vec![1, 2, 3]);
+            assert_eq!(vested_balance.transferred_balance, 50);
+            assert_eq!(vested_balance.requested_balance, 0);
+
+            // The particulars are kept out of the vested balances, archived records carry them
+            assert!(vesting.vested_balances[0].vested_balance_schedules[0].particulars.is_empty());
+            vesting.remove_vested_balance(accounts.bob, true, None).unwrap();
+            assert_eq!(vesting.get_archived(accounts.bob).unwrap().vested_balance_schedules[0].particulars, vec![1, 2, 3]);
+            vesting.restore_archived(accounts.bob).unwrap();
+            assert!(vesting.vested_balances[0].vested_balance_schedules[0].particulars.is_empty());
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().particulars, vec![1, 2, 3]);
+        }
+
+        /// We test the caller scoped views.
+        #[ink::test]
+        fn my_views_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            assert!(vesting.my_pending_requests().is_empty());
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            assert_eq!(vesting.my_vested_balance().unwrap().address, accounts.bob);
+            let requests = vesting.my_pending_requests();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].schedule_number, 1);
+
+            set_caller(accounts.charlie);
+            assert_eq!(vesting.my_vested_balance(), None);
+        }
+
+        /// We test the status breakdown follows the schedule transitions.
+        #[ink::test]
+        fn status_breakdown_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 200, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+
+            let breakdown = vesting.get_status_breakdown();
+            assert_eq!(breakdown[0], (ScheduleStatus::Frozen, 2, 150));
+            assert_eq!(breakdown[1], (ScheduleStatus::Liquid, 1, 100));
+            assert_eq!(breakdown[2], (ScheduleStatus::Requested, 1, 50));
+            assert_eq!(breakdown[3], (ScheduleStatus::Transferred, 0, 0));
+
+            set_caller(accounts.alice);
+            vesting.remove_vested_balance(accounts.charlie, false, None).unwrap();
+
+            let breakdown = vesting.get_status_breakdown();
+            assert_eq!(breakdown[0], (ScheduleStatus::Frozen, 1, 50));
+            assert_eq!(breakdown[1], (ScheduleStatus::Liquid, 0, 0));
+
+            let totals = vesting.get_totals();
+            assert_eq!(totals.original_balance, 100);
+            assert_eq!(totals.frozen_balance, 50);
+            assert_eq!(totals.requested_balance, 50);
+        }
+
+        /// We test the top holders ordering by remaining balance.
+        #[ink::test]
+        fn top_holders_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 80, None).unwrap();
+            vesting.add_vested_balance(accounts.django, 60, None).unwrap();
+            assert_eq!(vesting.get_top_holders(2), vec![(accounts.bob, 100), (accounts.charlie, 80)]);
+
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+
+            assert_eq!(
+                vesting.get_top_holders(3),
+                vec![(accounts.charlie, 80), (accounts.django, 60), (accounts.bob, 50)]
+            );
+        }
+
+        /// We test the unlocks window query.
+        #[ink::test]
+        fn unlocks_between_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_unlock_time(1, 1_000).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            let version = vesting.get_vested_balance(accounts.bob).unwrap().version;
+            vesting.set_unlock_time(2, 2_000).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().version, version + 1);
+
+            assert_eq!(vesting.get_unlocks_between(0, 1_500), vec![(accounts.bob, 1, 50)]);
+            assert_eq!(vesting.get_unlocks_between(1_500, 2_000), vec![(accounts.bob, 2, 50)]);
+
+            vesting.thaw_vested_balances(1, None).unwrap();
+            assert!(vesting.get_unlocks_between(0, 1_500).is_empty());
+        }
+
+        /// We test that anyone can thaw due unlocks, bounded by the limit.
+        #[ink::test]
+        fn process_due_unlocks_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_unlock_time(1, 1_000).unwrap();
+            vesting.set_unlock_time(2, 2_000).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+
+            set_caller(accounts.django);
+            assert_eq!(vesting.process_due_unlocks(10).unwrap(), 0);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_500);
+            assert_eq!(vesting.process_due_unlocks(1).unwrap(), 1);
+            assert_eq!(vesting.process_due_unlocks(10).unwrap(), 1);
+            assert_eq!(vesting.get_vested_balance(accounts.charlie).unwrap().liquid_balance, 50);
+            assert_eq!(vesting.get_vested_balance(accounts.charlie).unwrap().frozen_balance, 50);
+        }
+
+        /// We test the keeper bounty is paid per processed item out of the pool.
+        #[ink::test]
+        fn keeper_bounty_works() {
+            let (accounts, mut vesting) = setup();
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+
+            vesting.set_keeper_bounty(10).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(15);
+            vesting.fund_keeper_pool().unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(vesting.fund_keeper_pool(), Err(Error::BadOrigin));
+            set_caller(accounts.alice);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 15);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+
+            vesting.set_unlock_time(1, 1_000).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            set_caller(accounts.django);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.django, 0);
+            assert_eq!(vesting.process_due_unlocks(10).unwrap(), 2);
+
+            // Two items are worth 20, the pool only holds 15
+            assert_eq!(vesting.get_keeper_info(), (10, 0));
+            assert_eq!(
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.django),
+                Ok(15)
+            );
+        }
+
+        /// We test the dispute flow and its resolution by the arbiter.
+        #[ink::test]
+        fn dispute_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_arbiter(Some(accounts.eve)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            let payout_id = vesting.approve_payout(accounts.bob, 1, None).unwrap().unwrap();
+            vesting.approve_transfer(accounts.bob, 2, vec![2], None).unwrap();
+
+            // Paid schedules cannot be disputed
+            set_caller(accounts.bob);
+            vesting.dispute_schedule(accounts.bob, 2).unwrap();
+            assert_error(Error::VestedBalanceScheduleNotDisputable { address: accounts.bob, schedule_number: 2, status: ScheduleStatus::Transferred });
+
+            // The beneficiary disputes the wrong recipient, the payout is withdrawn
+            vesting.dispute_schedule(accounts.bob, 1).unwrap();
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Disputed);
+            assert_eq!(vested_balance.disputed_balance, 50);
+            set_caller(accounts.alice);
+            vesting.ack_payout(payout_id, vec![1], None).unwrap();
+            assert_error(Error::PayoutNotFound { payout_id });
+            set_caller(accounts.bob);
+
+            // Only the arbiter resolves
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Reverse).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Disputed);
+
+            set_caller(accounts.eve);
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Reverse).unwrap();
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Liquid);
+            assert_eq!(vested_balance.vested_balance_schedules[0].recipient_address, None);
+            assert_eq!(vested_balance.liquid_balance, 50);
+            assert_eq!(vested_balance.transferred_balance, 50);
+
+            // The decision is recorded in the schedule history
+            let history = vesting.get_schedule_history(accounts.bob, 1);
+            let entry = history.last().unwrap();
+            assert_eq!((entry.previous_status, entry.status), (ScheduleStatus::Disputed, ScheduleStatus::Liquid));
+            assert_eq!(entry.operator, accounts.eve);
+            assert_eq!(entry.reason, DisputeOutcome::Reverse.encode());
+        }
+
+        /// We test a split decision returns part of a disputed schedule to the beneficiary.
+        #[ink::test]
+        fn dispute_split_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_arbiter(Some(accounts.eve)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            let payout_id = vesting.approve_payout(accounts.bob, 1, None).unwrap().unwrap();
+            vesting.dispute_schedule(accounts.bob, 1).unwrap();
+
+            set_caller(accounts.eve);
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Split { returned: 50 }).unwrap();
+            assert_error(Error::InvalidDisputeSplit { address: accounts.bob, schedule_number: 1, amount: 50 });
+
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Split { returned: 20 }).unwrap();
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Approved);
+            assert_eq!(vested_balance.vested_balance_schedules[0].schedule_balance, 30);
+            assert_eq!(vested_balance.vested_balance_schedules[2].schedule_number, 3);
+            assert_eq!(vested_balance.vested_balance_schedules[2].status, ScheduleStatus::Liquid);
+            assert_eq!(vested_balance.vested_balance_schedules[2].schedule_balance, 20);
+            assert_eq!(vested_balance.requested_balance, 30);
+            assert_eq!(vested_balance.liquid_balance, 20);
+            assert_eq!(vested_balance.disputed_balance, 0);
+            assert_eq!(vesting.get_schedule_history(accounts.bob, 3).len(), 1);
+
+            // The upheld part is instructed again under a new payout id
+            set_caller(accounts.alice);
+            vesting.ack_payout(payout_id, vec![1], None).unwrap();
+            assert_error(Error::PayoutNotFound { payout_id });
+            vesting.ack_payout(payout_id + 1, vec![1], None).unwrap();
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.transferred_balance, 30);
+        }
+
+        /// We test a dispute of a transferred schedule, opened before they were refused, can only be upheld.
+        #[ink::test]
+        fn transferred_dispute_is_only_upheld() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_arbiter(Some(accounts.eve)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+
+            // Dispute left in storage by an earlier version
+            let vested_balance = &mut vesting.vested_balances[0];
+            vested_balance.vested_balance_schedules[0].status = ScheduleStatus::Disputed;
+            vested_balance.transferred_balance -= 50;
+            vested_balance.disputed_balance += 50;
+            vesting.disputes.insert((accounts.bob, 1), &ScheduleStatus::Transferred);
+
+            set_caller(accounts.eve);
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Split { returned: 20 }).unwrap();
+            assert_error(Error::DisputeOutcomeNotAllowed { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Transferred });
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Reverse).unwrap();
+            assert_error(Error::DisputeOutcomeNotAllowed { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Transferred });
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules.len(), 2);
+
+            vesting.resolve_dispute(accounts.bob, 1, DisputeOutcome::Uphold).unwrap();
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.vested_balance_schedules[0].schedule_balance, 50);
+            assert_eq!(vested_balance.transferred_balance, 50);
+        }
+
+        /// We test an approval revert needs both the owner and the guardian.
+        #[ink::test]
+        fn revert_approval_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_guardian(Some(accounts.frank)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer_with_memo(accounts.bob, 1, vec![7], b"board ok".to_vec(), None).unwrap();
+
+            // One account holding both roles cannot sign twice
+            vesting.guardian = Some(accounts.alice);
+            vesting.revert_approval(accounts.bob, 1, ScheduleStatus::Requested, vec![1]).unwrap();
+            assert_error(Error::GuardianIsOwner);
+            vesting.guardian = Some(accounts.frank);
+
+            vesting.revert_approval(accounts.bob, 1, ScheduleStatus::Requested, vec![1]).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+
+            set_caller(accounts.frank);
+            vesting.revert_approval(accounts.bob, 1, ScheduleStatus::Requested, vec![1]).unwrap();
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Requested);
+            assert_eq!(vested_balance.vested_balance_schedules[0].recipient_address, Some(accounts.charlie));
+            assert_eq!(vested_balance.requested_balance, 50);
+            assert_eq!(vesting.find_by_tx_hash(vec![7]), None);
+            assert!(vesting.get_receipts_for(accounts.bob, 0, 10).is_empty());
+
+            // After the unlock and the request, the approval is recorded with its approver and memo, then the revert
+            let history = vesting.get_schedule_history(accounts.bob, 1);
+            assert_eq!(history.len(), 4);
+            assert_eq!((history[0].previous_status, history[0].status), (ScheduleStatus::Frozen, ScheduleStatus::Liquid));
+            assert_eq!((history[1].previous_status, history[1].status, history[1].operator), (ScheduleStatus::Liquid, ScheduleStatus::Requested, accounts.bob));
+            assert_eq!(history[2].status, ScheduleStatus::Transferred);
+            assert_eq!(history[2].operator, accounts.alice);
+            assert_eq!(history[2].reason, b"board ok".to_vec());
+            assert_eq!(history[3].particulars, vec![7]);
+            assert_eq!(history[3].reason, vec![1]);
+        }
+
+        /// We test an operation id is only applied once.
+        #[ink::test]
+        fn op_id_is_idempotent() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, Some([1; 32])).unwrap();
+            assert_eq!(vesting.thaw_vested_balances(1, Some([2; 32])).unwrap().len(), 1);
+
+            // Replays are no-ops
+            vesting.remove_vested_balance(accounts.bob, false, Some([1; 32])).unwrap();
+            assert!(vesting.get_vested_balance(accounts.bob).is_some());
+            assert_eq!(vesting.add_vested_balance(accounts.charlie, 100, Some([1; 32])).unwrap(), Vec::new());
+            assert!(vesting.get_vested_balance(accounts.charlie).is_none());
+            assert_eq!(vesting.thaw_vested_balances(2, Some([2; 32])).unwrap(), Vec::new());
+        }
+
+        /// We test a signed request is rejected without a valid signature and nonce.
+        #[ink::test]
+        fn signed_request_checks_signature_and_nonce() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.django);
+            assert_eq!(vesting.request_transfer_signed(accounts.bob, 1, accounts.django, Vec::new(), 1, [0; 65]).unwrap(), None);
+            assert_eq!(vesting.request_transfer_signed(accounts.bob, 1, accounts.django, Vec::new(), 0, [1; 65]).unwrap(), None);
+
+            assert_eq!(vesting.get_nonce(accounts.bob), 0);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Liquid);
+        }
+
+        /// We test guarded messages are rejected while the reentrancy lock is held. Re-entering through a
+        /// real callback is tested end-to-end with the mock contract.
+        #[ink::test]
+        fn reentrancy_lock_blocks_guarded_messages() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+
+            // Simulate a token calling back while an approval is in progress
+            vesting.set_locked(true);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.approve_payout(accounts.bob, 1, None).unwrap(), None);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Requested);
+
+            vesting.set_locked(false);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert!(!vesting.is_locked());
+        }
+
+        /// We test the incremental balance totals match a full recalculation.
+        #[ink::test]
+        fn incremental_balances_match_recalculation() {
+            let (accounts, mut vesting) = setup_with(1, 3);
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            assert!(vesting.verify_balances(accounts.bob));
+
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.bob, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            vesting.approve_payout(accounts.bob, 2, None).unwrap();
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.frozen_balance, 33);
+            assert_eq!(vested_balance.requested_balance, 33);
+            assert_eq!(vested_balance.transferred_balance, 33);
+            assert!(vesting.verify_balances(accounts.bob));
+            assert!(!vesting.verify_balances(accounts.charlie));
+        }
+
+        /// We test the guarded configuration setters.
+        #[ink::test]
+        fn configuration_setters_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_asset_id(7).unwrap();
+            vesting.set_default_schedule_count(0).unwrap();
+            vesting.set_fee_bps(10_001).unwrap();
+            assert_eq!(vesting.get_vesting_info().0, 7);
+            assert_eq!(vesting.get_vesting_info().1, 2);
+            assert_eq!(vesting.get_request_config(), (None, 0));
+
+            vesting.set_default_schedule_count(4).unwrap();
+            vesting.set_fee_bps(50).unwrap();
+            vesting.set_request_ttl(Some(100)).unwrap();
+            assert_eq!(vesting.get_request_config(), (Some(100), 50));
+
+            vesting.set_compliance_hook(Some(accounts.frank)).unwrap();
+            assert_eq!(vesting.get_compliance_hook(), Some(accounts.frank));
+            vesting.set_compliance_hook(None).unwrap();
+            vesting.set_callback_gas_limit(1_000).unwrap();
+            assert_eq!(vesting.callback_gas_limit, 1_000);
+            vesting.set_callback_gas_limit(0).unwrap();
+            assert_error(Error::InvalidCallbackGasLimit);
+            assert_eq!(vesting.callback_gas_limit, 1_000);
+
+            // Recipients register their own callback
+            set_caller(accounts.frank);
+            vesting.register_transfer_callback(Some(ink::selector_bytes!("on_vested_transfer"))).unwrap();
+            assert_eq!(vesting.get_transfer_callback(accounts.frank), Some(ink::selector_bytes!("on_vested_transfer")));
+            vesting.register_transfer_callback(None).unwrap();
+            assert_eq!(vesting.get_transfer_callback(accounts.frank), None);
+            set_caller(accounts.alice);
+
+            // The asset cannot change once grants exist
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.set_asset_id(8).unwrap();
+            assert_eq!(vesting.get_vesting_info().0, 7);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules.len(), 4);
+        }
+
+        /// We test requests older than the time-to-live return to liquid.
+        #[ink::test]
+        fn expire_requests_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_request_ttl(Some(100)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.django);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(99);
+            assert_eq!(vesting.expire_requests(10).unwrap(), 0);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(vesting.expire_requests(10).unwrap(), 1);
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Liquid);
+            assert_eq!(vested_balance.vested_balance_schedules[0].recipient_address, None);
+            assert_eq!(vested_balance.liquid_balance, 50);
+            assert_eq!(vested_balance.requested_balance, 0);
+        }
+
+        /// We test a beneficiary can forfeit frozen and liquid schedules only.
+        #[ink::test]
+        fn forfeit_works() {
+            let (accounts, mut vesting) = setup_with(1, 3);
+
+            vesting.add_vested_balance(accounts.bob, 90, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(2, accounts.bob, Vec::new()).unwrap();
+            let results = vesting.forfeit(vec![1, 2, 3]).unwrap();
+            assert_eq!(results.iter().filter(|item| item.result.is_ok()).count(), 2);
+            assert_eq!(results[1].index, 1);
+            assert!(results[1].result.is_err());
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Forfeited);
+            assert_eq!(vested_balance.vested_balance_schedules[1].status, ScheduleStatus::Requested);
+            assert_eq!(vested_balance.vested_balance_schedules[2].status, ScheduleStatus::Forfeited);
+            assert_eq!(vested_balance.forfeited_balance, 60);
+            assert_eq!(vesting.get_totals().forfeited_balance, 60);
+            assert_eq!(vesting.get_top_holders(1), vec![(accounts.bob, 30)]);
+        }
+
+        /// We test a liquid schedule donation merges into the receiving position.
+        #[ink::test]
+        fn donate_schedule_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 40, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.donate_schedule(1, accounts.charlie).unwrap(), None);
+
+            set_caller(accounts.alice);
+            vesting.set_donations_enabled(true).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.donate_schedule(2, accounts.charlie).unwrap(), None);
+            let schedule_id = vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].schedule_id;
+            assert_eq!(vesting.donate_schedule(1, accounts.charlie).unwrap(), Some(3));
+
+            let donor = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(donor.vested_balance_schedules.len(), 1);
+            assert_eq!(donor.original_balance, 50);
+            assert!(vesting.verify_balances(accounts.bob));
+
+            let receiver = vesting.get_vested_balance(accounts.charlie).unwrap();
+            assert_eq!(receiver.vested_balance_schedules[2].schedule_number, 3);
+            assert_eq!(receiver.vested_balance_schedules[2].schedule_id, schedule_id);
+            assert_ne!(receiver.vested_balance_schedules[0].schedule_id, schedule_id);
+            assert_eq!(receiver.liquid_balance, 70);
+            assert_eq!(receiver.original_balance, 90);
+            assert!(vesting.verify_balances(accounts.charlie));
+
+            // The history follows the schedule
+            assert!(vesting.schedule_history.get((accounts.bob, 1)).is_none());
+            assert_eq!(vesting.schedule_history.get((accounts.charlie, 3)).unwrap()[0].status, ScheduleStatus::Liquid);
+
+            // A grant added again in the same block gets new ids
+            set_caller(accounts.alice);
+            let schedule_id = vesting.add_vested_balance(accounts.django, 100, None).unwrap()[0].schedule_id;
+            assert_eq!(vesting.get_schedule(accounts.django, 1).unwrap().schedule_id, schedule_id);
+            vesting.remove_vested_balance(accounts.django, false, None).unwrap();
+            assert_ne!(vesting.add_vested_balance(accounts.django, 100, None).unwrap()[0].schedule_id, schedule_id);
+        }
+
+        /// We test native vesting is paid out of the escrow on approval.
+        #[ink::test]
+        fn native_payout_works() {
+            let (accounts, mut vesting) = setup_with(0, 2);
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+
+            // An asset program has no native escrow to fund
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(10);
+            assert_eq!(vesting.fund_native(), Err(Error::NativeVestingDisabled));
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+
+            vesting.set_asset_kind(AssetKind::Native).unwrap();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 100);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie, 0);
+
+            // The grant must be funded exactly in the same call
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(80);
+            assert_eq!(
+                vesting.add_vested_balance(accounts.bob, 100, None),
+                Err(Error::FundingMismatch { expected: 100, transferred: 80 })
+            );
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(vesting.get_native_info(), (AssetKind::Native, 100));
+
+            // Only the owner tops up the escrow, refused funding reverts
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(10);
+            vesting.fund_native().unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(vesting.fund_native(), Err(Error::BadOrigin));
+            set_caller(accounts.alice);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            assert_eq!(vesting.get_native_info(), (AssetKind::Native, 110));
+
+            // Leave less than a schedule in the escrow
+            vesting.native_pool = 80;
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            assert_eq!(vesting.approve_payout(accounts.bob, 1, None).unwrap(), None);
+            assert_eq!(vesting.get_native_info(), (AssetKind::Native, 30));
+
+            // The escrow cannot cover the second schedule, the failed payout is reported
+            let emitted = ink::env::test::recorded_events().count();
+            vesting.approve_transfer(accounts.bob, 2, vec![], None).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), emitted + 2);
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.vested_balance_schedules[1].status, ScheduleStatus::Requested);
+            assert_eq!(vested_balance.transferred_balance, 50);
+            assert_eq!(
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie),
+                Ok(50)
+            );
+        }
+
+        /// We test the error codes are stable.
+        #[ink::test]
+        fn error_codes_are_stable() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(Error::BadOrigin.error_code(), 1);
+            assert_eq!(Error::PayoutNotFound { payout_id: 1 }.error_code(), 8);
+            assert_eq!(Error::ScheduleLimitReached { address: accounts.bob }.error_code(), 24);
+            assert_eq!(Error::FundingMismatch { expected: 1, transferred: 0 }.error_code(), 27);
+        }
+
+        /// We test a checked approval is rejected once the vested balance changed.
+        #[ink::test]
+        fn approve_transfer_checked_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            let reviewed = vesting.get_vested_balance(accounts.bob).unwrap().version;
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer_checked(accounts.bob, 1, vec![1], reviewed, None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Requested);
+
+            let current = vesting.get_vested_balance(accounts.bob).unwrap().version;
+            vesting.approve_transfer_checked(accounts.bob, 1, vec![1], current, None).unwrap();
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.version, current + 1);
+        }
+
+        /// We test transfer requests are cancelled, rejected and approved by their id.
+        #[ink::test]
+        fn request_ids_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, [0; MAX_MEMO_LENGTH + 1].to_vec()).unwrap(), None);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, b"to exchange deposit".to_vec()).unwrap(), Some(1));
+            assert_eq!(vesting.get_request(1).unwrap().amount, 50);
+            assert_eq!(vesting.my_pending_requests()[0].memo, b"to exchange deposit".to_vec());
+            vesting.cancel_request(1).unwrap();
+            assert_eq!(vesting.get_request(1), None);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap(), Some(2));
+
+            set_caller(accounts.alice);
+            vesting.reject_request(2, b"unknown recipient".to_vec()).unwrap();
+
+            // The unlock, both requests, the cancel and the reject
+            assert_eq!(vesting.get_schedule_history(accounts.bob, 1).len(), 5);
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.django, Vec::new()).unwrap(), Some(3));
+
+            // Stale ids no longer resolve to the schedule
+            set_caller(accounts.alice);
+            vesting.approve_request(2, vec![1], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Requested);
+
+            vesting.approve_request(3, vec![1], None).unwrap();
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.vested_balance_schedules[0].recipient_address, Some(accounts.django));
+            assert_eq!(vesting.get_request(3), None);
+            assert!(vesting.verify_balances(accounts.bob));
+        }
+
+        /// We test programs with more than 255 schedules.
+        #[ink::test]
+        fn many_schedules_work() {
+            let (accounts, mut vesting) = setup();
+            vesting.set_max_schedule_number(312).unwrap();
+            vesting.set_default_schedule_count(312).unwrap();
+
+            let schedules = vesting.add_vested_balance(accounts.bob, 3_120, None).unwrap();
+            assert_eq!(schedules.len(), 312);
+            assert_eq!(schedules[311].schedule_number, 312);
+
+            assert_eq!(vesting.thaw_vested_balances(300, None).unwrap().len(), 1);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().liquid_balance, 10);
+        }
+
+        /// We test transfers are pinned to the first approved recipient until the owner unlocks it.
+        #[ink::test]
+        fn recipient_pinning_works() {
+            let (accounts, mut vesting) = setup_with(1, 3);
+
+            vesting.add_vested_balance(accounts.bob, 90, None).unwrap();
+            vesting.set_recipient_pinning(accounts.bob, true).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            vesting.thaw_vested_balances(3, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().pinned_recipient, Some(accounts.charlie));
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(2, accounts.django, Vec::new()).unwrap(), None);
+            assert_eq!(vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap(), Some(2));
+
+            set_caller(accounts.alice);
+            vesting.unlock_recipient(accounts.bob).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(3, accounts.django, Vec::new()).unwrap(), Some(3));
+        }
+
+        /// We test a transfer request is verified by compliance before the treasury executes it.
+        #[ink::test]
+        fn two_phase_approval_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_compliance_operator(Some(accounts.eve)).unwrap();
+            vesting.set_treasury(Some(accounts.frank)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap(), Some(1));
+
+            // Not verified yet, the owner cannot skip the verification either
+            set_caller(accounts.frank);
+            vesting.execute_transfer(1, vec![1], None).unwrap();
+            assert!(!vesting.get_request(1).unwrap().verified);
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_error(Error::RequestNotVerified { request_id: 1 });
+            assert_eq!(vesting.approve_payout(accounts.bob, 1, None).unwrap(), None);
+            assert_error(Error::RequestNotVerified { request_id: 1 });
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Requested);
+            set_caller(accounts.frank);
+
+            // Only the compliance operator verifies
+            vesting.mark_verified(1).unwrap();
+            assert!(!vesting.get_request(1).unwrap().verified);
+            set_caller(accounts.eve);
+            vesting.mark_verified(1).unwrap();
+            assert!(vesting.get_request(1).unwrap().verified);
+
+            // The treasury cannot approve directly, it executes verified requests
+            set_caller(accounts.frank);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Requested);
+            vesting.execute_transfer(1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+        }
+
+        /// We test the approval preview reports the fee and blocking conditions.
+        #[ink::test]
+        fn can_approve_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_fee_bps(1_000).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            let preview = vesting.can_approve(accounts.bob, 1).unwrap();
+            assert_eq!(preview.blocking, vec![Error::VestedBalanceScheduleNotRequested {
+                address: accounts.bob,
+                schedule_number: 1,
+                status: ScheduleStatus::Liquid,
+            }]);
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            assert_eq!(vesting.can_approve(accounts.bob, 1).unwrap().blocking, vec![Error::BadOrigin]);
+
+            set_caller(accounts.alice);
+            let preview = vesting.can_approve(accounts.bob, 1).unwrap();
+            assert_eq!(preview.amount, 50);
+            assert_eq!(preview.recipient, Recipient::Account(accounts.charlie));
+            assert_eq!(preview.fee, 5);
+            assert!(preview.blocking.is_empty());
+
+            // The tier, verification and escrow checks of the approval are reported
+            vesting.set_approval_tiers(vec![ApprovalTier { min_amount: 50, approvals: 2, guardian: false }]).unwrap();
+            vesting.set_compliance_operator(Some(accounts.eve)).unwrap();
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.frank), 10).unwrap();
+            assert_eq!(vesting.can_approve(accounts.bob, 1).unwrap().blocking, vec![
+                Error::ApprovalsMissing { address: accounts.bob, schedule_number: 1 },
+                Error::RequestNotVerified { request_id: 1 },
+                Error::EscrowApprovalRequired { request_id: 1, escrow: accounts.frank },
+            ]);
+            assert_eq!(
+                vesting.can_approve(accounts.charlie, 1),
+                Err(Error::VestedBalanceNotFound { address: accounts.charlie })
+            );
+        }
+
+        /// We test large transfers wait for the approvals of their amount tier.
+        #[ink::test]
+        fn approval_tiers_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_guardian(Some(accounts.eve)).unwrap();
+            vesting.set_approvers(vec![accounts.django]).unwrap();
+            vesting.set_approval_tiers(vec![
+                ApprovalTier { min_amount: 100, approvals: 2, guardian: true },
+                ApprovalTier { min_amount: 50, approvals: 2, guardian: false },
+            ]).unwrap();
+            vesting.add_vested_balance(accounts.bob, 200, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 20, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+            set_caller(accounts.charlie);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            // Below every tier a single approval is enough
+            set_caller(accounts.django);
+            vesting.approve_transfer(accounts.charlie, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.charlie).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+
+            // The top tier needs two approvers and the guardian
+            vesting.approve_transfer(accounts.bob, 1, vec![2], None).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![2], None).unwrap();
+            assert_eq!(vesting.get_approvals(accounts.bob, 1), vec![accounts.django, accounts.alice]);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Requested);
+
+            set_caller(accounts.eve);
+            vesting.approve_transfer(accounts.bob, 1, vec![2], None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert!(vesting.get_approvals(accounts.bob, 1).is_empty());
+        }
+
+        /// We test unverified beneficiaries cannot request transfers when KYC is required.
+        #[ink::test]
+        fn kyc_gating_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_compliance_operator(Some(accounts.eve)).unwrap();
+            vesting.set_kyc_required(true).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap(), None);
+
+            // Only the compliance operator verifies
+            vesting.set_kyc_verified(accounts.bob, true).unwrap();
+            assert!(!vesting.get_vested_balance(accounts.bob).unwrap().kyc_verified);
+            set_caller(accounts.eve);
+            vesting.set_kyc_verified(accounts.bob, true).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap(), Some(1));
+        }
+
+        /// We test an investor category in lockup cannot request transfers.
+        #[ink::test]
+        fn category_lockup_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            vesting.set_compliance_profile(accounts.bob, Some(ComplianceProfile { jurisdiction: 840, category: 2 })).unwrap();
+            vesting.set_category_lockup(2, Some(1_000)).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            assert_eq!(vesting.get_beneficiaries_by_category(2), vec![
+                (accounts.bob, ComplianceProfile { jurisdiction: 840, category: 2 }, 100),
+            ]);
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap(), None);
+            set_caller(accounts.charlie);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap(), Some(1));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap(), Some(2));
+        }
+
+        /// We test grants shaped by a template.
+        #[ink::test]
+        fn templates_work() {
+            let (accounts, mut vesting) = setup();
+
+            assert_eq!(vesting.create_template(b"empty".to_vec(), vec![0, 0], 0, 0).unwrap(), None);
+            assert_eq!(vesting.create_template(b"quarterly".to_vec(), vec![1, 1, 1, 1], 1_000, 100).unwrap(), Some(1));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(10);
+            assert!(vesting.add_vested_balance_from_template(accounts.bob, 101, 2, None).unwrap().is_empty());
+            let schedules = vesting.add_vested_balance_from_template(accounts.bob, 101, 1, None).unwrap();
+            assert_eq!(schedules.iter().map(|s| s.schedule_balance).collect::<Vec<_>>(), vec![25, 25, 25, 26]);
+            assert_eq!(schedules[0].unlock_time, Some(1_010));
+            assert_eq!(schedules[3].unlock_time, Some(1_310));
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().frozen_balance, 101);
+            assert!(vesting.verify_balances(accounts.bob));
+        }
+
+        /// We test a new grant copies the terms of an existing one.
+        #[ink::test]
+        fn clone_grant_terms_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.create_template(b"back loaded".to_vec(), vec![1, 3], 100, 100).unwrap();
+            vesting.add_vested_balance_from_template(accounts.bob, 400, 1, None).unwrap();
+
+            assert!(vesting.clone_grant_terms(accounts.charlie, accounts.django, 80, None).unwrap().is_empty());
+            let schedules = vesting.clone_grant_terms(accounts.bob, accounts.django, 80, None).unwrap();
+            assert_eq!(schedules.iter().map(|s| s.schedule_balance).collect::<Vec<_>>(), vec![20, 60]);
+            assert_eq!(schedules[1].unlock_time, Some(200));
+            assert!(vesting.verify_balances(accounts.django));
+
+            // Large amounts and weights are split without overflowing
+            let large = u128::MAX / 3;
+            assert_eq!(Vesting::split_by_weights(u128::MAX, &[large, 2 * large]), vec![large, u128::MAX - large]);
+            let parts = Vesting::split_by_weights(u128::MAX, &[u128::MAX, u128::MAX, 1]);
+            assert_eq!(parts.iter().try_fold(0u128, |total, part| total.checked_add(*part)), Some(u128::MAX));
+        }
+
+        /// We test batch messages report each item and keep going past bad ones.
+        #[ink::test]
+        fn batch_results_work() {
+            let (accounts, mut vesting) = setup();
+
+            let added = vesting.add_vested_balances(vec![(accounts.bob, 100), (accounts.bob, 50), (accounts.charlie, 40)], None).unwrap();
+            assert_eq!(added[1].result, Err(Error::VestedBalanceAlreadyExist { address: accounts.bob }));
+            assert_eq!(added[2], BatchItemResult { index: 2, result: Ok(Success::VestedBalanceAdded) });
+
+            let thawed = vesting.thaw_schedules(vec![(accounts.bob, 1), (accounts.bob, 1), (accounts.charlie, 2)], None).unwrap();
+            assert_eq!(thawed[1].result, Err(Error::VestedBalanceScheduleNotFrozen { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Liquid }));
+            assert!(thawed[2].result.is_ok());
+            assert!(vesting.verify_balances(accounts.bob));
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            let approved = vesting.approve_transfers(vec![(accounts.charlie, 2, vec![2]), (accounts.bob, 1, vec![1])], None).unwrap();
+            assert!(approved[0].result.is_err());
+            assert_eq!(approved[1].result, Ok(Success::VestedBalanceScheduleApproved));
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().transferred_balance, 50);
+        }
+
+        /// We test a tx-hash cannot settle two schedules.
+        #[ink::test]
+        fn duplicate_tx_hash_is_rejected() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.bob, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![9], None).unwrap();
+            let approved = vesting.approve_transfers(vec![(accounts.bob, 2, vec![9]), (accounts.bob, 2, vec![8])], None).unwrap();
+            assert_eq!(approved[0].result, Err(Error::DuplicateTxHash { address: accounts.bob, schedule_number: 1 }));
+            assert!(approved[1].result.is_ok());
+
+            assert_eq!(vesting.find_by_tx_hash(vec![9]), Some((accounts.bob, 1)));
+            assert_eq!(vesting.find_by_tx_hash(vec![8]), Some((accounts.bob, 2)));
+            assert_eq!(vesting.find_by_tx_hash(vec![7]), None);
+
+            // The hashes follow a migrated position
+            set_caller(accounts.bob);
+            vesting.set_backup(Some(accounts.eve), 1_000).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2_000);
+            set_caller(accounts.eve);
+            vesting.claim_as_backup(accounts.bob).unwrap();
+            assert_eq!(vesting.find_by_tx_hash(vec![9]), Some((accounts.eve, 1)));
+            assert_eq!(vesting.find_by_tx_hash(vec![8]), Some((accounts.eve, 2)));
+        }
+
+        /// We test removing several beneficiaries in one call.
+        #[ink::test]
+        fn remove_vested_balances_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balances(vec![(accounts.bob, 100), (accounts.charlie, 40), (accounts.django, 10)], None).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.charlie);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+
+            // Charlie has a pending request and is kept unless forced
+            let removed = vesting.remove_vested_balances(vec![accounts.bob, accounts.eve, accounts.django, accounts.charlie], false, None).unwrap();
+            assert_eq!(removed[3].result, Err(Error::VestedBalanceHasActivity { address: accounts.charlie, requested_balance: 20, transferred_balance: 0 }));
+            assert!(removed[0].result.is_ok());
+            assert_eq!(removed[1].result, Err(Error::VestedBalanceNotFound { address: accounts.eve }));
+            assert!(removed[2].result.is_ok());
+            assert_eq!(vesting.get_all_vested_balance().len(), 1);
+            assert_eq!(vesting.get_totals().original_balance, 40);
+            vesting.remove_vested_balance(accounts.charlie, true, None).unwrap();
+            assert!(vesting.get_vested_balance(accounts.charlie).is_none());
+
+            // Removed balances are archived until purged
+            assert_eq!(vesting.get_archived(accounts.bob).unwrap().original_balance, 100);
+            vesting.restore_archived(accounts.bob).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().liquid_balance, 50);
+            assert_eq!(vesting.get_totals().original_balance, 100);
+            assert!(vesting.get_archived(accounts.bob).is_none());
+
+            // Removing an address again keeps its earlier archive
+            vesting.add_vested_balance(accounts.django, 60, None).unwrap();
+            vesting.remove_vested_balance(accounts.django, false, None).unwrap();
+            let records = vesting.get_archived_records(accounts.django);
+            assert_eq!(records.iter().map(|v| v.original_balance).collect::<Vec<_>>(), vec![10, 60]);
+            vesting.restore_archived(accounts.django).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.django).unwrap().original_balance, 60);
+            assert_eq!(vesting.get_archived(accounts.django).unwrap().original_balance, 10);
+            vesting.remove_vested_balance(accounts.django, false, None).unwrap();
+
+            vesting.purge_archived(accounts.django).unwrap();
+            assert!(vesting.get_archived(accounts.django).is_none());
+            assert!(vesting.get_archived_records(accounts.django).is_empty());
+        }
+
+        /// We test the single schedule and paginated summary views.
+        #[ink::test]
+        fn listing_views_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balances(vec![(accounts.bob, 100), (accounts.charlie, 40), (accounts.django, 10)], None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.charlie, 1).unwrap().status, ScheduleStatus::Liquid);
+            assert!(vesting.get_schedule(accounts.charlie, 3).is_none());
+
+            let page = vesting.get_summaries(1, 1);
+            assert_eq!(page, vec![VestedSummary {
+                address: accounts.charlie,
+                original: 40,
+                frozen: 20,
+                liquid: 20,
+                requested: 0,
+                transferred: 0,
+                schedule_count: 2,
+                version: vesting.get_vested_balance(accounts.charlie).unwrap().version,
+            }]);
+            assert_eq!(vesting.get_summaries(2, 5).len(), 1);
+            assert!(vesting.get_summaries(3, 5).is_empty());
+
+            let balances = vesting.get_balances_for(vec![accounts.django, accounts.eve, accounts.charlie]);
+            assert_eq!(balances.len(), 3);
+            assert_eq!(balances[0].as_ref().unwrap().original, 10);
+            assert!(balances[1].is_none());
+            assert_eq!(balances[2], page.into_iter().next());
+        }
+
+        /// We test the storage deposit estimate grows with beneficiaries and schedules.
+        #[ink::test]
+        fn estimate_storage_deposit_works() {
+            let mut vesting = Vesting::new(1, 2).unwrap();
+
+            assert_eq!(vesting.estimate_storage_deposit(10, 4), 0);
+            vesting.set_deposit_per_byte(2).unwrap();
+
+            let schedule_size = VestedBalanceSchedule::default().encoded_size() as u128;
+            assert_eq!(vesting.estimate_storage_deposit(0, 4), 0);
+            assert_eq!(vesting.estimate_storage_deposit(3, 4), 3 * vesting.estimate_storage_deposit(1, 4));
+            assert_eq!(vesting.estimate_storage_deposit(1, 5) - vesting.estimate_storage_deposit(1, 4), 2 * schedule_size);
+        }
+
+        /// We test the owner rotation waits for the delay and can be cancelled meanwhile.
+        #[ink::test]
+        fn owner_rotation_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_guardian(Some(accounts.frank)).unwrap();
+            vesting.set_owner_rotation_delay(1_000).unwrap();
+            vesting.set_owner_rotation_delay(10).unwrap();
+            assert_eq!(vesting.owner_rotation_delay, 1_000);
+
+            // The guardian cannot become the owner
+            vesting.propose_owner(accounts.frank).unwrap();
+            assert_error(Error::GuardianIsOwner);
+            assert_eq!(vesting.get_pending_owner(), None);
+
+            // The guardian cancels a rotation proposed with a compromised key
+            vesting.propose_owner(accounts.eve).unwrap();
+            set_caller(accounts.frank);
+            vesting.cancel_owner_rotation().unwrap();
+            assert_eq!(vesting.get_pending_owner(), None);
+
+            set_caller(accounts.alice);
+            vesting.propose_owner(accounts.bob).unwrap();
+            assert_eq!(vesting.get_pending_owner(), Some((accounts.bob, 1_000)));
+
+            set_caller(accounts.bob);
+            vesting.accept_owner().unwrap();
+            assert_eq!(vesting.get_vesting_info().2, accounts.alice);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            vesting.accept_owner().unwrap();
+            assert_eq!(vesting.get_vesting_info().2, accounts.bob);
+            assert_eq!(vesting.get_pending_owner(), None);
+        }
+
+        /// We test guardian changes need the guardian or wait for the owner rotation delay.
+        #[ink::test]
+        fn guardian_change_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_guardian(Some(accounts.frank)).unwrap();
+            assert_eq!(vesting.guardian, Some(accounts.frank));
+
+            // The owner cannot be its own guardian
+            set_caller(accounts.frank);
+            vesting.set_guardian(Some(accounts.alice)).unwrap();
+            assert_error(Error::GuardianIsOwner);
+            assert_eq!(vesting.guardian, Some(accounts.frank));
+            set_caller(accounts.alice);
+
+            // Without a delay the owner cannot replace the guardian alone
+            vesting.set_guardian(None).unwrap();
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.guardian, Some(accounts.frank));
+
+            // With a delay the owner's change waits for it
+            vesting.set_owner_rotation_delay(1_000).unwrap();
+            vesting.set_guardian(Some(accounts.eve)).unwrap();
+            assert_eq!(last_status(), Some(VestingStatus::EmitSuccess(Success::GuardianChangeProposed)));
+            assert_eq!(vesting.get_pending_guardian(), Some((Some(accounts.eve), 1_000)));
+            vesting.set_guardian(Some(accounts.eve)).unwrap();
+            assert_error(Error::GuardianChangeNotReady { activates_at: 1_000 });
+
+            // The guardian changes it right away, dropping the owner's proposal
+            set_caller(accounts.frank);
+            vesting.set_guardian(Some(accounts.django)).unwrap();
+            assert_eq!(vesting.guardian, Some(accounts.django));
+            assert_eq!(vesting.get_pending_guardian(), None);
+
+            set_caller(accounts.alice);
+            vesting.set_guardian(Some(accounts.eve)).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            vesting.set_guardian(Some(accounts.eve)).unwrap();
+            assert_eq!(vesting.guardian, Some(accounts.eve));
+            assert_eq!(vesting.get_pending_guardian(), None);
+        }
+
+        /// We test the backup claims the position only after the beneficiary went inactive.
+        #[ink::test]
+        fn backup_claim_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            let price_gate = PriceGate { threshold: 10, observations: 1, twap_window: None };
+            vesting.set_price_gate(accounts.bob, 1, Some(price_gate.clone())).unwrap();
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.django), 40).unwrap();
+            vesting.reschedule_grant(accounts.bob, vec![(2, 2_000)]).unwrap();
+            vesting.suspend_vesting(accounts.bob).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.set_backup(Some(accounts.eve), 1_000).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(800);
+            vesting.heartbeat().unwrap();
+
+            // The heartbeat restarted the inactivity period
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_500);
+            set_caller(accounts.charlie);
+            vesting.claim_as_backup(accounts.bob).unwrap();
+            set_caller(accounts.eve);
+            vesting.claim_as_backup(accounts.bob).unwrap();
+            assert!(vesting.get_vested_balance(accounts.bob).is_some());
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_800);
+            vesting.claim_as_backup(accounts.bob).unwrap();
+            assert!(vesting.get_vested_balance(accounts.bob).is_none());
+            assert_eq!(vesting.get_vested_balance(accounts.eve).unwrap().frozen_balance, 100);
+            assert_eq!(vesting.get_top_holders(1), vec![(accounts.eve, 100)]);
+            assert!(vesting.get_backup(accounts.bob).is_none());
+
+            // The schedule conditions, the escrow, the suspension and the amendments move with the position
+            assert_eq!(vesting.get_price_gate(accounts.eve, 1), Some((price_gate, false)));
+            assert_eq!(vesting.get_price_gate(accounts.bob, 1), None);
+            assert_eq!(vesting.get_grant_escrow(accounts.eve).unwrap().escrow, accounts.django);
+            assert_eq!(vesting.get_grant_escrow(accounts.bob), None);
+            assert!(vesting.suspensions.contains(accounts.eve));
+            assert!(!vesting.suspensions.contains(accounts.bob));
+            assert_eq!(vesting.get_amendments(accounts.eve).len(), 1);
+            assert!(vesting.get_amendments(accounts.bob).is_empty());
+        }
+
+        /// We test recovery guardians move a position after the challenge period.
+        #[ink::test]
+        fn social_recovery_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.set_recovery_guardians(vec![accounts.charlie, accounts.django, accounts.eve], 2, 500).unwrap();
+
+            set_caller(accounts.charlie);
+            vesting.vote_recovery(accounts.bob, accounts.frank).unwrap();
+            set_caller(accounts.django);
+            vesting.vote_recovery(accounts.bob, accounts.eve).unwrap();
+            assert_eq!(vesting.get_recovery(accounts.bob).1.unwrap().agreed, None);
+
+            vesting.vote_recovery(accounts.bob, accounts.frank).unwrap();
+            assert_eq!(vesting.get_recovery(accounts.bob).1.unwrap().agreed, Some((accounts.frank, 500)));
+
+            // Nothing moves during the challenge period
+            vesting.execute_recovery(accounts.bob).unwrap();
+            assert!(vesting.get_vested_balance(accounts.bob).is_some());
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500);
+            vesting.execute_recovery(accounts.bob).unwrap();
+            assert!(vesting.get_vested_balance(accounts.bob).is_none());
+            assert_eq!(vesting.get_vested_balance(accounts.frank).unwrap().original_balance, 100);
+            assert_eq!(vesting.get_recovery(accounts.frank).0.unwrap().threshold, 2);
+        }
+
+        /// We test a suspended beneficiary does not unlock and its dates shift on resume.
+        #[ink::test]
+        fn suspension_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_unlock_time(2, 1_000).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            vesting.suspend_vesting(accounts.bob).unwrap();
+
+            vesting.thaw_vested_balances(1, None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().liquid_balance, 0);
+            assert_eq!(vesting.get_vested_balance(accounts.charlie).unwrap().liquid_balance, 50);
+            let thawed = vesting.thaw_schedules(vec![(accounts.bob, 1)], None).unwrap();
+            assert_eq!(thawed[0].result, Err(Error::VestingSuspended { address: accounts.bob }));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(400);
+            vesting.resume_vesting(accounts.bob, true).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().unlock_time, Some(1_400));
+            vesting.thaw_vested_balances(1, None).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().liquid_balance, 50);
+        }
+
+        /// We test rescheduling only touches frozen schedules.
+        #[ink::test]
+        fn reschedule_grant_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            // A liquid schedule rejects the whole call
+            vesting.reschedule_grant(accounts.bob, vec![(2, 5_000), (1, 5_000)]).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().unlock_time, None);
+
+            vesting.reschedule_grant(accounts.bob, vec![(2, 5_000)]).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().unlock_time, Some(5_000));
+        }
+
+        /// We test that adverse amendments wait for the beneficiary.
+        #[ink::test]
+        fn amendments_need_consent_when_adverse() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.reschedule_grant(accounts.bob, vec![(1, 5_000)]).unwrap();
+
+            // A later unlock is adverse
+            let amendment_id = vesting.reschedule_grant(accounts.bob, vec![(1, 9_000)]).unwrap().unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().unlock_time, Some(5_000));
+
+            let balance = vesting.get_schedule(accounts.bob, 1).unwrap().schedule_balance;
+            let reduction = vesting.propose_amendment(accounts.bob,
+                AmendmentChange::ScheduleAmounts(vec![(1, balance - 10)])).unwrap().unwrap();
+
+            // Only the beneficiary can accept
+            vesting.accept_amendment(amendment_id).unwrap();
+            assert!(vesting.get_amendment(amendment_id).is_some());
+
+            set_caller(accounts.bob);
+            vesting.accept_amendment(amendment_id).unwrap();
+            vesting.accept_amendment(reduction).unwrap();
+            assert!(vesting.get_amendment(amendment_id).is_none());
+
+            let schedule = vesting.get_schedule(accounts.bob, 1).unwrap();
+            assert_eq!(schedule.unlock_time, Some(9_000));
+            assert_eq!(schedule.schedule_balance, balance - 10);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().original_balance, 90);
+
+            let history = vesting.get_amendments(accounts.bob);
+            assert_eq!(history.len(), 3);
+            assert_eq!(history[0].accepted_by, None);
+            assert_eq!(history[2].accepted_by, Some(accounts.bob));
+        }
+
+        /// Mock pallet-assets chain extension answering the total supply, or the held balance when asked for a holder
+        struct MockAssets {
+            total_supply: Balance,
+            held: Balance,
+            status_code: u32,
+        }
+
+        impl ink::env::test::ChainExtension for MockAssets {
+            fn func_id(&self) -> u32 {
+                0x5603
+            }
+
+            fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                let input: Vec<u8> = scale::Decode::decode(&mut &input[..]).unwrap();
+                let (_, holder): (u128, Option<AccountId>) = scale::Decode::decode(&mut &input[..]).unwrap();
+                if self.status_code == 0 {
+                    let supply = if holder.is_some() { self.held } else { self.total_supply };
+                    output.extend_from_slice(&supply.encode());
+                }
+                self.status_code
+            }
+        }
+
+        /// We test that grants cannot oblige more than the asset supply.
+        #[ink::test]
+        fn asset_supply_caps_grants() {
+            let (accounts, mut vesting) = setup();
+
+            ink::env::test::register_chain_extension(MockAssets { total_supply: 150, held: 100, status_code: 0 });
+            vesting.set_supply_check(Some(SupplyScope::Total)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+
+            let results = vesting.add_vested_balances(vec![(accounts.charlie, 60), (accounts.django, 50)], None).unwrap();
+            assert_eq!(results[0].result, Err(Error::AssetSupplyExceeded { supply: 150, obligations: 160 }));
+            assert_eq!(results[1].result, Ok(Success::VestedBalanceAdded));
+
+            // Raising a schedule is checked as well
+            vesting.propose_amendment(accounts.bob, AmendmentChange::ScheduleAmounts(vec![(1, 60)])).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().schedule_balance, 50);
+
+            // The owner-held balance is checked when asked
+            vesting.set_supply_check(Some(SupplyScope::OwnerHeld)).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 10, None).unwrap();
+            assert_error(Error::AssetSupplyExceeded { supply: 100, obligations: 160 });
+
+            // A supply that cannot be read is reported
+            ink::env::test::register_chain_extension(MockAssets { total_supply: 150, held: 100, status_code: 5 });
+            vesting.add_vested_balance(accounts.charlie, 10, None).unwrap();
+            assert_error(Error::AssetSupplyUnavailable { error: ExtensionError::Other });
+
+            vesting.set_supply_check(None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 60, None).unwrap();
+            assert!(vesting.get_vested_balance(accounts.charlie).is_some());
+        }
+
+        /// Mock pallet-assets chain extension answering the metadata of the asset
+        struct MockAssetMetadata {
+            metadata: crate::AssetDetails,
+            status_code: u32,
+        }
+
+        impl ink::env::test::ChainExtension for MockAssetMetadata {
+            fn func_id(&self) -> u32 {
+                0x5604
+            }
+
+            fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                if self.status_code == 0 {
+                    output.extend_from_slice(&self.metadata.encode());
+                }
+                self.status_code
+            }
+        }
+
+        /// We test decimal-adjusted amounts and the minimum balance check.
+        #[ink::test]
+        fn asset_metadata_works() {
+            let (accounts, mut vesting) = setup();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(7);
+            ink::env::test::register_chain_extension(MockAssetMetadata {
+                metadata: (b"Acme".to_vec(), b"ACM".to_vec(), 2, 10),
+                status_code: 0,
+            });
+            vesting.refresh_asset_metadata().unwrap();
+            assert_eq!(vesting.get_cached_asset_metadata(), AssetMetadata {
+                name: b"Acme".to_vec(),
+                symbol: b"ACM".to_vec(),
+                decimals: 2,
+                min_balance: 10,
+                refreshed_at: 7,
+            });
+            assert_eq!(vesting.get_asset_metadata(), (2, 10));
+
+            vesting.add_vested_balance(accounts.bob, 1_002, None).unwrap();
+            let schedules = vesting.get_decimal_schedules(accounts.bob);
+            assert_eq!(schedules[1].1, DecimalAmount { raw: 501, whole: 5, fraction: 1, decimals: 2 });
+            assert_eq!(vesting.to_decimal_amount(1_002).whole, 10);
+
+            // Dust schedules could not be paid out
+            assert_eq!(vesting.add_vested_balance(accounts.charlie, 10, None), Ok(Vec::new()));
+            assert_error(Error::BelowMinimumBalance { schedule_number: 1, amount: 5, min_balance: 10 });
+
+            vesting.set_asset_metadata(39, 0).unwrap();
+            assert_error(Error::InvalidAssetDecimals { decimals: 39 });
+            ink::env::test::register_chain_extension(MockAssetMetadata {
+                metadata: (Vec::new(), Vec::new(), 39, 0),
+                status_code: 0,
+            });
+            vesting.refresh_asset_metadata().unwrap();
+            assert_error(Error::InvalidAssetDecimals { decimals: 39 });
+            ink::env::test::register_chain_extension(MockAssetMetadata {
+                metadata: (Vec::new(), Vec::new(), 0, 0),
+                status_code: 5,
+            });
+            vesting.refresh_asset_metadata().unwrap();
+            assert_error(Error::AssetMetadataUnavailable { error: ExtensionError::Other });
+            assert_eq!(vesting.get_asset_metadata(), (2, 10));
+
+            // The owner can still set the decimals and minimum balance directly
+            vesting.set_asset_metadata(3, 1).unwrap();
+            assert_eq!(vesting.get_cached_asset_metadata().name, b"Acme".to_vec());
+            assert_eq!(vesting.get_asset_metadata(), (3, 1));
+        }
+
+        /// We test the position report.
+        #[ink::test]
+        fn position_report_works() {
+            let (accounts, mut vesting) = setup();
+
+            assert_eq!(vesting.get_position_report(accounts.bob), None);
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![7], None).unwrap();
+
+            let report = vesting.get_position_report(accounts.bob).unwrap();
+            assert_eq!(report.remaining.raw, 50);
+            assert_eq!(report.transferred.raw, 50);
+            assert_eq!(report.schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(report.schedules[0].recipient, Some(accounts.charlie));
+            assert_eq!(report.schedules[0].settlement_hash, Some(vec![7]));
+            assert_eq!(report.schedules[1].status, ScheduleStatus::Frozen);
+            assert_eq!(report.schedules[1].settlement_hash, None);
+        }
+
+        /// We test the atomic multicall.
+        #[ink::test]
+        fn multicall_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+
+            let results = vesting.multicall(vec![
+                VestingCall::AddVestedBalance { address: accounts.charlie, original_balance: 40 },
+                VestingCall::AmendGrant { address: accounts.charlie, change: AmendmentChange::ScheduleAmounts(vec![(2, 30)]) },
+                VestingCall::ThawSchedule { address: accounts.charlie, schedule_number: 1 },
+                VestingCall::ForfeitSchedule { address: accounts.bob, schedule_number: 2 },
+            ]).unwrap();
+            assert_eq!(results, vec![
+                Success::VestedBalanceAdded,
+                Success::AmendmentApplied,
+                Success::VestedBalanceScheduleThawed,
+                Success::VestedBalanceScheduleForfeited,
+            ]);
+            assert_eq!(vesting.get_schedule(accounts.charlie, 1).unwrap().status, ScheduleStatus::Liquid);
+            assert_eq!(vesting.get_schedule(accounts.charlie, 2).unwrap().schedule_balance, 30);
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().status, ScheduleStatus::Forfeited);
+
+            // A failing call fails the whole multicall, the chain reverts the calls before it
+            let result = vesting.multicall(vec![
+                VestingCall::ThawSchedule { address: accounts.bob, schedule_number: 1 },
+                VestingCall::ThawSchedule { address: accounts.django, schedule_number: 1 },
+            ]);
+            assert_eq!(result, Err(Error::VestedBalanceNotFound { address: accounts.django }));
+
+            // The best-effort variant carries on after a failing call
+            let results = vesting.best_effort_multicall(vec![
+                VestingCall::ThawSchedule { address: accounts.django, schedule_number: 1 },
+                VestingCall::RemoveVestedBalance { address: accounts.charlie, force: false },
+            ]).unwrap();
+            assert_eq!(results[0].result, Err(Error::VestedBalanceNotFound { address: accounts.django }));
+            assert_eq!(results[1].result, Ok(Success::VestedBalanceRemoved));
+            assert!(vesting.get_vested_balance(accounts.charlie).is_none());
+        }
+
+        /// We test the thaw simulation against the actual thaw.
+        #[ink::test]
+        fn simulate_thaw_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 40, None).unwrap();
+            vesting.add_vested_balance(accounts.django, 20, None).unwrap();
+            vesting.suspend_vesting(accounts.django).unwrap();
+
+            let simulation = vesting.simulate_thaw(2);
+            assert_eq!(simulation, SimulationResult {
+                schedule_count: 2,
+                total_amount: 70,
+                beneficiaries: vec![(accounts.bob, 50), (accounts.charlie, 20)],
+                suspended: vec![accounts.django],
+            });
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().status, ScheduleStatus::Frozen);
+
+            assert_eq!(vesting.thaw_vested_balances(2, None).unwrap(), vec![
+                BatchItemResult { index: 0, result: Ok(Success::VestedBalanceScheduleThawed) },
+                BatchItemResult { index: 1, result: Ok(Success::VestedBalanceScheduleThawed) },
+                BatchItemResult { index: 2, result: Err(Error::VestingSuspended { address: accounts.django }) },
+            ]);
+            assert_eq!(simulation.schedule_count, 2);
+            assert_eq!(vesting.simulate_thaw(2).total_amount, 0);
+        }
+
+        /// We test the health report.
+        #[ink::test]
+        fn health_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_request_ttl(Some(100)).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.reschedule_grant(accounts.bob, vec![(2, 50)]).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(200);
+            let health = vesting.health();
+            assert_eq!(health.obligations, 100);
+            assert!(health.solvent);
+            assert_eq!(health.overdue_unlocks, 1);
+            assert_eq!(health.stale_requests, 1);
+            assert_eq!(health.suspended, 0);
+
+            vesting.suspend_vesting(accounts.bob).unwrap();
+            let health = vesting.health();
+            assert_eq!(health.suspended, 1);
+            assert_eq!(health.overdue_unlocks, 0);
+            vesting.resume_vesting(accounts.bob, false).unwrap();
+            assert_eq!(vesting.health().suspended, 0);
+        }
+
+        /// We test the synthetic data generator.
+        #[cfg(feature = "bench")]
+        #[ink::test]
+        fn bench_works() {
+            let mut vesting = Vesting::new(1, 2).unwrap();
+
+            assert_eq!(vesting.bench_populate(20, 3, 10).unwrap(), 20);
+            assert_eq!(vesting.get_totals().frozen_balance, 600);
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap().len(), 20);
+
+            vesting.bench_approve_last(2).unwrap();
+            assert_eq!(vesting.get_totals().transferred_balance, 10);
+        }
+
+        /// We test a grant from addition to the last transfer.
+        #[ink::test]
+        fn full_lifecycle_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            assert_eq!(last_status(), Some(VestingStatus::EmitSuccess(Success::VestedBalanceAdded)));
+
+            for schedule_number in 1..=2 {
+                set_caller(accounts.alice);
+                vesting.thaw_vested_balances(schedule_number, None).unwrap();
+
+                set_caller(accounts.bob);
+                vesting.request_transfer(schedule_number, accounts.charlie, Vec::new()).unwrap();
+
+                set_caller(accounts.alice);
+                vesting.approve_transfer(accounts.bob, schedule_number, vec![schedule_number as u8], None).unwrap();
+                assert_eq!(last_status(), Some(VestingStatus::EmitSuccess(Success::VestedBalanceScheduleApproved)));
+            }
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.transferred_balance, 100);
+            assert_eq!(vested_balance.frozen_balance + vested_balance.liquid_balance + vested_balance.requested_balance, 0);
+            assert!(vesting.verify_balances(accounts.bob));
+            assert_eq!(vesting.get_totals().transferred_balance, 100);
+
+            // Fully transferred balances are accounting data, removal must be forced
+            vesting.remove_vested_balance(accounts.bob, false, None).unwrap();
+            assert_error(Error::VestedBalanceHasActivity { address: accounts.bob, requested_balance: 0, transferred_balance: 100 });
+            vesting.remove_vested_balance(accounts.bob, true, None).unwrap();
+            assert_eq!(vesting.get_archived(accounts.bob).unwrap().transferred_balance, 100);
+        }
+
+        /// We test that owner messages reject other callers.
+        #[ink::test]
+        fn owner_messages_reject_other_callers() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+
+            set_caller(accounts.bob);
+
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.add_vested_balances(vec![(accounts.charlie, 100)], None).unwrap(), Vec::new());
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap(), Vec::new());
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.thaw_schedules(vec![(accounts.bob, 1)], None).unwrap(), Vec::new());
+            assert_error(Error::BadOrigin);
+            vesting.set_request_ttl(Some(1)).unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.set_fee_bps(1).unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.set_supply_check(Some(SupplyScope::Total)).unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.refresh_asset_metadata().unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.set_asset_metadata(1, 1).unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.suspend_vesting(accounts.bob).unwrap();
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.propose_amendment(accounts.bob, AmendmentChange::UnlockTimes(vec![(1, None)])).unwrap(), None);
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.multicall(vec![VestingCall::ThawSchedule { address: accounts.bob, schedule_number: 1 }]).unwrap(), Vec::new());
+            assert_error(Error::BadOrigin);
+            vesting.repair_balances(accounts.bob).unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.remove_vested_balance(accounts.bob, true, None).unwrap();
+            assert_error(Error::BadOrigin);
+
+            // Nothing changed
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.frozen_balance, 100);
+            assert_eq!(vested_balance.version, 0);
+            assert!(vesting.get_vested_balance(accounts.charlie).is_none());
+            assert_eq!(vesting.get_cached_asset_metadata(), AssetMetadata::default());
+        }
+
+        /// We test that a beneficiary cannot be added twice.
+        #[ink::test]
+        fn duplicate_add_is_rejected() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.bob, 200, None).unwrap();
+            assert_error(Error::VestedBalanceAlreadyExist { address: accounts.bob });
+
+            let results = vesting.add_vested_balances(vec![(accounts.charlie, 10), (accounts.charlie, 20)], None).unwrap();
+            assert_eq!(results[0].result, Ok(Success::VestedBalanceAdded));
+            assert_eq!(results[1].result, Err(Error::VestedBalanceAlreadyExist { address: accounts.charlie }));
+
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().original_balance, 100);
+            assert_eq!(vesting.get_vested_balance(accounts.charlie).unwrap().original_balance, 10);
+            assert_eq!(vesting.get_totals().frozen_balance, 110);
+        }
+
+        /// We test that schedules only move along the allowed status transitions.
+        #[ink::test]
+        fn status_transitions_are_checked() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+
+            // Frozen schedules cannot be requested or approved
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap(), None);
+            assert_error(Error::VestedBalanceScheduleNotLiquid { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Frozen });
+            vesting.request_transfer(3, accounts.charlie, Vec::new()).unwrap();
+            assert_error(Error::VestedBalanceScheduleNotFound { address: accounts.bob, schedule_number: 3 });
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_error(Error::VestedBalanceScheduleNotRequested { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Frozen });
+
+            // Liquid schedules cannot be thawed again
+            vesting.thaw_vested_balances(1, None).unwrap();
+            let results = vesting.thaw_schedules(vec![(accounts.bob, 1)], None).unwrap();
+            assert_eq!(results[0].result, Err(Error::VestedBalanceScheduleNotFrozen { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Liquid }));
+
+            // Transferred schedules cannot be forfeited or transferred again, a retried approval is a no-op
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            vesting.approve_transfer(accounts.bob, 1, vec![2], None).unwrap();
+            assert_eq!(last_status(), Some(VestingStatus::EmitSuccess(Success::AlreadyTransferred)));
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().particulars, vec![1]);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().transferred_balance, 50);
+
+            set_caller(accounts.bob);
+            let results = vesting.forfeit(vec![1]).unwrap();
+            assert_eq!(results[0].result, Err(Error::VestedBalanceScheduleNotForfeitable { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Transferred }));
+
+            // Unknown beneficiaries have nothing to request
+            set_caller(accounts.django);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            assert_error(Error::VestedBalanceNotFound { address: accounts.django });
+
+            assert!(vesting.verify_balances(accounts.bob));
+        }
+
+        /// We test that vested balances and reports round-trip through JSON.
+        #[ink::test]
+        fn serde_round_trip_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            let json = serde_json::to_string(&vested_balance).unwrap();
+            assert!(json.contains("\"address\":\"0x0202"));
+            assert_eq!(serde_json::from_str::<VestedBalance>(&json).unwrap(), vested_balance);
+
+            let report = vesting.get_position_report(accounts.bob).unwrap();
+            let json = serde_json::to_string(&report).unwrap();
+            assert_eq!(serde_json::from_str::<PositionReport>(&json).unwrap(), report);
+        }
+
+        /// We test that versioned views carry a stable version tag.
+        #[ink::test]
+        fn versioned_views_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 40, None).unwrap();
+
+            let versioned = vesting.get_vested_balance_versioned(accounts.bob).unwrap();
+            let VersionedVestedBalance::V2(vested_balance) = versioned.clone() else {
+                panic!("not the current shape");
+            };
+            assert_eq!(Some(vested_balance.clone()), vesting.get_vested_balance(accounts.bob));
+
+            // The version is the first encoded byte, V2 is 1
+            let encoded = versioned.encode();
+            assert_eq!(encoded[0], 1);
+            assert_eq!(<VersionedVestedBalance as scale::Decode>::decode(&mut &encoded[..]).unwrap(), versioned);
+
+            // V1 keeps the shape before the notes, it decodes from the current shape without them
+            let mut v1 = vec![0u8];
+            v1.extend(&encoded[1..encoded.len() - vested_balance.notes.encoded_size()]);
+            assert!(matches!(
+                <VersionedVestedBalance as scale::Decode>::decode(&mut &v1[..]).unwrap(),
+                VersionedVestedBalance::V1(v) if v.address == accounts.bob && v.original_balance == 100
+            ));
+
+            let export = vesting.export_vested_balances(1, 10);
+            assert_eq!(export.len(), 1);
+            assert!(matches!(&export[0], VersionedVestedBalance::V2(v) if v.address == accounts.charlie));
+
+            assert!(matches!(vesting.get_position_report_versioned(accounts.bob), Some(VersionedPositionReport::V1(_))));
+        }
+
+        /// We test that the state chunks add up to the state digest.
+        #[ink::test]
+        fn state_digest_works() {
+            let mut vesting = Vesting::new(1, 2).unwrap();
+
+            let empty_digest = vesting.get_state_digest();
+            for i in 0..20u8 {
+                vesting.add_vested_balance(AccountId::from([i + 10; 32]), 100, None).unwrap();
+            }
+            assert_eq!(vesting.get_state_chunk_count(), 2);
+            assert!(vesting.get_state_chunk(2).is_empty());
+
+            // Rebuild the digest from the chunks, as a syncing explorer would
+            let mut synced: Vec<VestedBalance> = Vec::new();
+            let mut chunk_hashes: Vec<Hash> = Vec::new();
+            for chunk_index in 0..vesting.get_state_chunk_count() {
+                let chunk = vesting.get_state_chunk(chunk_index);
+                let mut hash = [0u8; 32];
+                ink::env::hash_bytes::<Blake2x256>(&chunk, &mut hash);
+                chunk_hashes.push(Hash::from(hash));
+                synced.extend(<Vec<VestedBalance> as scale::Decode>::decode(&mut &chunk[..]).unwrap());
+            }
+            let mut digest = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&chunk_hashes, &mut digest);
+
+            assert_eq!(Hash::from(digest), vesting.get_state_digest());
+            assert_ne!(empty_digest, vesting.get_state_digest());
+            assert_eq!(synced, vesting.get_all_vested_balance());
+        }
+
+        /// We test that purging a settled account frees its storage.
+        #[ink::test]
+        fn purge_releases_storage() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_deposit_per_byte(2).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            vesting.remove_vested_balance(accounts.bob, true, None).unwrap();
+
+            // Schedule 2 is still requested
+            vesting.purge_archived(accounts.bob).unwrap();
+            assert_error(Error::VestedBalanceNotSettled { address: accounts.bob, requested_balance: 50, disputed_balance: 0 });
+            assert!(vesting.get_archived(accounts.bob).is_some());
+
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.charlie);
+            vesting.request_transfer(1, accounts.django, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.charlie, 1, vec![2], None).unwrap();
+            vesting.remove_vested_balance(accounts.charlie, true, None).unwrap();
+
+            let history_size = vesting.schedule_history.get((accounts.charlie, 1)).unwrap().encoded_size();
+            let archived_size = vesting.get_archived(accounts.charlie).unwrap().encoded_size();
+            let emitted = ink::env::test::recorded_events().count();
+            vesting.purge_archived(accounts.charlie).unwrap();
+            assert_eq!(last_status(), Some(VestingStatus::EmitSuccess(Success::ArchivedBalancePurged)));
+            assert_eq!(ink::env::test::recorded_events().count(), emitted + 2);
+
+            let released = ink::env::test::recorded_events()
+                .filter_map(|event| match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                    Ok(Event::StorageReleased(released)) => Some(released),
+                    _ => None,
+                })
+                .last()
+                .unwrap();
+            assert!(released.bytes as usize >= archived_size + history_size);
+            assert_eq!(released.deposit, released.bytes as Balance * 2);
+
+            assert!(vesting.get_archived(accounts.charlie).is_none());
+            assert!(vesting.get_schedule_history(accounts.charlie, 1).is_empty());
+
+            // Replays stay rejected
+            assert_eq!(vesting.find_by_tx_hash(vec![2]), Some((accounts.charlie, 1)));
+
+            // An account added again keeps the storage of its active balance
+            vesting.add_vested_balance(accounts.django, 100, None).unwrap();
+            vesting.remove_vested_balance(accounts.django, false, None).unwrap();
+            vesting.add_vested_balance(accounts.django, 100, None).unwrap();
+            vesting.purge_archived(accounts.django).unwrap();
+            assert_error(Error::VestedBalanceAlreadyExist { address: accounts.django });
+            assert!(vesting.get_archived(accounts.django).is_some());
+        }
+
+        /// We test the owner notes of a vested balance.
+        #[ink::test]
+        fn notes_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.set_notes(accounts.bob, b"HR-1024".to_vec()).unwrap();
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().notes, b"HR-1024".to_vec());
+
+            vesting.set_notes(accounts.bob, vec![0; MAX_NOTES_LENGTH + 1]).unwrap();
+            assert_error(Error::NotesTooLong { length: MAX_NOTES_LENGTH as u32 + 1 });
+            vesting.set_notes(accounts.charlie, Vec::new()).unwrap();
+            assert_error(Error::VestedBalanceNotFound { address: accounts.charlie });
+
+            // Only the owner writes notes, only the owner and the viewers read them
+            set_caller(accounts.bob);
+            vesting.set_notes(accounts.bob, Vec::new()).unwrap();
+            assert_error(Error::BadOrigin);
+            assert!(vesting.get_vested_balance(accounts.bob).unwrap().notes.is_empty());
+            assert!(vesting.my_vested_balance().unwrap().notes.is_empty());
+            assert!(vesting.get_all_vested_balance()[0].notes.is_empty());
+
+            set_caller(accounts.alice);
+            vesting.set_viewers(vec![accounts.eve]).unwrap();
+            set_caller(accounts.eve);
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().notes, b"HR-1024".to_vec());
+        }
+
+        /// We test viewers get no rights besides reading.
+        #[ink::test]
+        fn viewers_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.set_viewers(vec![accounts.eve]).unwrap();
+            assert_eq!(vesting.get_viewers(), vec![accounts.eve]);
+            assert!(vesting.is_viewer(accounts.alice));
+            assert!(vesting.is_viewer(accounts.eve));
+            assert!(!vesting.is_viewer(accounts.bob));
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            // Viewers cannot change anything
+            set_caller(accounts.eve);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_error(Error::BadOrigin);
+            vesting.set_viewers(Vec::new()).unwrap();
+            assert_error(Error::BadOrigin);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Requested);
+            assert_eq!(vesting.get_viewers(), vec![accounts.eve]);
+        }
+
+        /// We test the bulk views are gated when private.
+        #[ink::test]
+        fn privacy_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 200, None).unwrap();
+            vesting.set_viewers(vec![accounts.eve]).unwrap();
+            vesting.set_private(true).unwrap();
+            assert!(vesting.is_private());
+            assert_eq!(vesting.get_all_vested_balance().len(), 2);
+
+            // Viewers read everything
+            set_caller(accounts.eve);
+            assert_eq!(vesting.get_all_vested_balance().len(), 2);
+            assert_eq!(vesting.get_summaries(0, 10).len(), 2);
+            assert_eq!(vesting.get_top_holders(10).len(), 2);
+
+            // Beneficiaries only read their own position
+            set_caller(accounts.bob);
+            assert!(vesting.get_all_vested_balance().is_empty());
+            assert!(vesting.export_vested_balances(0, 10).is_empty());
+            assert!(vesting.get_state_chunk(0).is_empty());
+            assert!(vesting.get_summaries(0, 10).is_empty());
+            assert!(vesting.get_top_holders(10).is_empty());
+            let balances = vesting.get_balances_for(vec![accounts.bob, accounts.charlie]);
+            assert_eq!(balances[0].as_ref().map(|s| s.address), Some(accounts.bob));
+            assert!(balances[1].is_none());
+            assert!(vesting.my_vested_balance().is_some());
+            assert!(vesting.get_vested_balance(accounts.bob).is_some());
+            assert!(vesting.get_position_report(accounts.bob).is_some());
+            assert!(vesting.get_vested_balance(accounts.charlie).is_none());
+            assert!(vesting.get_position_report(accounts.charlie).is_none());
+            assert!(vesting.get_schedule_history(accounts.charlie, 1).is_empty());
+            assert!(vesting.get_receipts_for(accounts.charlie, 0, 10).is_empty());
+            assert!(vesting.get_statement(accounts.charlie, 0, u64::MAX).entries.is_empty());
+            assert!(vesting.get_unlocks_between(0, u64::MAX).iter().all(|(address, _, _)| *address == accounts.bob));
+            assert!(vesting.get_schedule(accounts.bob, 1).is_some());
+            assert!(vesting.get_schedule(accounts.charlie, 1).is_none());
+            assert!(vesting.get_decimal_schedules(accounts.charlie).is_empty());
+            assert!(vesting.get_archived(accounts.charlie).is_none());
+            assert!(vesting.get_archived_records(accounts.charlie).is_empty());
+            assert_eq!(vesting.simulate_thaw(1), SimulationResult::default());
+            assert!(vesting.get_beneficiaries_by_category(0).is_empty());
+            vesting.set_private(false).unwrap();
+            assert_error(Error::BadOrigin);
+
+            // Public again
+            set_caller(accounts.alice);
+            vesting.set_private(false).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(vesting.get_all_vested_balance().len(), 2);
+            assert!(vesting.get_vested_balance(accounts.charlie).is_some());
+            assert_eq!(vesting.get_top_holders(10).len(), 2);
+        }
+
+        /// We test committed recipients are only requested once revealed.
+        #[ink::test]
+        fn recipient_commitment_works() {
+            let (accounts, mut vesting) = setup();
+            let recipient = Recipient::Account(accounts.charlie);
+            let salt = [7u8; 32];
+            let mut commitment = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&(recipient, salt), &mut commitment);
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer_commit(1, Hash::from(commitment)).unwrap();
+            assert_error(Error::VestedBalanceScheduleNotLiquid { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Frozen });
+
+            set_caller(accounts.alice);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.reveal_recipient(1, recipient, salt, Vec::new()).unwrap();
+            assert_error(Error::RecipientCommitmentNotFound { address: accounts.bob, schedule_number: 1 });
+            vesting.request_transfer_commit(1, Hash::from(commitment)).unwrap();
+            assert_eq!(vesting.get_recipient_commitment(accounts.bob, 1), Some(Hash::from(commitment)));
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Liquid);
+
+            // The recipient and the salt must match the commitment
+            assert_eq!(vesting.reveal_recipient(1, Recipient::Account(accounts.django), salt, Vec::new()), Ok(None));
+            assert_error(Error::RecipientCommitmentMismatch { address: accounts.bob, schedule_number: 1 });
+            assert_eq!(vesting.reveal_recipient(1, recipient, [8u8; 32], Vec::new()), Ok(None));
+            assert_error(Error::RecipientCommitmentMismatch { address: accounts.bob, schedule_number: 1 });
+
+            vesting.reveal_recipient(1, recipient, salt, Vec::new()).unwrap().unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().recipient_address, Some(accounts.charlie));
+            assert_eq!(vesting.get_recipient_commitment(accounts.bob, 1), None);
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+        }
+
+        /// We test the program metadata.
+        #[ink::test]
+        fn program_metadata_works() {
+            let mut vesting = Vesting::new(1, 2).unwrap();
+
+            vesting.set_program_metadata(b"Acme vesting".to_vec(), b"https://acme.example/vesting".to_vec(), b"team".to_vec()).unwrap();
+            assert_eq!(vesting.get_program_metadata(),
+                (b"Acme vesting".to_vec(), b"https://acme.example/vesting".to_vec(), b"team".to_vec()));
+
+            vesting.set_program_metadata(Vec::new(), Vec::new(), vec![0; MAX_METADATA_LENGTH + 1]).unwrap();
+            assert_error(Error::MetadataTooLong { length: MAX_METADATA_LENGTH as u32 + 1 });
+            assert_eq!(vesting.get_program_metadata().2, b"team".to_vec());
+        }
+
+        /// We test that pledged schedules are locked until the lender releases them.
+        #[ink::test]
+        fn collateral_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            // Liquid schedules cannot be pledged, nothing is pledged then
+            set_caller(accounts.bob);
+            vesting.lock_as_collateral(vec![2, 1], accounts.eve).unwrap();
+            assert_error(Error::VestedBalanceScheduleNotFrozen { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Liquid });
+            assert_eq!(vesting.get_pledge(accounts.bob, 2), None);
+
+            // A schedule counts once towards the pledged amount
+            vesting.lock_as_collateral(vec![2, 2], accounts.eve).unwrap();
+            assert_error(Error::DuplicateScheduleNumber { schedule_number: 2 });
+            assert_eq!(vesting.get_pledge(accounts.bob, 2), None);
+
+            vesting.lock_as_collateral(vec![2], accounts.eve).unwrap();
+            assert_eq!(vesting.get_pledge(accounts.bob, 2), Some(accounts.eve));
+
+            // Pledged schedules stay put once thawed
+            set_caller(accounts.alice);
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap(), None);
+            assert_error(Error::SchedulePledged { address: accounts.bob, schedule_number: 2, lender: accounts.eve });
+            let results = vesting.forfeit(vec![2]).unwrap();
+            assert_eq!(results[0].result, Err(Error::SchedulePledged { address: accounts.bob, schedule_number: 2, lender: accounts.eve }));
+
+            // Only the lender releases
+            vesting.release_collateral(accounts.bob, vec![2]).unwrap();
+            assert_error(Error::ScheduleNotPledged { address: accounts.bob, schedule_number: 2 });
+            set_caller(accounts.eve);
+            vesting.release_collateral(accounts.bob, vec![2]).unwrap();
+            assert_eq!(vesting.get_pledge(accounts.bob, 2), None);
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap(), Some(1));
+        }
+
+        /// We test that large transfers of a grant with an escrow need the escrow approval.
+        #[ink::test]
+        fn escrow_approval_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.eve), 40).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            let request_id = vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap().unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_request(request_id, vec![1], None).unwrap();
+            assert_error(Error::EscrowApprovalRequired { request_id, escrow: accounts.eve });
+
+            // Only the escrow of the grant can approve
+            set_caller(accounts.django);
+            vesting.escrow_approve(request_id).unwrap();
+            assert_error(Error::BadOrigin);
+            set_caller(accounts.eve);
+            vesting.escrow_approve(request_id).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_request(request_id, vec![1], None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+
+            // Payout approvals need the escrow approval as well
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            let request_id = vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap().unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_payout(accounts.bob, 2, None).unwrap();
+            assert_error(Error::EscrowApprovalRequired { request_id, escrow: accounts.eve });
+            set_caller(accounts.bob);
+            vesting.cancel_request(request_id).unwrap();
+
+            // The owner cannot weaken or remove the escrow alone
+            set_caller(accounts.alice);
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.eve), 50).unwrap();
+            assert_error(Error::EscrowConsentRequired { escrow: accounts.eve });
+            vesting.set_grant_escrow(accounts.bob, None, 0).unwrap();
+            assert_error(Error::EscrowConsentRequired { escrow: accounts.eve });
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.alice), 40).unwrap();
+            assert_error(Error::EscrowConsentRequired { escrow: accounts.eve });
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.eve), 30).unwrap();
+            assert_eq!(vesting.get_grant_escrow(accounts.bob).unwrap().threshold, 30);
+
+            // Transfers up to the threshold only need the owner
+            set_caller(accounts.eve);
+            vesting.set_grant_escrow(accounts.bob, Some(accounts.eve), 50).unwrap();
+            assert_eq!(vesting.get_grant_escrow(accounts.bob).unwrap().threshold, 50);
+            set_caller(accounts.bob);
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 2, vec![2], None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().status, ScheduleStatus::Transferred);
+        }
+
+        /// We test failed native payouts are queued and can be retried.
+        #[ink::test]
+        fn payout_retry_works() {
+            let (accounts, mut vesting) = setup_with(0, 2);
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+
+            vesting.set_asset_kind(AssetKind::Native).unwrap();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 100);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie, 0);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            vesting.thaw_vested_balances(1, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+
+            // The escrow cannot cover the schedule, the payout is queued
+            vesting.native_pool = 30;
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![], None).unwrap();
+            let stuck = vesting.get_stuck_payouts();
+            assert_eq!(stuck.len(), 1);
+            assert_eq!((stuck[0].beneficiary, stuck[0].schedule_number, stuck[0].amount), (accounts.bob, 1, 50));
+            assert_eq!(stuck[0].last_error_code, 25);
+            let payout_id = stuck[0].payout_id;
+
+            // A failed retry counts the attempt
+            vesting.retry_payout(payout_id).unwrap();
+            assert_error(Error::InsufficientNativeEscrow { available: 30, required: 50 });
+            assert_eq!(vesting.get_stuck_payouts()[0].attempts, 2);
+            vesting.retry_payout(payout_id + 1).unwrap();
+            assert_error(Error::PayoutNotFound { payout_id: payout_id + 1 });
+
+            vesting.native_pool = 100;
+            vesting.retry_payout(payout_id).unwrap();
+            assert_eq!(last_status(), Some(VestingStatus::EmitSuccess(Success::PayoutRetried)));
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+            assert!(vesting.get_stuck_payouts().is_empty());
+            assert_eq!(
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie),
+                Ok(50)
+            );
+        }
+
+        /// Arguments of a pallet-vesting vested transfer (target, locked, per block, starting block)
+        type VestedTransfer = (AccountId, Balance, Balance, BlockNumber);
+
+        /// Mock pallet-vesting chain extension recording the created schedules
+        struct MockPalletVesting {
+            schedules: std::rc::Rc<std::cell::RefCell<Vec<VestedTransfer>>>,
+            status_code: u32,
+        }
+
+        impl ink::env::test::ChainExtension for MockPalletVesting {
+            fn func_id(&self) -> u32 {
+                0x5601
+            }
+
+            fn call(&mut self, input: &[u8], _output: &mut Vec<u8>) -> u32 {
+                let input: Vec<u8> = scale::Decode::decode(&mut &input[..]).unwrap();
+                if self.status_code == 0 {
+                    self.schedules.borrow_mut().push(scale::Decode::decode(&mut &input[..]).unwrap());
+                }
+                self.status_code
+            }
+        }
+
+        /// We test approved native transfers become pallet-vesting schedules of the recipient.
+        #[ink::test]
+        fn pallet_vesting_backend_works() {
+            let (accounts, mut vesting) = setup();
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+
+            // The pallet-vesting backend needs native vesting
+            let backend = EnforcementBackend::PalletVesting { unlock_blocks: 10, start_delay: 5 };
+            vesting.set_enforcement_backend(backend).unwrap();
+            assert_error(Error::InvalidEnforcementBackend);
+            vesting.set_asset_kind(AssetKind::Native).unwrap();
+            vesting.set_enforcement_backend(EnforcementBackend::PalletVesting { unlock_blocks: 0, start_delay: 5 }).unwrap();
+            assert_error(Error::InvalidEnforcementBackend);
+            vesting.set_enforcement_backend(backend).unwrap();
+            assert_eq!(vesting.get_enforcement_backend(), backend);
+
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 100);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+            set_caller(accounts.alice);
+
+            // A rejected schedule leaves the request queued for a retry
+            let schedules = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+            ink::env::test::register_chain_extension(MockPalletVesting { schedules: schedules.clone(), status_code: 2 });
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_error(Error::PalletVestingFailed {
+                recipient: accounts.charlie,
+                error: ExtensionError::AtMaxVestingSchedules,
+            });
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Requested);
+
+            ink::env::test::register_chain_extension(MockPalletVesting { schedules: schedules.clone(), status_code: 0 });
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            vesting.approve_transfer(accounts.bob, 2, vec![2], None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+            assert_eq!(vesting.get_native_info(), (AssetKind::Native, 0));
+            let block = ink::env::block_number::<ink::env::DefaultEnvironment>();
+            assert_eq!(*schedules.borrow(), vec![
+                (accounts.charlie, 50, 5, block + 5),
+                (accounts.charlie, 50, 5, block + 5),
+            ]);
+        }
+
+        /// Arguments of an XCM reserve transfer (asset id, amount, destination)
+        type ReserveTransfer = (Option<u128>, Balance, XcmDestination);
+
+        /// Mock XCM chain extension recording the reserve transfers
+        struct MockXcm {
+            transfers: std::rc::Rc<std::cell::RefCell<Vec<ReserveTransfer>>>,
+            status_code: u32,
+        }
+
+        impl ink::env::test::ChainExtension for MockXcm {
+            fn func_id(&self) -> u32 {
+                0x5602
+            }
+
+            fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                let input: Vec<u8> = scale::Decode::decode(&mut &input[..]).unwrap();
+                if self.status_code == 0 {
+                    self.transfers.borrow_mut().push(scale::Decode::decode(&mut &input[..]).unwrap());
+                    output.extend_from_slice(&[7; 32]);
+                }
+                self.status_code
+            }
+        }
+
+        /// We test approving a transfer requested to a sibling parachain dispatches an XCM reserve transfer.
+        #[ink::test]
+        fn xcm_transfer_works() {
+            let (accounts, mut vesting) = setup();
+            let destination = XcmDestination { para_id: 2000, account: [9; 32] };
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.set_recipient_pinning(accounts.bob, true).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_xcm_transfer(1, destination, Vec::new()).unwrap().unwrap();
+            assert_eq!(vesting.get_xcm_destination(accounts.bob, 1), Some(destination));
+            assert_eq!(vesting.get_recipient(accounts.bob, 1), Some(Recipient::Xcm(destination)));
+            set_caller(accounts.alice);
+
+            // XCM transfers are not left to the payout bots
+            assert_eq!(vesting.approve_payout(accounts.bob, 1, None).unwrap(), None);
+            assert_error(Error::XcmTransferRequired { address: accounts.bob, schedule_number: 1 });
+
+            let transfers = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+            ink::env::test::register_chain_extension(MockXcm { transfers: transfers.clone(), status_code: 4 });
+            vesting.approve_transfer(accounts.bob, 1, Vec::new(), None).unwrap();
+            assert_error(Error::XcmTransferFailed { para_id: 2000, error: ExtensionError::XcmSendFailed });
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Requested);
+
+            // The message hash settles the schedule
+            ink::env::test::register_chain_extension(MockXcm { transfers: transfers.clone(), status_code: 0 });
+            vesting.approve_transfer(accounts.bob, 1, Vec::new(), None).unwrap();
+            let schedule = vesting.get_schedule(accounts.bob, 1).unwrap();
+            assert_eq!(schedule.status, ScheduleStatus::Transferred);
+            assert_eq!(schedule.particulars, vec![7; 32]);
+            assert_eq!(schedule.recipient_address, Some(AccountId::from([9; 32])));
+            assert_eq!(vesting.get_xcm_destination(accounts.bob, 1), None);
+            assert_eq!(*transfers.borrow(), vec![(Some(1), 50, destination)]);
+
+            // The receipt keeps the destination and the foreign account is not pinned
+            assert_eq!(vesting.get_receipts_for(accounts.bob, 0, 10)[0].recipient, Recipient::Xcm(destination));
+            assert_eq!(vesting.get_vested_balance(accounts.bob).unwrap().pinned_recipient, None);
+
+            // The contract sent the transfer itself, a revert cannot take it back
+            vesting.set_guardian(Some(accounts.frank)).unwrap();
+            vesting.revert_approval(accounts.bob, 1, ScheduleStatus::Requested, Vec::new()).unwrap();
+            set_caller(accounts.frank);
+            vesting.revert_approval(accounts.bob, 1, ScheduleStatus::Requested, Vec::new()).unwrap();
+            assert_error(Error::PayoutNotRevertible { address: accounts.bob, schedule_number: 1 });
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+        }
+
+        /// We test transfers requested to an Ethereum-style address keep the address as their recipient.
+        #[ink::test]
+        fn eth_recipient_works() {
+            let (accounts, mut vesting) = setup();
+            let eth_address = [0xab; 20];
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer_to(1, Recipient::EthAddress(eth_address), Vec::new()).unwrap().unwrap();
+            vesting.request_transfer_to(2, Recipient::Account(accounts.charlie), Vec::new()).unwrap().unwrap();
+
+            // The schedule is paid to the mapped account of the address
+            let mut mapped = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&[&b"evm:"[..], &eth_address[..]].concat(), &mut mapped);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().recipient_address, Some(AccountId::from(mapped)));
+            assert_eq!(vesting.get_recipient(accounts.bob, 1), Some(Recipient::EthAddress(eth_address)));
+            assert_eq!(vesting.get_recipient(accounts.bob, 2), Some(Recipient::Account(accounts.charlie)));
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+            assert_eq!(vesting.get_recipient(accounts.bob, 1), Some(Recipient::EthAddress(eth_address)));
+        }
+
+        /// We test approvals and acknowledged payouts leave settlement receipts.
+        #[ink::test]
+        fn receipts_work() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer_to(2, Recipient::EthAddress([0xab; 20]), Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            let payout_id = vesting.approve_payout(accounts.bob, 2, None).unwrap().unwrap();
+            assert_eq!(vesting.get_receipts_for(accounts.bob, 0, 10).len(), 1);
+            vesting.ack_payout(payout_id, vec![2], None).unwrap();
+
+            let receipts = vesting.get_receipts_for(accounts.bob, 0, 10);
+            assert_eq!(receipts.len(), 2);
+            assert_eq!(receipts[0], Receipt {
+                receipt_id: 1,
+                beneficiary: accounts.bob,
+                schedule_number: 1,
+                recipient: Recipient::Account(accounts.charlie),
+                amount: 50,
+                asset_id: 1,
+                tx_hash: vec![1],
+                block: ink::env::block_number::<ink::env::DefaultEnvironment>(),
+                settled_at: ink::env::block_timestamp::<ink::env::DefaultEnvironment>(),
             });
+            assert_eq!(receipts[1].recipient, Recipient::EthAddress([0xab; 20]));
+            assert_eq!(receipts[1].tx_hash, vec![2]);
+            assert_eq!(vesting.get_receipt(2), Some(receipts[1].clone()));
+            assert_eq!(vesting.get_receipts_for(accounts.bob, 1, 10), vec![receipts[1].clone()]);
+            assert_eq!(vesting.get_receipt(3), None);
 
-            Ok(())
+            // Receipts survive later changes of the schedule
+            vesting.remove_vested_balance(accounts.bob, true, None).unwrap();
+            assert!(vesting.get_vested_balance(accounts.bob).is_none());
+            assert_eq!(vesting.get_receipts_for(accounts.bob, 0, 10), receipts);
         }
 
-        /// Get vesting information
-        #[ink(message)]
-        pub fn get_vesting_info(&self,) -> (u128, u8, AccountId) {
-            (
-                self.asset_id,
-                self.total_vested_schedule,
-                self.vesting_owner,
-            )
-        }
+        /// We test statements list the unlocks, requests and settlements of their period.
+        #[ink::test]
+        fn statement_works() {
+            let (accounts, mut vesting) = setup();
 
-        /// Add vested balances
-        #[ink(message)]
-        pub fn add_vested_balance(&mut self,
-            address: AccountId,
-            original_balance: u128,) -> Result<(), Error> {
-            
-            // Check the caller, it must be the owner
-            let caller = self.env().caller();
-            if self.env().caller() != self.vesting_owner {
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::BadOrigin),
-                });
-                return Ok(());
-            } 
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
 
-            // Check if the address already exist
-            if self.vested_balances.iter().any(|v| v.address == address)
-            {
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::VestedBalanceAlreadyExist),
-                });
-                return Ok(());
-            }
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(2_000);
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
 
-            // Compute for the vested balance schedules
-            let mut schedules: Vec<VestedBalanceSchedule> =
-                Vec::with_capacity(self.total_vested_schedule as usize);
+            let statement = vesting.get_statement(accounts.bob, 0, 1_999);
+            let kinds = statement.entries.iter().map(|e| (e.kind, e.schedule_number, e.at)).collect::<Vec<_>>();
+            assert_eq!(kinds, vec![
+                (StatementEntryKind::Unlock, 1, 1_000),
+                (StatementEntryKind::Request, 1, 1_000),
+            ]);
+            assert_eq!((statement.unlocked, statement.requested, statement.settled), (50, 50, 0));
+            assert_eq!(statement.entries[1].recipient, Some(Recipient::Account(accounts.charlie)));
 
-            let schedule_balance = original_balance / self.total_vested_schedule as u128;
+            let statement = vesting.get_statement(accounts.bob, 2_000, 3_000);
+            let kinds = statement.entries.iter().map(|e| (e.kind, e.schedule_number, e.at)).collect::<Vec<_>>();
+            assert_eq!(kinds, vec![
+                (StatementEntryKind::Unlock, 2, 2_000),
+                (StatementEntryKind::Settlement, 1, 2_000),
+            ]);
+            assert_eq!((statement.unlocked, statement.requested, statement.settled), (50, 0, 50));
+            assert_eq!(statement.entries[1].tx_hash, vec![1]);
+            assert!(vesting.get_statement(accounts.charlie, 0, 3_000).entries.is_empty());
+        }
 
-            for i in 1..=self.total_vested_schedule {
-                schedules.push(VestedBalanceSchedule {
-                    schedule_number: i,
-                    schedule_balance: schedule_balance,
-                    status: 0,                      // 0 = Frozen - Default status
-                    recipient_address: None,     // the address is the default recipient
-                    particulars: Vec::new(),
-                });
-            }
+        /// We test voting weights are read from snapshots of the frozen and liquid balances.
+        #[ink::test]
+        fn voting_weight_snapshots_work() {
+            let (accounts, mut vesting) = setup();
 
-            // Save the vested balance
-            self.vested_balances.push(VestedBalance {
-                address: address,
-                vested_balance_schedules: schedules,
-                original_balance: original_balance,
-                frozen_balance: original_balance,
-                liquid_balance: 0,
-                requested_balance: 0,
-                transferred_balance: 0,   
-            });
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 40, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            let first = vesting.take_snapshot().unwrap().unwrap();
 
-            self.env().emit_event(VestingEvent {
-                operator: caller,
-                status: VestingStatus::EmitSuccess(Success::VestedBalanceAdded),
-            });
+            // Transferred balances no longer count
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.bob, Vec::new()).unwrap();
+            assert_eq!(vesting.take_snapshot().unwrap(), None);
+            assert_error(Error::BadOrigin);
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![1], None).unwrap();
+            let second = vesting.take_snapshot().unwrap().unwrap();
 
-            Ok(())
+            assert_eq!(vesting.voting_weight_of(accounts.bob, first), 100);
+            assert_eq!(vesting.voting_weight_of(accounts.bob, second), 50);
+            assert_eq!(vesting.voting_weight_of(accounts.charlie, second), 40);
+            assert_eq!(vesting.voting_weight_of(accounts.django, second), 0);
+            assert_eq!(vesting.voting_weight_of(accounts.bob, second + 1), 0);
+            assert_eq!(vesting.get_snapshot(first).unwrap().total_weight, 140);
+            assert_eq!(vesting.get_snapshot(second).unwrap().total_weight, 90);
         }
 
-        /// Get a vested balance per address
-        #[ink(message)]
-        pub fn get_vested_balance(
-            &self,
-            address: AccountId,
-        ) -> Option<VestedBalance> {
-            self.vested_balances
-                .iter()
-                .find(|v| v.address == address)
-                .cloned()
-        }
+        /// We test delegated voting weight accrues to the delegate from the next snapshot.
+        #[ink::test]
+        fn weight_delegation_works() {
+            let (accounts, mut vesting) = setup();
 
-        /// Get all vested balances
-        #[ink(message)]
-        pub fn get_all_vested_balance(&self,) -> Vec<VestedBalance> {
-            self.vested_balances.clone()
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 40, None).unwrap();
+
+            // Only beneficiaries delegate
+            set_caller(accounts.eve);
+            vesting.delegate_weight(accounts.bob).unwrap();
+            assert_error(Error::VestedBalanceNotFound { address: accounts.eve });
+
+            set_caller(accounts.bob);
+            vesting.delegate_weight(accounts.django).unwrap();
+            set_caller(accounts.charlie);
+            vesting.delegate_weight(accounts.django).unwrap();
+            assert_eq!(vesting.get_delegate(accounts.bob), Some(accounts.django));
+            set_caller(accounts.alice);
+            let first = vesting.take_snapshot().unwrap().unwrap();
+
+            // Re-delegation replaces the delegate, self-delegation undelegates
+            set_caller(accounts.bob);
+            vesting.delegate_weight(accounts.charlie).unwrap();
+            set_caller(accounts.charlie);
+            vesting.delegate_weight(accounts.charlie).unwrap();
+            assert_eq!(vesting.get_delegate(accounts.charlie), None);
+            set_caller(accounts.alice);
+            let second = vesting.take_snapshot().unwrap().unwrap();
+
+            // Undelegation announces the change to indexers
+            set_caller(accounts.bob);
+            let emitted = ink::env::test::recorded_events().count();
+            vesting.undelegate_weight().unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), emitted + 2);
+            assert_eq!(vesting.get_delegate(accounts.bob), None);
+            set_caller(accounts.alice);
+            let third = vesting.take_snapshot().unwrap().unwrap();
+
+            assert_eq!(vesting.voting_weight_of(accounts.django, first), 140);
+            assert_eq!(vesting.voting_weight_of(accounts.bob, first), 0);
+            assert_eq!(vesting.voting_weight_of(accounts.charlie, second), 140);
+            assert_eq!(vesting.voting_weight_of(accounts.django, second), 0);
+            assert_eq!(vesting.voting_weight_of(accounts.bob, third), 100);
+            assert_eq!(vesting.voting_weight_of(accounts.charlie, third), 40);
+            assert_eq!(vesting.get_snapshot(third).unwrap().total_weight, 140);
         }
-    
-        /// Thaw frozen balances
-        #[ink(message)]
-        pub fn thaw_vested_balances(&mut self,
-            schedule_number: u8,) -> Result<(), Error> {
-            
-            // Check the caller, it must be the owner
-            let caller = self.env().caller();
-            if self.env().caller() != self.vesting_owner {
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::BadOrigin),
-                });
-                return Ok(());
-            } 
 
-            // Iterate all vested frozen balances on a given schedule number and thaw 
-            for vested_balance in self.vested_balances.iter_mut() {
+        /// We test thaws go through the governor once one is set.
+        #[ink::test]
+        fn governance_thaw_works() {
+            let (accounts, mut vesting) = setup();
 
-                // Change the status
-                for schedule in vested_balance.vested_balance_schedules.iter_mut() {
-                    if schedule.schedule_number == schedule_number && schedule.status == 0 {
-                        schedule.status = 1; // 1 = Liquid (thawed)
-                    }
-                }
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.set_governor(accounts.frank).unwrap();
+            assert_eq!(vesting.get_governor(), Some(accounts.frank));
 
-                // Calculate balances of the vested address
-                Self::calculate_balances(vested_balance);
-            }  
+            // The owner can no longer thaw or replace the governor
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap(), Vec::new());
+            assert_error(Error::GovernorRequired { governor: accounts.frank });
+            vesting.set_governor(accounts.alice).unwrap();
+            assert_error(Error::GovernorRequired { governor: accounts.frank });
+            vesting.execute_governance_action(GovernanceAction::ThawVestedBalances { schedule_number: 1 }).unwrap();
+            assert_error(Error::BadOrigin);
 
-            self.env().emit_event(VestingEvent {
-                operator: caller,
-                status: VestingStatus::EmitSuccess(Success::VestedBalanceScheduleThawed),
-            });
+            set_caller(accounts.frank);
+            let results = vesting.execute_governance_action(GovernanceAction::ThawVestedBalances { schedule_number: 1 }).unwrap();
+            assert_eq!(results, vec![BatchItemResult { index: 0, result: Ok(Success::VestedBalanceScheduleThawed) }]);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Liquid);
+            vesting.execute_governance_action(GovernanceAction::ThawSchedule { address: accounts.bob, schedule_number: 1 }).unwrap();
+            assert_error(Error::VestedBalanceScheduleNotFrozen { address: accounts.bob, schedule_number: 1, status: ScheduleStatus::Liquid });
 
-            Ok(())
+            // Earlier unlocks go through the governor as well
+            set_caller(accounts.alice);
+            vesting.set_unlock_time(2, 0).unwrap();
+            assert_error(Error::GovernorRequired { governor: accounts.frank });
+            vesting.reschedule_grant(accounts.bob, vec![(2, 0)]).unwrap();
+            assert_error(Error::GovernorRequired { governor: accounts.frank });
+            set_caller(accounts.django);
+            assert_eq!(vesting.process_due_unlocks(10).unwrap(), 0);
+            assert_error(Error::GovernorRequired { governor: accounts.frank });
+            set_caller(accounts.frank);
+            vesting.execute_governance_action(GovernanceAction::SetUnlockTime { schedule_number: 2, unlock_time: 0 }).unwrap();
+            assert_eq!(vesting.process_due_unlocks(10).unwrap(), 1);
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().status, ScheduleStatus::Liquid);
+
+            // Handing the thaws back to the owner
+            vesting.execute_governance_action(GovernanceAction::SetGovernor { governor: None }).unwrap();
+            set_caller(accounts.alice);
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            assert_eq!(vesting.thaw_vested_balances(2, None).unwrap().len(), 1);
         }
 
-        /// Request for transfer
-        #[ink(message)]
-        pub fn request_transfer(&mut self,
-            schedule_number: u8,
-            recipient_address: AccountId) -> Result<(), Error> {
+        /// We test price-gated schedules only thaw after enough observations above the threshold.
+        #[ink::test]
+        fn price_gate_works() {
+            let (accounts, mut vesting) = setup();
 
-            let caller = self.env().caller();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            vesting.set_price_oracle(Some(accounts.eve)).unwrap();
+            vesting.set_price_gate(accounts.bob, 1, Some(PriceGate { threshold: 10, observations: 0, twap_window: None })).unwrap();
+            assert_error(Error::InvalidPriceGate);
+            vesting.set_price_gate(accounts.bob, 1, Some(PriceGate { threshold: 10, observations: 2, twap_window: None })).unwrap();
 
-            // 1️. Find the caller's vested balance
-            if let Some(vested_balance) = self.vested_balances.iter_mut().find(|v| v.address == caller) {
+            // Only the oracle reports prices
+            vesting.report_price(20).unwrap();
+            assert_error(Error::BadOrigin);
+            set_caller(accounts.eve);
+            for (time, price) in [(1, 20), (2, 5), (3, 20)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(time);
+                vesting.report_price(price).unwrap();
+            }
+            assert_eq!(vesting.get_price_observations().len(), 3);
 
-                // 2️. Find the schedule in the caller's vested_balance
-                if let Some(schedule) = vested_balance.vested_balance_schedules.iter_mut()
-                    .find(|s| s.schedule_number == schedule_number) {
+            // Repeated reports at the same time are not counted
+            vesting.report_price(20).unwrap();
+            assert_error(Error::PriceAlreadyReported { reported_at: 3 });
+            assert_eq!(vesting.get_price_observations().len(), 3);
 
-                    // 3️. Ensure the schedule is liquid
-                    if schedule.status == 1 {
+            // One observation above the threshold is not enough
+            set_caller(accounts.alice);
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap(), vec![
+                BatchItemResult { index: 0, result: Err(Error::PriceGateClosed { address: accounts.bob, schedule_number: 1 }) },
+                BatchItemResult { index: 1, result: Ok(Success::VestedBalanceScheduleThawed) },
+            ]);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Frozen);
+            assert_eq!(vesting.get_schedule(accounts.charlie, 1).unwrap().status, ScheduleStatus::Liquid);
+            let results = vesting.thaw_schedules(vec![(accounts.bob, 1)], None).unwrap();
+            assert_eq!(results[0].result, Err(Error::PriceGateClosed { address: accounts.bob, schedule_number: 1 }));
 
-                        // Update the schedule
-                        schedule.status = 2; // Requested
-                        schedule.recipient_address = Some(recipient_address);
+            set_caller(accounts.eve);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(4);
+            vesting.report_price(11).unwrap();
+            assert_eq!(vesting.get_price_gate(accounts.bob, 1), Some((PriceGate { threshold: 10, observations: 2, twap_window: None }, true)));
+            set_caller(accounts.alice);
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap().len(), 1);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Liquid);
+        }
 
-                        // Recalculate balances
-                        Self::calculate_balances(vested_balance);
+        /// We test a TWAP gate is not opened by a single manipulated tick.
+        #[ink::test]
+        fn twap_price_gate_works() {
+            let (accounts, mut vesting) = setup();
 
-                        // Emit success event
-                        self.env().emit_event(VestingEvent {
-                            operator: caller,
-                            status: VestingStatus::EmitSuccess(Success::VestedBalanceScheduleRequested),
-                        });
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.set_price_oracle(Some(accounts.eve)).unwrap();
+            let price_gate = PriceGate { threshold: 10, observations: 2, twap_window: Some(100) };
+            vesting.set_price_gate(accounts.bob, 1, Some(price_gate)).unwrap();
 
-                    } else {
+            // Price 8 for most of the window, then a spike to 200
+            set_caller(accounts.eve);
+            for (time, price) in [(1_000, 8), (1_050, 8), (1_099, 200)] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(time);
+                vesting.report_price(price).unwrap();
+            }
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_100);
+            assert!(!vesting.get_price_gate(accounts.bob, 1).unwrap().1);
 
-                        // Schedule not liquid
-                        self.env().emit_event(VestingEvent {
-                            operator: caller,
-                            status: VestingStatus::EmitError(Error::VestedBalanceScheduleNotLiquid),
-                        });
+            // A sustained price above the threshold opens the gate
+            for time in [1_120, 1_150] {
+                ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(time);
+                vesting.report_price(12).unwrap();
+            }
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_200);
+            assert!(vesting.get_price_gate(accounts.bob, 1).unwrap().1);
 
-                    }
+            set_caller(accounts.alice);
+            assert_eq!(vesting.thaw_vested_balances(1, None).unwrap().len(), 1);
+        }
 
-                } else {
+        /// We test grants released along curves.
+        #[ink::test]
+        fn vesting_curves_work() {
+            let (accounts, mut vesting) = setup();
 
-                    // Schedule not found
-                    self.env().emit_event(VestingEvent {
-                        operator: caller,
-                        status: VestingStatus::EmitError(Error::VestedBalanceScheduleNotFound),
-                    });
+            let linear = VestingCurve::Linear { start: 0, duration: 300, steps: 3 };
+            let schedules = vesting.add_vested_balance_with_curve(accounts.bob, 100, linear.clone(), None).unwrap();
+            let shape: Vec<(u128, Option<Timestamp>)> = schedules.iter().map(|s| (s.schedule_balance, s.unlock_time)).collect();
+            assert_eq!(shape, vec![(33, Some(100)), (33, Some(200)), (34, Some(300))]);
+            assert_eq!(vesting.get_vesting_curve(accounts.bob), Some(linear));
 
-                }
+            let cliff = VestingCurve::CliffLinear { start: 0, cliff: 100, duration: 400, steps: 3 };
+            let schedules = vesting.add_vested_balance_with_curve(accounts.charlie, 400, cliff, None).unwrap();
+            let shape: Vec<(u128, Option<Timestamp>)> = schedules.iter().map(|s| (s.schedule_balance, s.unlock_time)).collect();
+            assert_eq!(shape, vec![(100, Some(100)), (100, Some(200)), (100, Some(300)), (100, Some(400))]);
 
-            } else {
+            let exponential = VestingCurve::Exponential { start: 0, interval: 10, steps: 3, rate_bps: 5_000 };
+            let schedules = vesting.add_vested_balance_with_curve(accounts.django, 100, exponential, None).unwrap();
+            let amounts: Vec<u128> = schedules.iter().map(|s| s.schedule_balance).collect();
+            assert_eq!(amounts, vec![50, 25, 25]);
 
-                // Caller has no vested balance
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::VestedBalanceNotFound),
-                });
+            // Piecewise points are interpolated, a flat segment unlocks nothing
+            let piecewise = VestingCurve::Piecewise { points: vec![(100, 2_000), (300, 6_000), (400, 6_000), (600, 10_000)], steps_per_segment: 2 };
+            let schedules = vesting.add_vested_balance_with_curve(accounts.frank, 1_000, piecewise, None).unwrap();
+            let shape: Vec<(u128, Option<Timestamp>)> = schedules.iter().map(|s| (s.schedule_balance, s.unlock_time)).collect();
+            assert_eq!(shape, vec![(200, Some(100)), (200, Some(200)), (200, Some(300)), (200, Some(500)), (200, Some(600))]);
 
+            for points in [vec![(100, 2_000), (300, 9_000)], vec![(100, 6_000), (300, 2_000), (400, 10_000)], vec![(100, 2_000), (100, 10_000)]] {
+                let invalid = VestingCurve::Piecewise { points, steps_per_segment: 2 };
+                vesting.add_vested_balance_with_curve(accounts.eve, 100, invalid, None).unwrap();
+                assert_error(Error::InvalidVestingCurve);
             }
 
-            Ok(())
+            let unordered = VestingCurve::Custom { points: vec![(20, 1), (10, 1)] };
+            assert_eq!(vesting.add_vested_balance_with_curve(accounts.eve, 100, unordered, None).unwrap(), Vec::new());
+            assert_error(Error::InvalidVestingCurve);
+            assert_eq!(vesting.get_vesting_curve(accounts.eve), None);
         }
 
-        /// Approve transfer
-        #[ink(message)]
-        pub fn approve_transfer(&mut self,
-            requesting_address: AccountId,
-            schedule_number: u8,
-            tx_hash: Vec<u8>) -> Result<(), Error> {
-            
-            // Check the caller, it must be the owner
-            let caller = self.env().caller();
-            if self.env().caller() != self.vesting_owner {
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::BadOrigin),
-                });
-                return Ok(());
-            }
+        /// We test pending requests become executable by a keeper once the owner stops approving.
+        #[ink::test]
+        fn dead_man_switch_works() {
+            let (accounts, mut vesting) = setup_with(0, 2);
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
 
-            if let Some(vested_balance) = self.vested_balances.iter_mut().find(|v| v.address == requesting_address) {
+            vesting.set_asset_kind(AssetKind::Native).unwrap();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 100);
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie, 0);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            vesting.thaw_vested_balances(1, None).unwrap();
 
-                // 2️. Find the schedule in the caller's vested_balance
-                if let Some(schedule) = vested_balance.vested_balance_schedules.iter_mut()
-                    .find(|s| s.schedule_number == schedule_number) {
+            set_caller(accounts.alice);
+            vesting.set_approval_tiers(vec![ApprovalTier { min_amount: 0, approvals: 2, guardian: false }]).unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
 
-                    // 3️. Ensure the schedule is requested
-                    if schedule.status == 2 {
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.execute_abandoned_request(accounts.bob, 1).unwrap();
+            assert_error(Error::RequestNotExecutable { executable_at: None });
 
-                        // Update the schedule
-                        schedule.status = 3;                    // Requested
-                        schedule.particulars = tx_hash;         // Tx-hash
+            // The switch counts from the request when it is later than the last approval
+            set_caller(accounts.alice);
+            vesting.set_dead_man_period(Some(3)).unwrap();
+            set_caller(accounts.django);
+            vesting.execute_abandoned_request(accounts.bob, 1).unwrap();
+            assert_error(Error::RequestNotExecutable { executable_at: Some(4) });
 
-                        // Recalculate balances
-                        Self::calculate_balances(vested_balance);
+            for _ in 0..3 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
 
-                        // Emit success event
-                        self.env().emit_event(VestingEvent {
-                            operator: caller,
-                            status: VestingStatus::EmitSuccess(Success::VestedBalanceScheduleApproved),
-                        });
+            // The switch stands in for a single approval of the tier
+            vesting.execute_abandoned_request(accounts.bob, 1).unwrap();
+            assert_error(Error::ApprovalsMissing { address: accounts.bob, schedule_number: 1 });
+            set_caller(accounts.alice);
+            vesting.approve_transfer(accounts.bob, 1, vec![], None).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Requested);
+            vesting.last_approval_block = 0;
+            set_caller(accounts.django);
+            vesting.execute_abandoned_request(accounts.bob, 1).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Transferred);
+            assert_eq!(
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.charlie),
+                Ok(50)
+            );
 
-                    } else {
+            // Executions without the owner do not reset the switch
+            assert_eq!(vesting.get_dead_man_switch(), (Some(3), 0));
 
-                        // Schedule not liquid
-                        self.env().emit_event(VestingEvent {
-                            operator: caller,
-                            status: VestingStatus::EmitError(Error::VestedBalanceScheduleNotRequested),
-                        });
+            // Requests are verified first when a compliance operator is set
+            set_caller(accounts.alice);
+            vesting.set_approval_tiers(Vec::new()).unwrap();
+            vesting.set_compliance_operator(Some(accounts.eve)).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            let request_id = vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap().unwrap();
+            vesting.last_approval_block = 0;
+            for _ in 0..3 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            set_caller(accounts.django);
+            vesting.execute_abandoned_request(accounts.bob, 2).unwrap();
+            assert_error(Error::RequestNotVerified { request_id });
+            set_caller(accounts.eve);
+            vesting.mark_verified(request_id).unwrap();
+            set_caller(accounts.django);
+            vesting.execute_abandoned_request(accounts.bob, 2).unwrap();
+            assert_eq!(vesting.get_schedule(accounts.bob, 2).unwrap().status, ScheduleStatus::Transferred);
+        }
 
-                    }
+        /// We test a transfer request to the beneficiary's own address.
+        #[ink::test]
+        fn request_transfer_to_self_works() {
+            let (accounts, mut vesting) = setup();
 
-                } else {
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
 
-                    // Schedule not found
-                    self.env().emit_event(VestingEvent {
-                        operator: caller,
-                        status: VestingStatus::EmitError(Error::VestedBalanceScheduleNotFound),
-                    });
+            set_caller(accounts.bob);
+            let request_id = vesting.request_transfer_to_self(1).unwrap().unwrap();
+            let request = vesting.get_request(request_id).unwrap();
+            assert_eq!(request.recipient_address, accounts.bob);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().recipient_address, Some(accounts.bob));
 
-                }
+            assert_eq!(vesting.request_transfer_to_self(2).unwrap(), None);
+            assert_error(Error::VestedBalanceScheduleNotLiquid { address: accounts.bob, schedule_number: 2, status: ScheduleStatus::Frozen });
+        }
 
-            } else {
+        /// We test the schedule numbers are kept within the configured maximum.
+        #[ink::test]
+        fn max_schedule_number_works() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert_eq!(
+                Vesting::new(1, 101).err(),
+                Some(Error::ScheduleNumberTooHigh { schedule_number: 101, max_schedule_number: 100 })
+            );
+            assert_eq!(Vesting::new(1, 0).err(), Some(Error::InvalidScheduleCount { total_vested_schedule: 0 }));
 
-                // Caller has no vested balance
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::VestedBalanceNotFound),
-                });
+            let mut vesting = Vesting::new(1, 2).unwrap();
+            assert_eq!(vesting.get_max_schedule_number(), DEFAULT_MAX_SCHEDULE_NUMBER);
+            vesting.set_default_schedule_count(101).unwrap();
+            assert_error(Error::ScheduleNumberTooHigh { schedule_number: 101, max_schedule_number: 100 });
 
-            }            
+            // The maximum cannot go below the schedule count
+            vesting.set_max_schedule_number(1).unwrap();
+            assert_error(Error::ScheduleNumberTooHigh { schedule_number: 2, max_schedule_number: 1 });
+            vesting.set_max_schedule_number(3).unwrap();
+            vesting.set_default_schedule_count(4).unwrap();
+            assert_error(Error::ScheduleNumberTooHigh { schedule_number: 4, max_schedule_number: 3 });
 
-            Ok(())
+            // Donations cannot number a schedule past the maximum
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.add_vested_balance(accounts.charlie, 100, None).unwrap();
+            vesting.set_donations_enabled(true).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(vesting.donate_schedule(1, accounts.charlie).unwrap(), Some(3));
+            assert_eq!(vesting.donate_schedule(2, accounts.charlie).unwrap(), None);
+            assert_error(Error::ScheduleLimitReached { address: accounts.charlie });
         }
 
-        /// Removes the balance and its schedules regardless of the status
-        #[ink(message)]
-        pub fn remove_vested_balance(&mut self,
-            address: AccountId,) -> Result<(), Error> {
+        /// We test the state version changes with successful operations only.
+        #[ink::test]
+        fn state_version_works() {
+            let (accounts, mut vesting) = setup();
 
-            // Check the caller, it must be the owner
-            let caller = self.env().caller();
-            if self.env().caller() != self.vesting_owner {
-                self.env().emit_event(VestingEvent {
-                    operator: caller,
-                    status: VestingStatus::EmitError(Error::BadOrigin),
-                });
-                return Ok(());
-            } 
+            let version = vesting.get_state_version();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            let bob_version = vesting.get_balances_for(vec![accounts.bob])[0].as_ref().unwrap().version;
+            assert!(vesting.get_state_version() > version);
+            assert_eq!(vesting.get_totals().state_version, vesting.get_state_version());
 
-            let index = match self
-                .vested_balances
-                .iter()
-                .position(|v| v.address == address)
-            {
-                Some(i) => i,
-                None => {
-                    self.env().emit_event(VestingEvent {
-                        operator: caller,
-                        status: VestingStatus::EmitError(Error::VestedBalanceNotFound),
-                    });
-                    return Ok(());
-                }
-            };
+            // Rejected operations change nothing
+            let version = vesting.get_state_version();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            assert_eq!(vesting.health().state_version, version);
 
-            self.vested_balances.swap_remove(index);
+            set_caller(accounts.alice);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            assert!(vesting.get_state_version() > version);
+            assert!(vesting.get_balances_for(vec![accounts.bob])[0].as_ref().unwrap().version > bob_version);
+        }
 
-            self.env().emit_event(VestingEvent {
-                operator: caller,
-                status: VestingStatus::EmitSuccess(Success::VestedBalanceRemoved),
-            });
+        /// We test strict mode is reflected in the vesting information.
+        #[ink::test]
+        fn strict_mode_is_set() {
+            let (accounts, mut vesting) = setup();
+            assert!(!vesting.get_vesting_info().3);
 
-            Ok(())
+            set_caller(accounts.bob);
+            vesting.set_strict_mode(true).unwrap();
+            assert_error(Error::BadOrigin);
+
+            set_caller(accounts.alice);
+            vesting.set_strict_mode(true).unwrap();
+            assert!(vesting.get_vesting_info().3);
         }
-        
-        /// Helper function to calculate balances
-        fn calculate_balances(vested_balance: &mut VestedBalance) {
-            vested_balance.frozen_balance = 0;
-            vested_balance.liquid_balance = 0;
-            vested_balance.requested_balance = 0;
-            vested_balance.transferred_balance = 0;
 
-            for schedule in vested_balance.vested_balance_schedules.iter() {
-                match schedule.status {
-                    0 => vested_balance.frozen_balance += schedule.schedule_balance,
-                    1 => vested_balance.liquid_balance += schedule.schedule_balance,
-                    2 => vested_balance.requested_balance += schedule.schedule_balance,
-                    3 => vested_balance.transferred_balance += schedule.schedule_balance,
-                    _ => {}, // status 1 = Liquid, ignored
-                }
-            }
+        /// We test an invalid operation in strict mode returns its error instead of emitting it.
+        #[ink::test]
+        fn strict_mode_returns_errors() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.set_strict_mode(true).unwrap();
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            let events = ink::env::test::recorded_events().count();
+            assert_eq!(
+                vesting.add_vested_balance(accounts.bob, 100, None),
+                Err(Error::VestedBalanceAlreadyExist { address: accounts.bob })
+            );
+            assert_eq!(ink::env::test::recorded_events().count(), events);
+
+            set_caller(accounts.bob);
+            assert_eq!(vesting.thaw_vested_balances(1, None), Err(Error::BadOrigin));
+            assert_eq!(vesting.request_transfer(1, accounts.charlie, Vec::new()), Err(Error::VestedBalanceScheduleNotLiquid {
+                address: accounts.bob,
+                schedule_number: 1,
+                status: ScheduleStatus::Frozen,
+            }));
         }
-    }
 
-    /// Unit tests
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
+        /// We test a bad item of a batch in strict mode fails the whole batch.
+        #[ink::test]
+        fn strict_mode_fails_batches() {
+            let (accounts, mut vesting) = setup();
 
-        /// We test if the default constructor does its job.
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            let results = vesting.add_vested_balances(vec![(accounts.charlie, 100), (accounts.bob, 100)], None).unwrap();
+            assert!(results[1].result.is_err());
+
+            vesting.set_strict_mode(true).unwrap();
+            assert_eq!(
+                vesting.add_vested_balances(vec![(accounts.django, 100), (accounts.bob, 100)], None),
+                Err(Error::VestedBalanceAlreadyExist { address: accounts.bob })
+            );
+        }
+
+        /// We test a native batch is funded with the sum of its grants and reverts on a bad item.
         #[ink::test]
-        fn default_works() {
-            let vesting = Vesting::default();
+        fn native_batch_add_works() {
+            let (accounts, mut vesting) = setup_with(0, 2);
+
+            vesting.set_asset_kind(AssetKind::Native).unwrap();
+            assert_eq!(
+                vesting.add_vested_balances(vec![(accounts.bob, 100)], None),
+                Err(Error::FundingMismatch { expected: 100, transferred: 0 })
+            );
+
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(140);
+            assert_eq!(
+                vesting.add_vested_balances(vec![(accounts.bob, 100), (accounts.charlie, 60)], None),
+                Err(Error::FundingMismatch { expected: 160, transferred: 140 })
+            );
+            let results = vesting.add_vested_balances(vec![(accounts.bob, 100), (accounts.charlie, 40)], None).unwrap();
+            assert!(results.iter().all(|r| r.result == Ok(Success::VestedBalanceAdded)));
+            assert_eq!(vesting.get_native_info(), (AssetKind::Native, 140));
+
+            // A funded item cannot be refused alone
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(150);
+            assert_eq!(
+                vesting.add_vested_balances(vec![(accounts.django, 50), (accounts.bob, 100)], None),
+                Err(Error::VestedBalanceAlreadyExist { address: accounts.bob })
+            );
+        }
+
+        /// We test a failed native payout in strict mode is reported and stays queued for a retry.
+        #[ink::test]
+        fn strict_mode_keeps_failed_payouts() {
+            let (accounts, mut vesting) = setup_with(0, 2);
+            let contract = ink::env::test::callee::<ink::env::DefaultEnvironment>();
+
+            vesting.set_asset_kind(AssetKind::Native).unwrap();
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(contract, 100);
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(100);
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.set_strict_mode(true).unwrap();
+            vesting.native_pool = 30;
+            assert_eq!(vesting.approve_transfer(accounts.bob, 1, vec![], None), Ok(()));
+            assert_error(Error::InsufficientNativeEscrow { available: 30, required: 50 });
+            assert_eq!(vesting.approve_payout(accounts.bob, 2, None), Ok(None));
+            assert_error(Error::InsufficientNativeEscrow { available: 30, required: 50 });
+
+            let stuck = vesting.get_stuck_payouts();
+            assert_eq!(stuck.len(), 2);
+            assert_eq!(vesting.get_schedule(accounts.bob, 1).unwrap().status, ScheduleStatus::Requested);
         }
 
+        /// We test the bulk acknowledgement of payouts.
+        #[ink::test]
+        fn ack_payouts_works() {
+            let (accounts, mut vesting) = setup();
+
+            vesting.add_vested_balance(accounts.bob, 100, None).unwrap();
+            vesting.thaw_vested_balances(1, None).unwrap();
+            vesting.thaw_vested_balances(2, None).unwrap();
+
+            set_caller(accounts.bob);
+            vesting.request_transfer(1, accounts.charlie, Vec::new()).unwrap();
+            vesting.request_transfer(2, accounts.charlie, Vec::new()).unwrap();
+
+            set_caller(accounts.alice);
+            vesting.approve_payout(accounts.bob, 1, None).unwrap();
+            vesting.approve_payout(accounts.bob, 2, None).unwrap();
+
+            // Payout 3 does not exist and must not stop the others
+            let results = vesting.ack_payouts(vec![(1, vec![1]), (3, vec![3]), (2, vec![2])], None).unwrap();
+            assert_eq!(results[1], BatchItemResult { index: 1, result: Err(Error::PayoutNotFound { payout_id: 3 }) });
+            assert_eq!(results[2], BatchItemResult { index: 2, result: Ok(Success::PayoutAcknowledged) });
+
+            let vested_balance = vesting.get_vested_balance(accounts.bob).unwrap();
+            assert_eq!(vested_balance.vested_balance_schedules[0].particulars, vec![1]);
+            assert_eq!(vested_balance.vested_balance_schedules[1].particulars, vec![2]);
+            assert_eq!(vested_balance.transferred_balance, 100);
+        }
     }
 
 
@@ -500,63 +11659,286 @@ mod vesting {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;
 
-        /// A helper function used for calling contract messages.
-        use ink_e2e::build_message;
+        /// A helper function used for calling contract messages. `ink_e2e::build_message` only builds
+        /// messages of the default environment
+        fn build_message<Ref>(account_id: AccountId) -> ink_e2e::MessageBuilder<crate::VestingEnvironment, Ref>
+        where
+            Ref: ink::codegen::TraitCallBuilder + ink::env::call::FromAccountId<crate::VestingEnvironment>,
+        {
+            ink_e2e::MessageBuilder::from_account_id(account_id)
+        }
+
+        /// Arguments of the runtime calls into pallet-assets.
+        use ink_e2e::subxt::dynamic::Value;
 
         /// The End-to-End test `Result` type.
         type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-        /// We test that we can upload and instantiate the contract using its default constructor.
-        #[ink_e2e::test]
-        async fn default_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            // Given
-            let constructor = VestingRef::default();
+        /// Events of the contract, as emitted through `Contracts::ContractEmitted`
+        type Event = <Vesting as ::ink::reflect::ContractEventBase>::Type;
+
+        /// Account id of a keyring account
+        fn account(keyring: ink_e2e::AccountKeyring) -> AccountId {
+            ink_e2e::account_id(keyring)
+        }
+
+        /// We test a grant from addition to an approved transfer on a node, off-chain asset payout.
+        #[ink_e2e::test(environment = crate::VestingEnvironment)]
+        async fn asset_lifecycle_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let bob = account(ink_e2e::AccountKeyring::Bob);
+            let charlie = account(ink_e2e::AccountKeyring::Charlie);
+
+            let constructor = VestingRef::new(1, 2);
+            let contract_account_id = client
+                .instantiate("vesting", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let add = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.add_vested_balance(bob, 100, None));
+            client.call(&ink_e2e::alice(), add, 0, None).await.expect("add failed");
+
+            let thaw = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.thaw_vested_balances(1, None));
+            client.call(&ink_e2e::alice(), thaw, 0, None).await.expect("thaw failed");
+
+            let request = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.request_transfer(1, charlie, Vec::new()));
+            client.call(&ink_e2e::bob(), request, 0, None).await.expect("request failed");
+
+            let approve = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.approve_transfer(bob, 1, vec![1], None));
+            client.call(&ink_e2e::alice(), approve, 0, None).await.expect("approve failed");
 
-            // When
+            let get = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.get_vested_balance(bob));
+            let vested_balance = client.call_dry_run(&ink_e2e::alice(), &get, 0, None).await
+                .return_value()
+                .expect("vested balance not found");
+            assert_eq!(vested_balance.transferred_balance, 50);
+            assert_eq!(vested_balance.frozen_balance, 50);
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+            assert_eq!(vested_balance.vested_balance_schedules[0].recipient_address, Some(charlie));
+
+            let find = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.find_by_tx_hash(vec![1]));
+            let found = client.call_dry_run(&ink_e2e::alice(), &find, 0, None).await.return_value();
+            assert_eq!(found, Some((bob, 1)));
+
+            Ok(())
+        }
+
+        /// We test an asset payout on a node with pallet-assets: the treasury pays the payout instruction emitted
+        /// by the approval and acknowledges it with the hash of the transfer, as the payout bot does.
+        #[ink_e2e::test(environment = crate::VestingEnvironment)]
+        async fn asset_payout_moves_tokens(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let alice = account(ink_e2e::AccountKeyring::Alice);
+            let bob = account(ink_e2e::AccountKeyring::Bob);
+            let charlie = account(ink_e2e::AccountKeyring::Charlie);
+            let dave = account(ink_e2e::AccountKeyring::Dave);
+            let asset_id: u128 = 7;
+
+            // The treasury holds the vested asset
+            client.runtime_call(&ink_e2e::alice(), "Assets", "create", vec![
+                Value::u128(asset_id),
+                Value::unnamed_variant("Id", vec![Value::from_bytes(alice)]),
+                Value::u128(1),
+            ]).await.expect("create asset failed");
+            client.runtime_call(&ink_e2e::alice(), "Assets", "mint", vec![
+                Value::u128(asset_id),
+                Value::unnamed_variant("Id", vec![Value::from_bytes(dave)]),
+                Value::u128(1_000),
+            ]).await.expect("mint failed");
+
+            let constructor = VestingRef::new(asset_id, 2);
             let contract_account_id = client
                 .instantiate("vesting", &ink_e2e::alice(), constructor, 0, None)
                 .await
                 .expect("instantiate failed")
                 .account_id;
 
-            // Then
-            let get = build_message::<VestingRef>(contract_account_id.clone())
-                .call(|web3_asset_vesting| web3_asset_vesting.get());
-            let get_result = client.call_dry_run(&ink_e2e::alice(), &get, 0, None).await;
-            assert!(matches!(get_result.return_value(), false));
+            let set_treasury = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.set_treasury(Some(dave)));
+            client.call(&ink_e2e::alice(), set_treasury, 0, None).await.expect("set treasury failed");
+
+            let add = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.add_vested_balance(bob, 100, None));
+            client.call(&ink_e2e::alice(), add, 0, None).await.expect("add failed");
+
+            let thaw = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.thaw_vested_balances(1, None));
+            client.call(&ink_e2e::alice(), thaw, 0, None).await.expect("thaw failed");
+
+            let request = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.request_transfer(1, charlie, Vec::new()));
+            client.call(&ink_e2e::bob(), request, 0, None).await.expect("request failed");
+
+            let approve = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.approve_payout(bob, 1, None));
+            let approved = client.call(&ink_e2e::alice(), approve, 0, None).await.expect("approve failed");
+
+            // The payout bot reads the instruction from the contract events
+            let instruction = approved.events.iter()
+                .filter_map(|event| event.ok())
+                .filter(|event| event.pallet_name() == "Contracts" && event.variant_name() == "ContractEmitted")
+                .filter_map(|event| {
+                    let (_, data) = <(AccountId, Vec<u8>) as scale::Decode>::decode(&mut event.field_bytes()).ok()?;
+                    match <Event as scale::Decode>::decode(&mut &data[..]) {
+                        Ok(Event::PayoutInstruction(instruction)) => Some(instruction),
+                        _ => None,
+                    }
+                })
+                .next()
+                .expect("payout instruction not found");
+            assert_eq!(Some(instruction.payout_id), approved.return_value().expect("approve reverted"));
+            assert_eq!((instruction.asset_id, instruction.recipient), (asset_id, Recipient::Account(charlie)));
+
+            // The treasury pays the instruction, the tokens reach the recipient
+            let transfer = client.runtime_call(&ink_e2e::dave(), "Assets", "transfer", vec![
+                Value::u128(asset_id),
+                Value::unnamed_variant("Id", vec![Value::from_bytes(charlie)]),
+                Value::u128(instruction.amount),
+            ]).await.expect("transfer failed");
+            let transferred = transfer.iter()
+                .filter_map(|event| event.ok())
+                .find(|event| event.pallet_name() == "Assets" && event.variant_name() == "Transferred")
+                .expect("transfer event not found");
+            let (paid_asset, from, to, amount) =
+                <(u32, AccountId, AccountId, Balance) as scale::Decode>::decode(&mut transferred.field_bytes())
+                    .expect("transfer event not decoded");
+            assert_eq!((paid_asset as u128, from, to, amount), (asset_id, dave, charlie, instruction.amount));
+
+            // The recipient holds the net amount of the instruction. The e2e client has no storage queries, the
+            // admin burning more than the recipient holds reports its whole balance
+            let burn = client.runtime_call(&ink_e2e::alice(), "Assets", "burn", vec![
+                Value::u128(asset_id),
+                Value::unnamed_variant("Id", vec![Value::from_bytes(charlie)]),
+                Value::u128(Balance::MAX),
+            ]).await.expect("burn failed");
+            let burned = burn.iter()
+                .filter_map(|event| event.ok())
+                .find(|event| event.pallet_name() == "Assets" && event.variant_name() == "Burned")
+                .expect("burn event not found");
+            let (_, owner, balance) = <(u32, AccountId, Balance) as scale::Decode>::decode(&mut burned.field_bytes())
+                .expect("burn event not decoded");
+            assert_eq!((owner, balance), (charlie, instruction.amount));
+
+            let tx_hash = transfer.extrinsic_hash().as_ref().to_vec();
+            let ack = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.ack_payout(instruction.payout_id, tx_hash.clone(), None));
+            client.call(&ink_e2e::alice(), ack, 0, None).await.expect("ack failed");
+
+            let get = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.get_vested_balance(bob));
+            let vested_balance = client.call_dry_run(&ink_e2e::alice(), &get, 0, None).await
+                .return_value()
+                .expect("vested balance not found");
+            assert_eq!(vested_balance.transferred_balance, instruction.amount);
+            assert_eq!(vested_balance.vested_balance_schedules[0].status, ScheduleStatus::Transferred);
+
+            let find = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.find_by_tx_hash(tx_hash.clone()));
+            let found = client.call_dry_run(&ink_e2e::alice(), &find, 0, None).await.return_value();
+            assert_eq!(found, Some((bob, 1)));
 
             Ok(())
         }
 
-        /// We test that we can read and write a value from the on-chain contract contract.
-        #[ink_e2e::test]
-        async fn it_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            // Given
-            let constructor = VestingRef::new(false);
+        /// We test that a native grant is escrowed and the approval pays the recipient.
+        #[ink_e2e::test(environment = crate::VestingEnvironment)]
+        async fn native_payout_moves_balances(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let bob = account(ink_e2e::AccountKeyring::Bob);
+            let charlie = account(ink_e2e::AccountKeyring::Charlie);
+            let amount: Balance = 1_000_000_000;
+
+            let constructor = VestingRef::new(0, 2);
             let contract_account_id = client
-                .instantiate("vesting", &ink_e2e::bob(), constructor, 0, None)
+                .instantiate("vesting", &ink_e2e::alice(), constructor, 0, None)
                 .await
                 .expect("instantiate failed")
                 .account_id;
 
-            let get = build_message::<VestingRef>(contract_account_id.clone())
-                .call(|web3_asset_vesting| web3_asset_vesting.get());
-            let get_result = client.call_dry_run(&ink_e2e::bob(), &get, 0, None).await;
-            assert!(matches!(get_result.return_value(), false));
+            let set_kind = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.set_asset_kind(AssetKind::Native));
+            client.call(&ink_e2e::alice(), set_kind, 0, None).await.expect("set asset kind failed");
+
+            // The grant is funded in the same call
+            let contract_before = client.balance(contract_account_id).await.expect("balance failed");
+            let add = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.add_vested_balance(bob, amount, None));
+            client.call(&ink_e2e::alice(), add, amount, None).await.expect("add failed");
+            let contract_after = client.balance(contract_account_id).await.expect("balance failed");
+            assert_eq!(contract_after, contract_before + amount);
+
+            let thaw = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.thaw_vested_balances(1, None));
+            client.call(&ink_e2e::alice(), thaw, 0, None).await.expect("thaw failed");
+
+            let request = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.request_transfer(1, charlie, Vec::new()));
+            client.call(&ink_e2e::bob(), request, 0, None).await.expect("request failed");
+
+            let charlie_before = client.balance(charlie).await.expect("balance failed");
+            let approve = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.approve_transfer(bob, 1, vec![1], None));
+            client.call(&ink_e2e::alice(), approve, 0, None).await.expect("approve failed");
 
-            // When
-            let flip = build_message::<VestingRef>(contract_account_id.clone())
-                .call(|web3_asset_vesting| web3_asset_vesting.flip());
-            let _flip_result = client
-                .call(&ink_e2e::bob(), flip, 0, None)
+            assert_eq!(client.balance(charlie).await.expect("balance failed"), charlie_before + amount / 2);
+            assert_eq!(client.balance(contract_account_id).await.expect("balance failed"), contract_after - amount / 2);
+
+            let info = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.get_native_info());
+            let native_info = client.call_dry_run(&ink_e2e::alice(), &info, 0, None).await.return_value();
+            assert_eq!(native_info, (AssetKind::Native, amount / 2));
+
+            Ok(())
+        }
+
+        /// We test a failing transfer callback in strict mode neither reverts nor blocks the approval.
+        /// The recipient registers a callback but is no contract, so the callback cannot be called
+        #[ink_e2e::test(environment = crate::VestingEnvironment)]
+        async fn strict_mode_keeps_failed_callbacks(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let bob = account(ink_e2e::AccountKeyring::Bob);
+            let charlie = account(ink_e2e::AccountKeyring::Charlie);
+
+            let constructor = VestingRef::new(1, 2);
+            let contract_account_id = client
+                .instantiate("vesting", &ink_e2e::alice(), constructor, 0, None)
                 .await
-                .expect("flip failed");
+                .expect("instantiate failed")
+                .account_id;
+
+            let strict = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.set_strict_mode(true));
+            client.call(&ink_e2e::alice(), strict, 0, None).await.expect("set strict mode failed");
+
+            let add = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.add_vested_balance(bob, 100, None));
+            client.call(&ink_e2e::alice(), add, 0, None).await.expect("add failed");
+
+            let thaw = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.thaw_vested_balances(1, None));
+            client.call(&ink_e2e::alice(), thaw, 0, None).await.expect("thaw failed");
+
+            let register = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.register_transfer_callback(Some(ink::selector_bytes!("on_vested_transfer"))));
+            client.call(&ink_e2e::charlie(), register, 0, None).await.expect("register failed");
+
+            let request = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.request_transfer(1, charlie, Vec::new()));
+            client.call(&ink_e2e::bob(), request, 0, None).await.expect("request failed");
+
+            let approve = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.approve_transfer(bob, 1, vec![1], None));
+            client.call(&ink_e2e::alice(), approve, 0, None).await.expect("approve failed");
 
-            // Then
-            let get = build_message::<VestingRef>(contract_account_id.clone())
-                .call(|web3_asset_vesting| web3_asset_vesting.get());
-            let get_result = client.call_dry_run(&ink_e2e::bob(), &get, 0, None).await;
-            assert!(matches!(get_result.return_value(), true));
+            let get = build_message::<VestingRef>(contract_account_id)
+                .call(|vesting| vesting.get_schedule(bob, 1));
+            let schedule = client.call_dry_run(&ink_e2e::alice(), &get, 0, None).await
+                .return_value()
+                .expect("schedule not found");
+            assert_eq!(schedule.status, ScheduleStatus::Transferred);
 
             Ok(())
         }
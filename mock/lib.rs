@@ -0,0 +1,230 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// Contract standing in for the recipients and compliance hooks the vesting contract calls out to,
+/// used by the end-to-end tests only
+#[ink::contract(env = vesting::VestingEnvironment)]
+mod vesting_mock {
+    use ink::codegen::TraitCallBuilder;
+    use ink::env::call::FromAccountId;
+    use vesting::VestingRef;
+
+    #[ink(storage)]
+    pub struct VestingMock {
+        /// Vesting contract the mock is registered with
+        vesting: AccountId,
+        /// Outcome of the last call back into the vesting contract from a transfer callback
+        reentered: Option<bool>,
+        /// Answer of the compliance hook
+        allowed: bool,
+        /// Make the compliance hook trap instead of answering
+        failing: bool,
+    }
+
+    impl VestingMock {
+        #[ink(constructor)]
+        pub fn new(vesting: AccountId) -> Self {
+            Self {
+                vesting,
+                reentered: None,
+                allowed: true,
+                failing: false,
+            }
+        }
+
+        /// Register `on_vested_transfer` as the transfer callback of the mock
+        #[ink(message)]
+        pub fn register(&mut self,) {
+            let mut vesting: VestingRef = FromAccountId::from_account_id(self.vesting);
+            vesting
+                .register_transfer_callback(Some(ink::selector_bytes!("on_vested_transfer")))
+                .expect("register failed");
+        }
+
+        /// Transfer callback, tries to call back into the vesting contract and records whether it got in
+        #[ink(message)]
+        pub fn on_vested_transfer(&mut self,
+            _beneficiary: AccountId,
+            _amount: u128,
+            _asset_id: u128,) {
+
+            let mut vesting: VestingRef = FromAccountId::from_account_id(self.vesting);
+            let result = vesting.call_mut().process_due_unlocks(1).try_invoke();
+            self.reentered = Some(matches!(result, Ok(Ok(Ok(_)))));
+        }
+
+        /// Get the outcome of the last call back into the vesting contract, none if no callback ran
+        #[ink(message)]
+        pub fn get_reentered(&self,) -> Option<bool> {
+            self.reentered
+        }
+
+        /// Set the answer of the compliance hook, or make it trap
+        #[ink(message)]
+        pub fn set_answer(&mut self,
+            allowed: bool,
+            failing: bool,) {
+            self.allowed = allowed;
+            self.failing = failing;
+        }
+
+        /// Compliance hook, answers the configured answer or traps when failing
+        #[ink(message)]
+        pub fn is_allowed(&self,
+            _beneficiary: AccountId,
+            _recipient: AccountId,
+            _amount: u128,) -> bool {
+
+            if self.failing {
+                panic!("compliance hook failed");
+            }
+            self.allowed
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use vesting::ScheduleStatus;
+
+        /// A helper function used for calling contract messages. `ink_e2e::build_message` only builds
+        /// messages of the default environment
+        fn build_message<Ref>(account_id: AccountId) -> ink_e2e::MessageBuilder<vesting::VestingEnvironment, Ref>
+        where
+            Ref: TraitCallBuilder + FromAccountId<vesting::VestingEnvironment>,
+        {
+            ink_e2e::MessageBuilder::from_account_id(account_id)
+        }
+
+        /// The End-to-End test `Result` type.
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        /// Account id of a keyring account
+        fn account(keyring: ink_e2e::AccountKeyring) -> AccountId {
+            ink_e2e::account_id(keyring)
+        }
+
+        /// We test a transfer callback cannot re-enter the vesting contract, the approval still goes through.
+        #[ink_e2e::test(environment = vesting::VestingEnvironment)]
+        async fn reentrant_call_is_rejected(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let bob = account(ink_e2e::AccountKeyring::Bob);
+
+            let constructor = VestingRef::new(1, 2);
+            let vesting_account_id = client
+                .instantiate("vesting", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let constructor = VestingMockRef::new(vesting_account_id);
+            let mock_account_id = client
+                .instantiate("vesting_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let register = build_message::<VestingMockRef>(mock_account_id)
+                .call(|mock| mock.register());
+            client.call(&ink_e2e::alice(), register, 0, None).await.expect("register failed");
+
+            let add = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.add_vested_balance(bob, 100, None));
+            client.call(&ink_e2e::alice(), add, 0, None).await.expect("add failed");
+
+            let thaw = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.thaw_vested_balances(1, None));
+            client.call(&ink_e2e::alice(), thaw, 0, None).await.expect("thaw failed");
+
+            let request = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.request_transfer(1, mock_account_id, Vec::new()));
+            client.call(&ink_e2e::bob(), request, 0, None).await.expect("request failed");
+
+            let approve = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.approve_transfer(bob, 1, vec![1], None));
+            client.call(&ink_e2e::alice(), approve, 0, None).await.expect("approve failed");
+
+            // The callback ran but could not get back in
+            let reentered = build_message::<VestingMockRef>(mock_account_id)
+                .call(|mock| mock.get_reentered());
+            let reentered = client.call_dry_run(&ink_e2e::alice(), &reentered, 0, None).await.return_value();
+            assert_eq!(reentered, Some(false));
+
+            let get = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.get_schedule(bob, 1));
+            let schedule = client.call_dry_run(&ink_e2e::alice(), &get, 0, None).await
+                .return_value()
+                .expect("schedule not found");
+            assert_eq!(schedule.status, ScheduleStatus::Transferred);
+
+            Ok(())
+        }
+
+        /// We test approvals follow the compliance hook: allowed transfers go through, denied ones and those the hook
+        /// fails on stay requested.
+        #[ink_e2e::test(environment = vesting::VestingEnvironment)]
+        async fn compliance_hook_decides_approvals(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let bob = account(ink_e2e::AccountKeyring::Bob);
+            let charlie = account(ink_e2e::AccountKeyring::Charlie);
+
+            let constructor = VestingRef::new(1, 3);
+            let vesting_account_id = client
+                .instantiate("vesting", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let constructor = VestingMockRef::new(vesting_account_id);
+            let mock_account_id = client
+                .instantiate("vesting_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let hook = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.set_compliance_hook(Some(mock_account_id)));
+            client.call(&ink_e2e::alice(), hook, 0, None).await.expect("set hook failed");
+
+            let add = build_message::<VestingRef>(vesting_account_id)
+                .call(|vesting| vesting.add_vested_balance(bob, 300, None));
+            client.call(&ink_e2e::alice(), add, 0, None).await.expect("add failed");
+
+            // Each answer of the hook is tried on its own schedule. The hook allows the request, which it is
+            // also asked about, and the answer under test then decides the approval
+            let answers = [
+                (false, true, ScheduleStatus::Requested),
+                (false, false, ScheduleStatus::Requested),
+                (true, false, ScheduleStatus::Transferred),
+            ];
+            for (schedule_number, (allowed, failing, status)) in (1u16..).zip(answers) {
+                let answer = build_message::<VestingMockRef>(mock_account_id)
+                    .call(|mock| mock.set_answer(true, false));
+                client.call(&ink_e2e::alice(), answer, 0, None).await.expect("set answer failed");
+
+                let thaw = build_message::<VestingRef>(vesting_account_id)
+                    .call(|vesting| vesting.thaw_vested_balances(schedule_number, None));
+                client.call(&ink_e2e::alice(), thaw, 0, None).await.expect("thaw failed");
+
+                let request = build_message::<VestingRef>(vesting_account_id)
+                    .call(|vesting| vesting.request_transfer(schedule_number, charlie, Vec::new()));
+                client.call(&ink_e2e::bob(), request, 0, None).await.expect("request failed");
+
+                let answer = build_message::<VestingMockRef>(mock_account_id)
+                    .call(|mock| mock.set_answer(allowed, failing));
+                client.call(&ink_e2e::alice(), answer, 0, None).await.expect("set answer failed");
+
+                let approve = build_message::<VestingRef>(vesting_account_id)
+                    .call(|vesting| vesting.approve_transfer(bob, schedule_number, vec![schedule_number as u8], None));
+                client.call(&ink_e2e::alice(), approve, 0, None).await.expect("approve failed");
+
+                let get = build_message::<VestingRef>(vesting_account_id)
+                    .call(|vesting| vesting.get_schedule(bob, schedule_number));
+                let schedule = client.call_dry_run(&ink_e2e::alice(), &get, 0, None).await
+                    .return_value()
+                    .expect("schedule not found");
+                assert_eq!(schedule.status, status);
+            }
+
+            Ok(())
+        }
+    }
+}